@@ -0,0 +1,150 @@
+use crate::config::ModifiersConfig;
+use crate::session::{SessionEvent, SessionRecord};
+use crate::{Modifiers, key_to_string};
+use rdev::{EventType, Key};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+// Aggregate statistics over one or more recorded sessions
+#[derive(Serialize, Default)]
+struct SessionStats {
+    sessions: usize,
+    events: u64,
+    key_presses: u64,
+    clicks: u64,
+    duration_secs: u64,
+    // Most used combos, most frequent first
+    top_combos: Vec<(String, u64)>,
+    // Key presses in each hour since the start of a session
+    per_hour: Vec<u64>,
+    // Fraction of key presses made with each modifier held
+    modifier_share: BTreeMap<String, f64>,
+}
+
+// Print statistics for the given session files, and optionally write them as JSON
+pub fn run(paths: &[String], json: Option<&Path>) -> Result<(), String> {
+    let mut stats = SessionStats::default();
+    let mut combos: HashMap<String, u64> = HashMap::new();
+    let mut modifier_counts: BTreeMap<String, u64> = BTreeMap::new();
+
+    for path in paths {
+        let file = File::open(path).map_err(|e| format!("{}: {}", path, e))?;
+        let mut modifiers = Modifiers::default();
+        let mut last_ms = 0;
+
+        for (number, line) in BufReader::new(file).lines().enumerate() {
+            let line = line.map_err(|e| format!("{}: {}", path, e))?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let record: SessionRecord = serde_json::from_str(&line)
+                .map_err(|e| format!("{} line {}: {}", path, number + 1, e))?;
+            stats.events += 1;
+            last_ms = record.elapsed_ms;
+
+            let (SessionEvent::Device(event_type) | SessionEvent::Tagged(event_type, _)) =
+                record.event
+            else {
+                continue;
+            };
+            match event_type {
+                EventType::KeyPress(key) if is_modifier(key) => modifiers.set(key, true),
+                EventType::KeyRelease(key) => modifiers.set(key, false),
+                EventType::KeyPress(key) => {
+                    stats.key_presses += 1;
+
+                    let hour = (record.elapsed_ms / 3_600_000) as usize;
+                    if stats.per_hour.len() <= hour {
+                        stats.per_hour.resize(hour + 1, 0);
+                    }
+                    stats.per_hour[hour] += 1;
+
+                    let held = [
+                        (modifiers.ctrl, "Ctrl"),
+                        (modifiers.shift, "Shift"),
+                        (modifiers.alt, "Alt"),
+                        (modifiers.altgr, "AltGr"),
+                        (modifiers.meta, "Win"),
+                    ];
+                    for (_, name) in held.iter().filter(|(held, _)| *held) {
+                        *modifier_counts.entry(name.to_string()).or_default() += 1;
+                    }
+
+                    // Stats always use the default spelling, so they compare across configs
+                    let prefix = modifiers.format(&ModifiersConfig::default());
+                    let combo = if prefix.is_empty() {
+                        key_to_string(key)
+                    } else {
+                        format!("{} + {}", prefix, key_to_string(key))
+                    };
+                    *combos.entry(combo).or_default() += 1;
+                }
+                EventType::ButtonPress(_) => stats.clicks += 1,
+                _ => {}
+            }
+        }
+
+        stats.sessions += 1;
+        stats.duration_secs += last_ms / 1000;
+    }
+
+    let mut top: Vec<(String, u64)> = combos.into_iter().collect();
+    top.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    top.truncate(20);
+    stats.top_combos = top;
+    stats.modifier_share = modifier_counts
+        .into_iter()
+        .map(|(name, count)| (name, count as f64 / stats.key_presses.max(1) as f64))
+        .collect();
+
+    print(&stats);
+
+    if let Some(json) = json {
+        let text = serde_json::to_string_pretty(&stats).map_err(|e| e.to_string())?;
+        std::fs::write(json, text).map_err(|e| format!("{}: {}", json.display(), e))?;
+    }
+    Ok(())
+}
+
+pub fn is_modifier(key: Key) -> bool {
+    matches!(
+        key,
+        Key::ControlLeft
+            | Key::ControlRight
+            | Key::ShiftLeft
+            | Key::ShiftRight
+            | Key::Alt
+            | Key::AltGr
+            | Key::MetaLeft
+            | Key::MetaRight
+    )
+}
+
+fn print(stats: &SessionStats) {
+    println!(
+        "{} session(s), {} events over {}h {}m",
+        stats.sessions,
+        stats.events,
+        stats.duration_secs / 3600,
+        stats.duration_secs / 60 % 60
+    );
+    println!("{} key presses, {} clicks", stats.key_presses, stats.clicks);
+
+    println!("\nTop combos:");
+    for (combo, count) in &stats.top_combos {
+        println!("  {:>7}  {}", count, combo);
+    }
+
+    println!("\nKey presses per hour:");
+    for (hour, count) in stats.per_hour.iter().enumerate() {
+        println!("  hour {:>3}  {}", hour + 1, count);
+    }
+
+    println!("\nModifier share:");
+    for (name, share) in &stats.modifier_share {
+        println!("  {:<5}  {:.1}%", name, share * 100.0);
+    }
+}
@@ -0,0 +1,139 @@
+use std::env;
+use std::path::PathBuf;
+
+// Start rusteys on login, from the current directory
+pub fn enable() -> Result<(), String> {
+    let exe = env::current_exe().map_err(|e| e.to_string())?;
+    let dir = env::current_dir().map_err(|e| e.to_string())?;
+    install(&exe, &dir)?;
+    println!("rusteys will start on login from {}", dir.display());
+    Ok(())
+}
+
+pub fn disable() -> Result<(), String> {
+    uninstall()?;
+    println!("rusteys will no longer start on login");
+    Ok(())
+}
+
+#[cfg(unix)]
+fn home() -> Result<PathBuf, String> {
+    env::var_os("HOME")
+        .map(PathBuf::from)
+        .ok_or_else(|| "HOME is not set".to_string())
+}
+
+// XDG autostart entry
+#[cfg(all(unix, not(target_os = "macos")))]
+fn entry_path() -> Result<PathBuf, String> {
+    let config = match env::var_os("XDG_CONFIG_HOME") {
+        Some(config) => PathBuf::from(config),
+        None => home()?.join(".config"),
+    };
+    Ok(config.join("autostart").join("rusteys.desktop"))
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn install(exe: &std::path::Path, dir: &std::path::Path) -> Result<(), String> {
+    let path = entry_path()?;
+    let entry = format!(
+        "[Desktop Entry]\nType=Application\nName=Rusteys\nComment=Keypress overlay\nExec=\"{}\"\nPath={}\nTerminal=false\nX-GNOME-Autostart-enabled=true\n",
+        exe.display(),
+        dir.display()
+    );
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    std::fs::write(&path, entry).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn uninstall() -> Result<(), String> {
+    remove(entry_path()?)
+}
+
+// Per-user LaunchAgent
+#[cfg(target_os = "macos")]
+fn entry_path() -> Result<PathBuf, String> {
+    Ok(home()?.join("Library/LaunchAgents/io.github.rusteys.plist"))
+}
+
+#[cfg(target_os = "macos")]
+fn install(exe: &std::path::Path, dir: &std::path::Path) -> Result<(), String> {
+    let path = entry_path()?;
+    let agent = format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>io.github.rusteys</string>
+    <key>ProgramArguments</key>
+    <array>
+        <string>{}</string>
+    </array>
+    <key>WorkingDirectory</key>
+    <string>{}</string>
+    <key>RunAtLoad</key>
+    <true/>
+</dict>
+</plist>
+"#,
+        exe.display(),
+        dir.display()
+    );
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    std::fs::write(&path, agent).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+#[cfg(target_os = "macos")]
+fn uninstall() -> Result<(), String> {
+    remove(entry_path()?)
+}
+
+#[cfg(unix)]
+fn remove(path: PathBuf) -> Result<(), String> {
+    match std::fs::remove_file(&path) {
+        Err(error) if error.kind() != std::io::ErrorKind::NotFound => {
+            Err(format!("{}: {}", path.display(), error))
+        }
+        _ => Ok(()),
+    }
+}
+
+// Value under the current user's Run key
+#[cfg(windows)]
+const RUN_KEY: &str = r"HKCU\Software\Microsoft\Windows\CurrentVersion\Run";
+
+#[cfg(windows)]
+fn install(exe: &std::path::Path, dir: &std::path::Path) -> Result<(), String> {
+    // Run entries start in the system directory; `start /D` sets the working directory
+    let command = format!(
+        r#"cmd /c start "" /D "{}" "{}""#,
+        dir.display(),
+        exe.display()
+    );
+    reg(&[
+        "add", RUN_KEY, "/v", "rusteys", "/t", "REG_SZ", "/d", &command, "/f",
+    ])
+}
+
+#[cfg(windows)]
+fn uninstall() -> Result<(), String> {
+    reg(&["delete", RUN_KEY, "/v", "rusteys", "/f"])
+}
+
+#[cfg(windows)]
+fn reg(args: &[&str]) -> Result<(), String> {
+    let output = std::process::Command::new("reg")
+        .args(args)
+        .output()
+        .map_err(|e| e.to_string())?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
@@ -0,0 +1,80 @@
+use crate::config::{AnimationConfig, BigKeyConfig};
+use crate::window_level;
+use crate::{KeyPress, MonitorInfo, chip_alpha};
+use eframe::egui;
+use std::collections::VecDeque;
+use std::time::Instant;
+
+// Space between the window and the monitor's top-right corner
+const MARGIN: f32 = 40.0;
+
+// Separate window showing only the most recent chip in a very large font
+pub fn show_big_key(
+    ctx: &egui::Context,
+    monitor: &MonitorInfo,
+    key_presses: &VecDeque<KeyPress>,
+    config: &BigKeyConfig,
+    animations: &AnimationConfig,
+    now: Instant,
+) {
+    let width = config.font_size * 8.0;
+    let height = config.font_size * 2.0;
+
+    ctx.show_viewport_immediate(
+        egui::ViewportId::from_hash_of("big_key"),
+        egui::ViewportBuilder::default()
+            .with_title("Key Display Overlay - Big Key")
+            .with_inner_size([width, height])
+            .with_position([
+                monitor.rect().right() - width - MARGIN,
+                monitor.rect().top() + MARGIN,
+            ])
+            .with_decorations(false)
+            .with_transparent(true)
+            .with_always_on_top()
+            .with_resizable(false)
+            .with_taskbar(false),
+        |ctx, _class| {
+            let latest = key_presses.back().cloned();
+            let alpha = latest.as_ref().map_or(0, |key_press| {
+                chip_alpha(
+                    now.duration_since(key_press.timestamp),
+                    key_press.display,
+                    animations,
+                )
+            });
+
+            egui::CentralPanel::default()
+                .frame(
+                    egui::Frame::new()
+                        .fill(egui::Color32::from_rgba_unmultiplied(35, 35, 35, alpha / 2))
+                        .corner_radius(egui::CornerRadius::same(16)),
+                )
+                .show(ctx, |ui| {
+                    // Drag anywhere to move the window out of the way
+                    let response = ui.interact(
+                        ui.max_rect(),
+                        ui.id().with("drag_big_key"),
+                        egui::Sense::drag(),
+                    );
+                    if response.dragged() {
+                        ctx.send_viewport_cmd(egui::ViewportCommand::StartDrag);
+                    }
+
+                    if let Some(key_press) = latest {
+                        ui.centered_and_justified(|ui| {
+                            ui.label(
+                                egui::RichText::new(&key_press.text)
+                                    .size(config.font_size)
+                                    .strong()
+                                    .color(egui::Color32::from_rgba_unmultiplied(
+                                        255, 255, 255, alpha,
+                                    )),
+                            );
+                        });
+                    }
+                });
+        },
+    );
+    window_level::raise("Key Display Overlay - Big Key");
+}
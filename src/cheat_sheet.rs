@@ -0,0 +1,152 @@
+use crate::MonitorInfo;
+use crate::config::ThemeConfig;
+use crate::window_level;
+use eframe::egui;
+use serde::Deserialize;
+use std::path::Path;
+
+// Widest a column of shortcuts gets before another one is added
+const COLUMN_WIDTH: f32 = 420.0;
+
+// Shortcuts of one application, from a TOML file in the `[cheat_sheets] dir`:
+//
+//     title = "VS Code"
+//     apps = ["Code", "Visual Studio Code"]
+//
+//     [[section]]
+//     name = "Editing"
+//     shortcuts = [["Ctrl+D", "Select next match"], ["Alt+Up", "Move line up"]]
+#[derive(Deserialize)]
+pub struct CheatSheet {
+    pub title: Option<String>,
+    // Focused application names this sheet is for; the file name without `.toml` if empty
+    #[serde(default)]
+    apps: Vec<String>,
+    #[serde(default, rename = "section")]
+    sections: Vec<Section>,
+}
+
+#[derive(Deserialize)]
+struct Section {
+    name: String,
+    shortcuts: Vec<(String, String)>,
+}
+
+// The sheet for `app`, or `default.toml` when there is none or no application is known
+pub fn load(dir: &Path, app: Option<&str>) -> Result<Option<CheatSheet>, String> {
+    let entries = std::fs::read_dir(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+    let mut fallback = None;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().is_none_or(|extension| extension != "toml") {
+            continue;
+        }
+        let stem = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let text =
+            std::fs::read_to_string(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let mut sheet: CheatSheet =
+            toml::from_str(&text).map_err(|e| format!("{}: {}", path.display(), e))?;
+        if sheet.apps.is_empty() {
+            sheet.apps.push(stem.clone());
+        }
+
+        let matches =
+            app.is_some_and(|app| sheet.apps.iter().any(|name| name.eq_ignore_ascii_case(app)));
+        if matches {
+            return Ok(Some(sheet));
+        }
+        if stem == "default" {
+            fallback = Some(sheet);
+        }
+    }
+    Ok(fallback)
+}
+
+// Translucent sheet over the whole first monitor, drawn with the overlay's theme: the panel
+// color and opacity behind it, and keys as idle chips
+pub fn show(ctx: &egui::Context, monitor: &MonitorInfo, sheet: &CheatSheet, theme: &ThemeConfig) {
+    let rect = monitor.rect();
+    let (chip_fill, chip_stroke, text_color) = theme.idle_colors();
+    let (_, accent) = theme.palette.accent();
+    let font_size = theme.font_size * 0.6;
+
+    ctx.show_viewport_immediate(
+        egui::ViewportId::from_hash_of("cheat_sheet"),
+        egui::ViewportBuilder::default()
+            .with_title("Key Display Overlay - Cheat Sheet")
+            .with_inner_size(rect.size())
+            .with_position(rect.min)
+            .with_decorations(false)
+            .with_transparent(true)
+            .with_always_on_top()
+            .with_resizable(false)
+            .with_taskbar(false)
+            .with_mouse_passthrough(true),
+        |ctx, _class| {
+            egui::CentralPanel::default()
+                .frame(
+                    egui::Frame::new()
+                        .fill(theme.panel_fill())
+                        .inner_margin(egui::Margin::same(48)),
+                )
+                .show(ctx, |ui| {
+                    if let Some(title) = &sheet.title {
+                        ui.label(
+                            egui::RichText::new(title)
+                                .size(theme.font_size * 1.2)
+                                .strong()
+                                .color(accent),
+                        );
+                        ui.add_space(font_size);
+                    }
+
+                    let columns = ((ui.available_width() / COLUMN_WIDTH) as usize).max(1);
+                    ui.columns(columns, |columns| {
+                        for (index, section) in sheet.sections.iter().enumerate() {
+                            let ui = &mut columns[index % columns.len()];
+                            ui.label(
+                                egui::RichText::new(&section.name)
+                                    .size(font_size * 1.1)
+                                    .strong()
+                                    .color(text_color),
+                            );
+                            egui::Grid::new(("cheat_sheet_section", index))
+                                .spacing([font_size, font_size * 0.4])
+                                .show(ui, |ui| {
+                                    for (keys, description) in &section.shortcuts {
+                                        egui::Frame::new()
+                                            .fill(chip_fill)
+                                            .stroke(egui::Stroke::new(
+                                                theme.chip_stroke_width,
+                                                chip_stroke,
+                                            ))
+                                            .corner_radius(egui::CornerRadius::same(
+                                                theme.chip_radius,
+                                            ))
+                                            .inner_margin(egui::Margin::symmetric(8, 2))
+                                            .show(ui, |ui| {
+                                                ui.label(
+                                                    egui::RichText::new(keys)
+                                                        .size(font_size)
+                                                        .color(text_color),
+                                                );
+                                            });
+                                        ui.label(
+                                            egui::RichText::new(description)
+                                                .size(font_size)
+                                                .color(text_color),
+                                        );
+                                        ui.end_row();
+                                    }
+                                });
+                            ui.add_space(font_size);
+                        }
+                    });
+                });
+        },
+    );
+    window_level::raise("Key Display Overlay - Cheat Sheet");
+}
@@ -0,0 +1,55 @@
+use crate::config::{ClockConfig, ClockMode, Corner, ThemeConfig};
+use crate::timer::format_duration;
+use crate::ui;
+use eframe::egui;
+use std::fmt::Write;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+const DEFAULT_FORMAT: &str = "%H:%M:%S";
+
+// What the clock shows now; None when it is off
+pub fn text(config: &ClockConfig, recording_started: Option<Instant>) -> Option<String> {
+    match config.show {
+        ClockMode::Off => None,
+        ClockMode::Time => Some(time(&config.format)),
+        // Placeholder until `recording-start` runs
+        ClockMode::Recording => Some(recording_started.map_or("● -:--".to_string(), |started| {
+            format!("● {}", format_duration(started.elapsed()))
+        })),
+    }
+}
+
+// Formatting with an invalid strftime string fails rather than writing anything, e.g. from a
+// profile, which isn't checked until it is switched to
+fn time(format: &str) -> String {
+    let now = chrono::Local::now();
+    let mut text = String::new();
+    if write!(text, "{}", now.format(format)).is_err() {
+        text = now.format(DEFAULT_FORMAT).to_string();
+    }
+    text
+}
+
+// Draw the clock in its corner of the panel with the theme's chip colors
+pub fn paint(ui: &egui::Ui, config: &ClockConfig, theme: &ThemeConfig, text: &str) {
+    let rect = ui.max_rect();
+    let (position, anchor) = match config.corner {
+        Corner::TopLeft => (rect.left_top(), egui::Align2::LEFT_TOP),
+        Corner::TopRight => (rect.right_top(), egui::Align2::RIGHT_TOP),
+        Corner::BottomLeft => (rect.left_bottom(), egui::Align2::LEFT_BOTTOM),
+        Corner::BottomRight => (rect.right_bottom(), egui::Align2::RIGHT_BOTTOM),
+    };
+    let (fill, _, color) = theme.idle_colors();
+    ui::badge(ui, position, anchor, text, config.font_size, fill, color);
+}
+
+// Time until the clock's seconds change
+pub fn next_tick(config: &ClockConfig, recording_started: Option<Instant>) -> Duration {
+    let into_second = match (config.show, recording_started) {
+        (ClockMode::Recording, Some(started)) => started.elapsed().subsec_millis(),
+        _ => SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| time.subsec_millis()),
+    };
+    Duration::from_millis(1000 - into_second as u64)
+}
@@ -0,0 +1,56 @@
+use crate::config::ComboCommand;
+use std::process::{Command, Stdio};
+use std::thread;
+
+// Start the command through the shell, without waiting for it; with `log_output` its output
+// is printed once it exits
+pub fn run(command: &ComboCommand) {
+    #[cfg(windows)]
+    let mut process = {
+        let mut process = Command::new("cmd");
+        process.args(["/C", &command.run]);
+        process
+    };
+    #[cfg(not(windows))]
+    let mut process = {
+        let mut process = Command::new("sh");
+        process.args(["-c", &command.run]);
+        process
+    };
+
+    let output = if command.log_output {
+        Stdio::piped
+    } else {
+        Stdio::null
+    };
+    process
+        .stdin(Stdio::null())
+        .stdout(output())
+        .stderr(output());
+    let child = match process.spawn() {
+        Ok(child) => child,
+        Err(error) => {
+            eprintln!("Error running `{}`: {}", command.run, error);
+            return;
+        }
+    };
+
+    // Waiting also reaps the process, so it doesn't linger as a zombie
+    let run = command.run.clone();
+    let log_output = command.log_output;
+    thread::spawn(move || match child.wait_with_output() {
+        Ok(output) => {
+            if log_output {
+                for stream in [&output.stdout, &output.stderr] {
+                    for line in String::from_utf8_lossy(stream).lines() {
+                        eprintln!("[{}] {}", run, line);
+                    }
+                }
+            }
+            if !output.status.success() {
+                eprintln!("`{}` exited with {}", run, output.status);
+            }
+        }
+        Err(error) => eprintln!("Error waiting for `{}`: {}", run, error),
+    });
+}
@@ -0,0 +1,90 @@
+use eframe::egui;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+// Downward acceleration in points per second squared
+const GRAVITY: f32 = 600.0;
+
+const COLORS: [egui::Color32; 5] = [
+    egui::Color32::from_rgb(255, 90, 90),
+    egui::Color32::from_rgb(255, 200, 60),
+    egui::Color32::from_rgb(90, 210, 120),
+    egui::Color32::from_rgb(90, 150, 255),
+    egui::Color32::from_rgb(220, 120, 255),
+];
+
+// One piece of confetti; its position is a function of time, so nothing updates per frame
+struct Particle {
+    // Launch velocity in points per second, from the bottom-right of the overlay
+    velocity: egui::Vec2,
+    color: egui::Color32,
+    size: f32,
+    launched: Instant,
+}
+
+// Particle bursts celebrating configured combos, drawn over the chips
+#[derive(Default)]
+pub struct Confetti {
+    particles: Vec<Particle>,
+    seed: u64,
+}
+
+impl Confetti {
+    pub fn burst(&mut self, count: usize, now: Instant) {
+        if self.seed == 0 {
+            self.seed = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(1, |time| time.as_nanos() as u64 | 1);
+        }
+
+        for _ in 0..count {
+            // Up and to the left, where the older chips are
+            let angle = std::f32::consts::PI * (0.55 + 0.35 * self.random());
+            let speed = 250.0 + 350.0 * self.random();
+            let color = COLORS[(self.random() * COLORS.len() as f32) as usize % COLORS.len()];
+            let size = 3.0 + 3.0 * self.random();
+            self.particles.push(Particle {
+                velocity: egui::vec2(angle.cos(), -angle.sin()) * speed,
+                color,
+                size,
+                launched: now,
+            });
+        }
+    }
+
+    // Drop particles older than `duration`; true while any are left
+    pub fn update(&mut self, now: Instant, duration: Duration) -> bool {
+        self.particles
+            .retain(|particle| now.duration_since(particle.launched) < duration);
+        !self.particles.is_empty()
+    }
+
+    pub fn paint(&self, ctx: &egui::Context, now: Instant, duration: Duration) {
+        if self.particles.is_empty() {
+            return;
+        }
+
+        let painter = ctx.layer_painter(egui::LayerId::new(
+            egui::Order::Foreground,
+            egui::Id::new("confetti"),
+        ));
+        let origin = ctx.content_rect().right_bottom() - egui::vec2(60.0, 20.0);
+        for particle in &self.particles {
+            let t = now.duration_since(particle.launched).as_secs_f32();
+            let position = origin + particle.velocity * t + egui::vec2(0.0, 0.5 * GRAVITY * t * t);
+            let life = 1.0 - t / duration.as_secs_f32();
+            painter.rect_filled(
+                egui::Rect::from_center_size(position, egui::Vec2::splat(particle.size)),
+                1.0,
+                particle.color.gamma_multiply(life.clamp(0.0, 1.0)),
+            );
+        }
+    }
+
+    // xorshift; good enough for scattering confetti
+    fn random(&mut self) -> f32 {
+        self.seed ^= self.seed << 13;
+        self.seed ^= self.seed >> 7;
+        self.seed ^= self.seed << 17;
+        (self.seed >> 40) as f32 / (1u64 << 24) as f32
+    }
+}
@@ -0,0 +1,1864 @@
+use crate::environment;
+use crate::hotkeys::Action;
+use crate::i18n::{self, Language};
+use crate::input::DeviceInfo;
+use crate::paths;
+use crate::ui::Repaint;
+use crate::validation;
+use crate::{key_from_name, key_symbol, key_to_string};
+use eframe::egui;
+use notify::{RecursiveMode, Watcher};
+use parking_lot::RwLock;
+use rdev::Key;
+use regex::Regex;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+const DEFAULT_MAX_CHIPS: usize = 15;
+
+// Editors often write a file in several steps; wait for them to finish
+const RELOAD_DEBOUNCE: Duration = Duration::from_millis(150);
+
+// The current config, replaced wholesale on reload; readers clone the inner Arc as a snapshot
+pub type SharedConfig = Arc<RwLock<Arc<Config>>>;
+
+// What a key chip names: the key on the keyboard or what it typed
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Default, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyDisplay {
+    // The physical key, the same whatever the layout or remapping (default)
+    #[default]
+    Physical,
+    // The character the OS produced, for Colemak/Dvorak and remappers; shortcuts stay physical
+    Character,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Default, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum DisplayMode {
+    // Scrolling row of key chips (default)
+    #[default]
+    Chips,
+    // On-screen keyboard highlighting held keys
+    Keyboard,
+}
+
+// Where the overlay window goes on each monitor
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Default, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub enum Placement {
+    // Floating panel centered near the bottom (default)
+    #[default]
+    Panel,
+    // Full-width strip along the top edge
+    TopBar,
+    // Full-width strip along the bottom edge
+    BottomBar,
+    // Floats next to the mouse pointer, where the audience is already looking
+    Cursor,
+    // On the focused window, e.g. at the bottom of the app being demonstrated
+    Window,
+}
+
+// Spot on a rectangle, like the screen or a window, that the overlay lines up with
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Default, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub enum Anchor {
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Center,
+    Right,
+    BottomLeft,
+    #[default]
+    Bottom,
+    BottomRight,
+}
+
+impl Anchor {
+    pub fn align(self) -> egui::Align2 {
+        match self {
+            Anchor::TopLeft => egui::Align2::LEFT_TOP,
+            Anchor::Top => egui::Align2::CENTER_TOP,
+            Anchor::TopRight => egui::Align2::RIGHT_TOP,
+            Anchor::Left => egui::Align2::LEFT_CENTER,
+            Anchor::Center => egui::Align2::CENTER_CENTER,
+            Anchor::Right => egui::Align2::RIGHT_CENTER,
+            Anchor::BottomLeft => egui::Align2::LEFT_BOTTOM,
+            Anchor::Bottom => egui::Align2::CENTER_BOTTOM,
+            Anchor::BottomRight => egui::Align2::RIGHT_BOTTOM,
+        }
+    }
+}
+
+impl Placement {
+    pub fn is_bar(self) -> bool {
+        matches!(self, Placement::TopBar | Placement::BottomBar)
+    }
+
+    // A single overlay that moves between monitors, rather than one on each
+    pub fn roams(self) -> bool {
+        matches!(self, Placement::Cursor | Placement::Window)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct PositionConfig {
+    pub placement: Placement,
+    // Keep maximized windows out from under a bar, like a panel or dock (X11 only)
+    pub exclusive_zone: bool,
+    // Panel width in points when following the cursor
+    pub cursor_width: f32,
+    // Gap between the pointer and the panel, in points
+    pub cursor_offset: f32,
+    // 0 sticks to the pointer; towards 1 the panel lags further behind and glides after it
+    pub smoothing: f32,
+    // Closest the panel gets to a monitor edge, in points
+    pub edge_margin: f32,
+    // Where on the focused window the panel goes
+    pub window_anchor: Anchor,
+    // Dragging the overlay doesn't move it; the padlock at the top of the panel and the
+    // `lock-position` hotkey action switch this
+    pub locked: bool,
+    // Dropping the dragged panel this close to a corner, edge center or the middle of the monitor
+    // snaps it there; 0 to turn snapping off
+    pub snap_distance: f32,
+    // Parts of the screen the overlay must not cover, like a game's minimap or a video's subtitles;
+    // it moves to the nearest anchor clear of them
+    pub keep_out: Vec<Region>,
+}
+
+// Rectangle in points from the top-left corner of a monitor, on every monitor
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct Region {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Default for PositionConfig {
+    fn default() -> Self {
+        Self {
+            placement: Placement::Panel,
+            exclusive_zone: false,
+            cursor_width: 480.0,
+            cursor_offset: 32.0,
+            smoothing: 0.8,
+            edge_margin: 8.0,
+            window_anchor: Anchor::Bottom,
+            locked: false,
+            snap_distance: 48.0,
+            keep_out: Vec::new(),
+        }
+    }
+}
+
+// When to trade animations and effects for lower power draw
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Default, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum PowerSaving {
+    // Only while running on battery (default)
+    #[default]
+    Auto,
+    On,
+    Off,
+}
+
+// Color written as "#RRGGBB" or "#RRGGBBAA" in the config file
+#[derive(Clone, Copy, Debug)]
+pub struct Color(pub egui::Color32);
+
+impl Serialize for Color {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0.to_hex())
+    }
+}
+
+impl<'de> Deserialize<'de> for Color {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        egui::Color32::from_hex(&text).map(Color).map_err(|_| {
+            serde::de::Error::custom(format!("invalid color `{}`, expected #RRGGBB or #RRGGBBAA", text))
+        })
+    }
+}
+
+// Key combination written as "Ctrl+Shift+Backspace" in the config file
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Combo {
+    pub ctrl: bool,
+    pub shift: bool,
+    pub alt: bool,
+    pub meta: bool,
+    pub key: Key,
+}
+
+impl Combo {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let (mut ctrl, mut shift, mut alt, mut meta) = (false, false, false, false);
+        let mut key = None;
+
+        for part in text.split('+').map(str::trim) {
+            match part.to_lowercase().as_str() {
+                "ctrl" | "control" => ctrl = true,
+                "shift" => shift = true,
+                "alt" | "option" => alt = true,
+                "win" | "meta" | "super" | "cmd" => meta = true,
+                _ if key.is_none() => {
+                    key =
+                        Some(key_from_name(part).ok_or_else(|| format!("unknown key `{}`", part))?);
+                }
+                _ => return Err(format!("more than one non-modifier key in `{}`", text)),
+            }
+        }
+
+        Ok(Combo {
+            ctrl,
+            shift,
+            alt,
+            meta,
+            key: key.ok_or_else(|| format!("no key in `{}`", text))?,
+        })
+    }
+}
+
+impl fmt::Display for Combo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let modifiers = [
+            (self.ctrl, "Ctrl+"),
+            (self.shift, "Shift+"),
+            (self.alt, "Alt+"),
+            (self.meta, "Win+"),
+        ];
+        for (held, name) in modifiers {
+            if held {
+                f.write_str(name)?;
+            }
+        }
+        f.write_str(&key_to_string(self.key))
+    }
+}
+
+impl Serialize for Combo {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Combo {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        Combo::parse(&text).map_err(serde::de::Error::custom)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct Config {
+    // Profile applied at startup
+    pub profile: Option<String>,
+    // Named overrides of any of the settings below, e.g. [profiles.gaming]
+    pub profiles: BTreeMap<String, toml::Table>,
+    pub mode: DisplayMode,
+    // Language of menus, notices and dialogs: auto (the system's), en, de, fr, es or pt
+    pub language: Language,
+    // Keyboard geometry for the keyboard mode (rusteys JSON or KLE raw data)
+    pub keyboard_layout: Option<PathBuf>,
+    // Show mouse button presses as chips alongside keys
+    pub show_mouse: bool,
+    // Keyboard and pointer chips in separate rows
+    pub lanes: bool,
+    // "Layout: DE" chip when the keyboard layout or input source changes
+    pub show_layout_changes: bool,
+    // Oldest chips are dropped beyond this many (per lane with `lanes`); 15 when unset
+    pub max_chips: Option<usize>,
+    // Only show keys pressed with a modifier held; plain typing and lone modifiers are hidden
+    pub shortcuts_only: bool,
+    pub key_display: KeyDisplay,
+    // Add rdev's key name and the raw key code to chips, for reporting unknown keys
+    pub show_scancodes: bool,
+    // Prefix key and click chips with the name of the keyboard or mouse (Linux evdev only)
+    pub show_device_names: bool,
+    // Per device settings, keyed by part of its name or its vendor:product id, e.g. [devices."Macro Pad"]
+    pub devices: BTreeMap<String, DeviceConfig>,
+    // Chip text for keys by rdev name, e.g. CapsLock = "Hyper", instead of the built-in names
+    #[serde(
+        serialize_with = "serialize_key_map",
+        deserialize_with = "deserialize_key_map"
+    )]
+    pub labels: Vec<(Key, String)>,
+    // SVG or PNG drawn in place of a key's name, e.g. MetaLeft = "icons/windows.svg"
+    #[serde(
+        serialize_with = "serialize_key_map",
+        deserialize_with = "deserialize_key_map"
+    )]
+    pub icons: Vec<(Key, PathBuf)>,
+    // Keys changed by a remapper, by the physical key's rdev name, e.g. CapsLock = "Escape"
+    #[serde(
+        serialize_with = "serialize_key_map",
+        deserialize_with = "deserialize_key_map"
+    )]
+    pub remap: Vec<(Key, Remap)>,
+    // Chip text, color and icon for combos, optionally only in some applications; the first
+    // matching rule applies
+    pub rules: Vec<Rule>,
+    // rhai script whose `format(event)` restyles or hides any chip (needs the `scripting` feature);
+    // read at startup
+    pub script: Option<PathBuf>,
+    // WASM plugins that see every input event and can add chips or run actions (needs the
+    // `plugins` feature); loaded at startup
+    pub plugins: Vec<PathBuf>,
+    // URLs to POST to when a combo is pressed
+    pub webhooks: Vec<Webhook>,
+    // Shell commands to run when a combo is pressed
+    pub commands: Vec<ComboCommand>,
+    // Symbols like ↵ ⇥ ⌫ ␣ and arrows instead of key names
+    pub key_symbols: bool,
+    // Font with the symbols, for systems where none of the usual ones is installed
+    pub symbol_font: Option<PathBuf>,
+    pub modifiers: ModifiersConfig,
+    // No scale/fade animations or shadow, and at most ~10 repaints a second
+    pub power_saving: PowerSaving,
+    pub durations: DurationsConfig,
+    pub animations: AnimationConfig,
+    pub confetti: ConfettiConfig,
+    pub theme: ThemeConfig,
+    pub position: PositionConfig,
+    pub cursor: CursorConfig,
+    pub gamepad: GamepadConfig,
+    // Graphics tablet stylus chips (Linux evdev only)
+    pub pen: bool,
+    // Touchscreen and touchpad gesture chips (Linux evdev only)
+    pub touch: bool,
+    pub hotkeys: HotkeysConfig,
+    pub history: HistoryConfig,
+    pub macros: MacrosConfig,
+    pub http: HttpConfig,
+    pub osc: OscConfig,
+    pub remote: RemoteConfig,
+    pub big_key: BigKeyConfig,
+    pub which_key: WhichKeyConfig,
+    pub clock: ClockConfig,
+    pub window_title: WindowTitleConfig,
+    pub cheat_sheets: CheatSheetsConfig,
+    pub screenshot: ScreenshotConfig,
+    pub replay: ReplayConfig,
+    pub websocket: WebSocketConfig,
+    // The file as written, before any profile was merged in
+    #[serde(skip)]
+    raw: toml::Table,
+    #[serde(skip)]
+    pub active_profile: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+#[serde(default)]
+pub struct DeviceConfig {
+    // Shown instead of the device name with `show_device_names`
+    pub label: Option<String>,
+    // Chip fill for everything from this device
+    pub color: Option<Color>,
+    // Show nothing from this device, e.g. a macro pad typing whole snippets
+    pub ignore: bool,
+}
+
+// Shape of an animation over its duration
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Default, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub enum Easing {
+    #[default]
+    Linear,
+    // Fast start, slow finish
+    EaseOut,
+    // Slow start, fast finish
+    EaseIn,
+    // Overshoots and settles, like a damped spring
+    Spring,
+}
+
+impl Easing {
+    // Map linear progress (0 to 1) onto the curve; spring briefly goes past 1
+    pub fn apply(self, progress: f32) -> f32 {
+        let t = progress.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseOut => 1.0 - (1.0 - t).powi(3),
+            Easing::EaseIn => t.powi(3),
+            Easing::Spring => 1.0 - (t * std::f32::consts::PI * 2.5).cos() * (-5.0 * t).exp(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct AnimationConfig {
+    // Off: chips appear and disappear without any motion (also forced by power saving)
+    pub enabled: bool,
+    // How much larger a new chip starts, 0.2 = 20%
+    pub pop_scale: f32,
+    pub pop_ms: u64,
+    pub pop_easing: Easing,
+    // Fade-out after the chip's display duration
+    pub fade_ms: u64,
+    pub fade_easing: Easing,
+    // New chips slide in from the right edge, pushing older ones left; 0 to appear in place
+    pub slide_ms: u64,
+    pub slide_easing: Easing,
+}
+
+impl Default for AnimationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            pop_scale: 0.2,
+            pop_ms: 100,
+            pop_easing: Easing::Linear,
+            fade_ms: 800,
+            fade_easing: Easing::Linear,
+            slide_ms: 0,
+            slide_easing: Easing::EaseOut,
+        }
+    }
+}
+
+impl AnimationConfig {
+    pub fn pop(&self) -> Duration {
+        if self.enabled {
+            Duration::from_millis(self.pop_ms)
+        } else {
+            Duration::ZERO
+        }
+    }
+
+    pub fn fade(&self) -> Duration {
+        if self.enabled {
+            Duration::from_millis(self.fade_ms)
+        } else {
+            Duration::ZERO
+        }
+    }
+
+    pub fn slide(&self) -> Duration {
+        if self.enabled {
+            Duration::from_millis(self.slide_ms)
+        } else {
+            Duration::ZERO
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct ThemeConfig {
+    // Dark or light chips; `system` follows the OS setting as it changes
+    pub mode: ThemeMode,
+    // Which way chips flow; `rtl` puts the newest chip on the left
+    pub direction: Direction,
+    // Background behind the chips; any alpha in the color is replaced by `panel_opacity`
+    pub panel_color: Color,
+    // Used instead of `panel_color` in light mode
+    pub light_panel_color: Color,
+    // 0 (only the chips are drawn) to 100 (opaque)
+    pub panel_opacity: u8,
+    pub panel_radius: u8,
+    // Blur the desktop behind the panel (Windows 11, macOS, KDE on X11)
+    pub blur: bool,
+    pub panel_shadow: ShadowConfig,
+    // 0 for sharp rectangles, about half the chip height for pills
+    pub chip_radius: u8,
+    // Space between the chip border and its text
+    pub chip_padding_x: i8,
+    pub chip_padding_y: i8,
+    pub chip_stroke_width: f32,
+    // Border color of every chip; by default the newest chip gets a blue border and older ones gray
+    pub chip_stroke_color: Option<Color>,
+    pub chip_shadow: ShadowConfig,
+    // Colors that tell chips, held keys and mouse buttons apart
+    pub palette: Palette,
+    // Chip text size in points
+    pub font_size: f32,
+    // Opaque black panel, black and white chips with thick borders and larger text,
+    // overriding the settings above; the `high-contrast` hotkey action flips it
+    pub high_contrast: bool,
+}
+
+impl Default for ThemeConfig {
+    fn default() -> Self {
+        Self {
+            mode: ThemeMode::default(),
+            direction: Direction::default(),
+            panel_color: Color(egui::Color32::from_rgb(35, 35, 35)),
+            light_panel_color: Color(egui::Color32::from_rgb(240, 240, 240)),
+            panel_opacity: 50,
+            panel_radius: 12,
+            blur: false,
+            panel_shadow: ShadowConfig {
+                offset_y: 4,
+                blur: 16,
+                color: Color(egui::Color32::from_rgba_unmultiplied(0, 0, 0, 115)),
+            },
+            chip_radius: 6,
+            chip_padding_x: 12,
+            chip_padding_y: 8,
+            chip_stroke_width: 1.5,
+            chip_stroke_color: None,
+            chip_shadow: ShadowConfig::default(),
+            palette: Palette::default(),
+            font_size: 28.0,
+            high_contrast: false,
+        }
+    }
+}
+
+const HIGH_CONTRAST_MIN_FONT_SIZE: f32 = 36.0;
+const HIGH_CONTRAST_MIN_STROKE_WIDTH: f32 = 3.0;
+
+impl ThemeConfig {
+    // The theme to draw with: `mode` settled to dark or light, then the high-contrast
+    // hotkey and its overrides
+    pub fn resolved(&self, toggle_high_contrast: bool, system: egui::Theme) -> ThemeConfig {
+        let mut theme = self.clone();
+        if theme.mode == ThemeMode::System {
+            theme.mode = match system {
+                egui::Theme::Dark => ThemeMode::Dark,
+                egui::Theme::Light => ThemeMode::Light,
+            };
+        }
+        if theme.mode == ThemeMode::Light {
+            theme.panel_color = theme.light_panel_color;
+        }
+        if theme.direction == Direction::Auto {
+            theme.direction = if rtl_locale() {
+                Direction::Rtl
+            } else {
+                Direction::Ltr
+            };
+        }
+
+        theme.high_contrast ^= toggle_high_contrast;
+        if theme.high_contrast {
+            theme.mode = ThemeMode::Dark;
+            theme.panel_color = Color(egui::Color32::BLACK);
+            theme.panel_opacity = 100;
+            theme.blur = false;
+            theme.panel_shadow.blur = 0;
+            theme.chip_shadow.blur = 0;
+            theme.chip_stroke_width = theme.chip_stroke_width.max(HIGH_CONTRAST_MIN_STROKE_WIDTH);
+            theme.chip_stroke_color = Some(Color(egui::Color32::WHITE));
+            theme.font_size = theme.font_size.max(HIGH_CONTRAST_MIN_FONT_SIZE);
+        }
+        theme
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Default, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum ThemeMode {
+    #[default]
+    System,
+    Dark,
+    Light,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Default, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    #[default]
+    Ltr,
+    Rtl,
+    // Right to left when the locale's language is written that way
+    Auto,
+}
+
+// Hebrew, Arabic, Persian, Urdu, Yiddish and others, by ISO 639-1 code
+const RTL_LANGUAGES: &[&str] = &["ar", "dv", "fa", "he", "iw", "ps", "sd", "ug", "ur", "yi"];
+
+fn rtl_locale() -> bool {
+    i18n::locale_language().is_some_and(|language| RTL_LANGUAGES.contains(&language.as_str()))
+}
+
+// Accent and mouse button colors, with sets that stay distinct under each kind of color blindness
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Default, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum Palette {
+    #[default]
+    Default,
+    // Red-green, missing or weak green cones
+    Deuteranopia,
+    // Red-green, missing or weak red cones; reds look dark
+    Protanopia,
+    // Blue-yellow
+    Tritanopia,
+}
+
+impl Palette {
+    // Fill and border of the newest chip and of held keys
+    pub fn accent(self) -> (egui::Color32, egui::Color32) {
+        match self {
+            Palette::Default | Palette::Deuteranopia | Palette::Protanopia => (
+                egui::Color32::from_rgb(70, 110, 200),
+                egui::Color32::from_rgb(100, 140, 220),
+            ),
+            Palette::Tritanopia => (
+                egui::Color32::from_rgb(180, 60, 80),
+                egui::Color32::from_rgb(220, 100, 120),
+            ),
+        }
+    }
+
+    // Left, right and middle/other mouse buttons
+    pub fn buttons(self) -> [egui::Color32; 3] {
+        // Mostly from the Okabe-Ito palette
+        match self {
+            Palette::Default => [
+                egui::Color32::from_rgb(100, 150, 255),
+                egui::Color32::from_rgb(255, 120, 100),
+                egui::Color32::from_rgb(120, 220, 120),
+            ],
+            Palette::Deuteranopia => [
+                egui::Color32::from_rgb(86, 180, 233),
+                egui::Color32::from_rgb(230, 159, 0),
+                egui::Color32::from_rgb(204, 121, 167),
+            ],
+            Palette::Protanopia => [
+                egui::Color32::from_rgb(86, 180, 233),
+                egui::Color32::from_rgb(230, 159, 0),
+                egui::Color32::from_rgb(240, 228, 66),
+            ],
+            Palette::Tritanopia => [
+                egui::Color32::from_rgb(230, 70, 70),
+                egui::Color32::from_rgb(0, 170, 170),
+                egui::Color32::from_rgb(220, 220, 220),
+            ],
+        }
+    }
+}
+
+// Drop shadow; a blur of 0 turns it off
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct ShadowConfig {
+    pub offset_y: i8,
+    pub blur: u8,
+    pub color: Color,
+}
+
+impl Default for ShadowConfig {
+    fn default() -> Self {
+        Self {
+            offset_y: 2,
+            blur: 0,
+            color: Color(egui::Color32::from_rgba_unmultiplied(0, 0, 0, 115)),
+        }
+    }
+}
+
+impl ShadowConfig {
+    pub fn shadow(&self) -> egui::epaint::Shadow {
+        if self.blur == 0 {
+            return egui::epaint::Shadow::NONE;
+        }
+        egui::epaint::Shadow {
+            offset: [0, self.offset_y],
+            blur: self.blur,
+            spread: 0,
+            color: self.color.0,
+        }
+    }
+}
+
+impl ThemeConfig {
+    // Scale the chips' text, padding and corners together
+    pub fn zoom(&mut self, factor: f32) {
+        let scale = |value: f32| (value * factor).round();
+        self.font_size *= factor;
+        self.chip_padding_x = scale(self.chip_padding_x as f32) as i8;
+        self.chip_padding_y = scale(self.chip_padding_y as f32) as i8;
+        self.chip_radius = scale(self.chip_radius as f32) as u8;
+    }
+
+    pub fn panel_fill(&self) -> egui::Color32 {
+        let [r, g, b, _] = self.panel_color.0.to_srgba_unmultiplied();
+        let alpha = u32::from(self.panel_opacity.min(100)) * 255 / 100;
+        egui::Color32::from_rgba_unmultiplied(r, g, b, alpha as u8)
+    }
+
+    // Fill, border and text of every chip but the newest, and of keys that aren't held
+    pub fn idle_colors(&self) -> (egui::Color32, egui::Color32, egui::Color32) {
+        match self.mode {
+            ThemeMode::Light => (
+                egui::Color32::from_rgb(225, 228, 235),
+                egui::Color32::from_rgb(150, 160, 180),
+                egui::Color32::from_rgb(30, 30, 30),
+            ),
+            _ => (
+                egui::Color32::from_rgb(70, 75, 85),
+                egui::Color32::from_rgb(140, 150, 170),
+                egui::Color32::WHITE,
+            ),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct ConfettiConfig {
+    // Combos that set off a burst, e.g. "Ctrl+S"
+    pub combos: Vec<Combo>,
+    pub particles: usize,
+    pub duration_ms: u64,
+}
+
+impl Default for ConfettiConfig {
+    fn default() -> Self {
+        Self {
+            combos: Vec::new(),
+            particles: 40,
+            duration_ms: 1200,
+        }
+    }
+}
+
+impl ConfettiConfig {
+    pub fn duration(&self) -> Duration {
+        Duration::from_millis(self.duration_ms)
+    }
+}
+
+// How long each kind of chip shows before it fades out
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct DurationsConfig {
+    // Keys pressed with modifiers, e.g. "Ctrl + S"
+    pub shortcut_ms: u64,
+    // Keys pressed on their own
+    pub key_ms: u64,
+    // Modifiers pressed and released on their own
+    pub modifier_ms: u64,
+    // Clicks, drags and scrolling
+    pub mouse_ms: u64,
+    // Gamepad, pen and touch chips and notices like profile switches
+    pub other_ms: u64,
+}
+
+impl Default for DurationsConfig {
+    fn default() -> Self {
+        Self {
+            shortcut_ms: 4000,
+            key_ms: 4000,
+            modifier_ms: 4000,
+            mouse_ms: 4000,
+            other_ms: 4000,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct HotkeysConfig {
+    // Swallow hotkey presses so other applications never see them
+    pub suppress: bool,
+    // Combo → action, e.g. "Ctrl+Shift+Backspace" = "clear"
+    #[serde(
+        serialize_with = "serialize_bindings",
+        deserialize_with = "deserialize_bindings"
+    )]
+    pub bindings: Vec<(Combo, Action)>,
+    // Shorthands for a `clear` or `toggle-visibility` binding, from before [hotkeys.bindings]
+    pub clear: Option<Combo>,
+    pub toggle_visibility: Option<Combo>,
+}
+
+impl HotkeysConfig {
+    // The action of the first binding `matches` accepts
+    pub fn action(&self, mut matches: impl FnMut(&Combo) -> bool) -> Option<Action> {
+        let shorthands = [
+            (self.clear, Action::Clear),
+            (self.toggle_visibility, Action::ToggleVisibility),
+        ];
+        self.bindings
+            .iter()
+            .cloned()
+            .chain(
+                shorthands
+                    .into_iter()
+                    .filter_map(|(combo, action)| Some((combo?, action))),
+            )
+            .find(|(combo, _)| matches(combo))
+            .map(|(_, action)| action)
+    }
+}
+
+fn serialize_bindings<S: Serializer>(
+    bindings: &[(Combo, Action)],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_map(
+        bindings
+            .iter()
+            .map(|(combo, action)| (combo.to_string(), action.to_string())),
+    )
+}
+
+fn deserialize_bindings<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<(Combo, Action)>, D::Error> {
+    BTreeMap::<String, Action>::deserialize(deserializer)?
+        .into_iter()
+        .map(|(combo, action)| {
+            let combo = Combo::parse(&combo).map_err(serde::de::Error::custom)?;
+            Ok((combo, action))
+        })
+        .collect()
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum Modifier {
+    #[serde(alias = "control")]
+    Ctrl,
+    Shift,
+    #[serde(alias = "option")]
+    Alt,
+    AltGr,
+    #[serde(alias = "win", alias = "super", alias = "cmd")]
+    Meta,
+}
+
+impl Modifier {
+    const ALL: [Modifier; 5] = [
+        Modifier::Ctrl,
+        Modifier::Shift,
+        Modifier::Alt,
+        Modifier::AltGr,
+        Modifier::Meta,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Modifier::Ctrl => "Ctrl",
+            Modifier::Shift => "Shift",
+            Modifier::Alt => "Alt",
+            Modifier::AltGr => "AltGr",
+            Modifier::Meta => "Win",
+        }
+    }
+}
+
+// How modifiers are written before a key, e.g. "Ctrl + Shift + S"
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct ModifiersConfig {
+    // Modifiers left out keep their default place after the listed ones
+    pub order: Vec<Modifier>,
+    // Between modifiers and before the key
+    pub separator: String,
+    // Show the character AltGr typed ("@") instead of the combo ("AltGr + Q")
+    pub altgr_characters: bool,
+}
+
+impl Default for ModifiersConfig {
+    fn default() -> Self {
+        Self {
+            order: Modifier::ALL.to_vec(),
+            separator: " + ".to_string(),
+            altgr_characters: false,
+        }
+    }
+}
+
+impl ModifiersConfig {
+    // Every modifier exactly once, in display order
+    pub fn order(&self) -> Vec<Modifier> {
+        let mut order = Vec::new();
+        for modifier in self.order.iter().chain(Modifier::ALL.iter()) {
+            if !order.contains(modifier) {
+                order.push(*modifier);
+            }
+        }
+        order
+    }
+}
+
+// Key written by rdev name, like table keys in `labels`
+#[derive(Clone, Copy, Debug)]
+pub struct KeyName(pub Key);
+
+impl Serialize for KeyName {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("{:?}", self.0))
+    }
+}
+
+impl<'de> Deserialize<'de> for KeyName {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        key_from_name(&name)
+            .map(KeyName)
+            .ok_or_else(|| serde::de::Error::custom(format!("unknown key `{}`", name)))
+    }
+}
+
+// `[[webhooks]]` entry
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Webhook {
+    pub combo: Combo,
+    pub url: String,
+    // JSON body; by default the combo and the time it was pressed
+    pub payload: Option<serde_json::Value>,
+}
+
+// `[[commands]]` entry
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ComboCommand {
+    pub combo: Combo,
+    // Run by `sh -c`, or `cmd /C` on Windows
+    pub run: String,
+    // Print what the command writes; otherwise it is discarded
+    #[serde(default)]
+    pub log_output: bool,
+}
+
+// Regular expression in the config file
+#[derive(Clone, Debug)]
+pub struct Pattern(pub Regex);
+
+impl Serialize for Pattern {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.0.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for Pattern {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        Regex::new(&text)
+            .map(Pattern)
+            .map_err(|e| serde::de::Error::custom(format!("invalid pattern `{}`: {}", text, e)))
+    }
+}
+
+// `[[rules]]` entry: what to show for the combos it matches. Every condition given must match.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Rule {
+    // Exactly this combo, e.g. "Ctrl+S"
+    pub combo: Option<Combo>,
+    // Combo as written in the config file, e.g. "^Ctrl\\+F[0-9]+$" for Ctrl with any function key
+    pub combo_pattern: Option<Pattern>,
+    // Focused application name, e.g. "(?i)^code$"; rules with one never match where it isn't known
+    pub app: Option<Pattern>,
+    // Chip text; `{keys}` is replaced by what would have been shown, e.g. "{keys} Save"
+    pub label: Option<String>,
+    pub color: Option<Color>,
+    // SVG or PNG drawn before the text
+    pub icon: Option<PathBuf>,
+}
+
+impl Rule {
+    fn matches(&self, combo: &Combo, app: Option<&str>) -> bool {
+        self.combo.is_none_or(|wanted| wanted == *combo)
+            && self
+                .combo_pattern
+                .as_ref()
+                .is_none_or(|pattern| pattern.0.is_match(&combo.to_string()))
+            && self
+                .app
+                .as_ref()
+                .is_none_or(|pattern| app.is_some_and(|app| pattern.0.is_match(app)))
+    }
+}
+
+// What a remapper (kanata, kmonad, Karabiner) makes of a key: another key, or one key when
+// tapped and another while held
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+#[serde(untagged)]
+pub enum Remap {
+    Key(KeyName),
+    TapHold {
+        tap: KeyName,
+        hold: KeyName,
+        // Held longer than this, it's a hold even with nothing else pressed; 200 when unset
+        timeout_ms: Option<u64>,
+    },
+}
+
+impl Remap {
+    pub fn tap_timeout(&self) -> Duration {
+        match self {
+            Remap::TapHold {
+                timeout_ms: Some(timeout),
+                ..
+            } => Duration::from_millis(*timeout),
+            _ => Duration::from_millis(200),
+        }
+    }
+}
+
+// Tables keyed by rdev key name
+fn serialize_key_map<S: Serializer, V: Serialize>(
+    map: &[(Key, V)],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_map(map.iter().map(|(key, value)| (format!("{:?}", key), value)))
+}
+
+fn deserialize_key_map<'de, D: Deserializer<'de>, V: Deserialize<'de>>(
+    deserializer: D,
+) -> Result<Vec<(Key, V)>, D::Error> {
+    BTreeMap::<String, V>::deserialize(deserializer)?
+        .into_iter()
+        .map(|(name, value)| {
+            let key = key_from_name(&name)
+                .ok_or_else(|| serde::de::Error::custom(format!("unknown key `{}`", name)))?;
+            Ok((key, value))
+        })
+        .collect()
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct CursorConfig {
+    // Soft circle drawn around the mouse pointer
+    pub halo: bool,
+    pub halo_radius: f32,
+    pub halo_color: Color,
+    // Expanding ring at each mouse click, colored per button
+    pub ripples: bool,
+    pub ripple_radius: f32,
+    pub ripple_duration_ms: u64,
+    // Override the theme palette's button colors
+    pub left_color: Option<Color>,
+    pub right_color: Option<Color>,
+    pub middle_color: Option<Color>,
+    // Fading line through recent pointer positions
+    pub trail: bool,
+    pub trail_length: usize,
+    pub trail_duration_ms: u64,
+    pub trail_color: Color,
+}
+
+impl Default for CursorConfig {
+    fn default() -> Self {
+        Self {
+            halo: false,
+            halo_radius: 36.0,
+            halo_color: Color(egui::Color32::from_rgba_unmultiplied(255, 220, 60, 90)),
+            ripples: false,
+            ripple_radius: 40.0,
+            ripple_duration_ms: 500,
+            left_color: None,
+            right_color: None,
+            middle_color: None,
+            trail: false,
+            trail_length: 40,
+            trail_duration_ms: 600,
+            trail_color: Color(egui::Color32::from_rgb(255, 220, 60)),
+        }
+    }
+}
+
+impl CursorConfig {
+    // Whether anything needs the cursor overlay windows
+    pub fn enabled(&self) -> bool {
+        self.halo || self.ripples || self.trail
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Default, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum GamepadGlyphs {
+    // Pick from the controller name
+    #[default]
+    Auto,
+    Xbox,
+    PlayStation,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct GamepadConfig {
+    // Requires the `gamepad` cargo feature, which is opt-in (`--features gamepad`)
+    pub enabled: bool,
+    pub glyphs: GamepadGlyphs,
+    // Stick deflection (0-1) before a direction chip is shown
+    pub stick_threshold: f32,
+}
+
+impl Default for GamepadConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            glyphs: GamepadGlyphs::Auto,
+            stick_threshold: 0.6,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct HistoryConfig {
+    // Keep the history window's entries across restarts by appending them to `path`. Off by
+    // default: this writes everything shown, passwords typed while visible included
+    pub persist: bool,
+    pub path: PathBuf,
+    // The file is moved to `<path>.1` past this size (1 to 10000), replacing the previous one
+    pub max_size_mb: u64,
+    // Chips the `copy-keys` action puts on the clipboard
+    pub copy_count: usize,
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self {
+            persist: false,
+            path: paths::data_dir().join("rusteys-history.jsonl"),
+            max_size_mb: 10,
+            copy_count: 10,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct MacrosConfig {
+    // Library file `macro-record` saves named combo sequences to
+    pub path: PathBuf,
+    // Show a "⟶ <name>" chip when a library macro is pressed
+    pub show_names: bool,
+    // Longest pause between two combos of the same macro
+    pub max_gap_ms: u64,
+}
+
+impl Default for MacrosConfig {
+    fn default() -> Self {
+        Self {
+            path: paths::data_dir().join("rusteys-macros.toml"),
+            show_names: true,
+            max_gap_ms: 2000,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct HttpConfig {
+    // Local REST API for stream decks and automation
+    pub enabled: bool,
+    // Always bound to 127.0.0.1
+    pub port: u16,
+    // Required as `Authorization: Bearer <token>` or `?token=`; when empty, one is generated and
+    // kept in `http-token` in the data folder
+    pub token: String,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 7373,
+            token: String::new(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct OscConfig {
+    // Send each chip as an OSC message over UDP
+    pub enabled: bool,
+    // host:port of the receiving application
+    pub target: String,
+    pub address: String,
+}
+
+impl Default for OscConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            target: "127.0.0.1:9000".to_string(),
+            address: "/rusteys/key".to_string(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Default, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum RemoteMode {
+    #[default]
+    Off,
+    // Forward chips to the receiver at `address`
+    Send,
+    // Listen on `address` and show the sender's chips instead of local input
+    Receive,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct RemoteConfig {
+    pub mode: RemoteMode,
+    pub address: String,
+    // Shared by sender and receiver; required to receive on anything but loopback
+    pub secret: String,
+}
+
+impl Default for RemoteConfig {
+    fn default() -> Self {
+        Self {
+            mode: RemoteMode::Off,
+            address: "127.0.0.1:7374".to_string(),
+            secret: String::new(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct BigKeyConfig {
+    // Extra window showing only the latest chip, e.g. for a slide corner
+    pub enabled: bool,
+    pub font_size: f32,
+}
+
+impl Default for BigKeyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            font_size: 96.0,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct WhichKeyConfig {
+    // File with the continuations of prefix combos like VS Code's Ctrl+K, shown after the prefix;
+    // read at startup
+    pub bindings: Option<PathBuf>,
+    // Hide the hints when no key follows the prefix for this long
+    pub timeout_ms: u64,
+}
+
+impl Default for WhichKeyConfig {
+    fn default() -> Self {
+        Self {
+            bindings: None,
+            timeout_ms: 2000,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Default, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum ClockMode {
+    #[default]
+    Off,
+    // Local time of day
+    Time,
+    // Time since the last `recording-start` action
+    Recording,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Default, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub enum Corner {
+    #[default]
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct ClockConfig {
+    // A time reference burned into recordings next to the keys
+    pub show: ClockMode,
+    // strftime-style, e.g. "%H:%M:%S" or "%Y-%m-%d %H:%M"; for `time` only
+    pub format: String,
+    pub corner: Corner,
+    pub font_size: f32,
+}
+
+impl Default for ClockConfig {
+    fn default() -> Self {
+        Self {
+            show: ClockMode::Off,
+            format: "%H:%M:%S".to_string(),
+            corner: Corner::TopLeft,
+            font_size: 14.0,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct WindowTitleConfig {
+    // Name the focused window so viewers know which document the shortcuts apply to
+    pub show: bool,
+    // Put the application name in front, e.g. "Code · README.md"
+    pub show_app: bool,
+    // Longer titles are cut off with "…"
+    pub max_length: usize,
+    // Titles matching any of these are never shown, e.g. "(?i)inbox|bank"
+    pub hide: Vec<Pattern>,
+    // Parts of titles matching any of these are replaced by "•••", e.g. "[\\w.+-]+@[\\w.-]+"
+    pub redact: Vec<Pattern>,
+    pub corner: Corner,
+    pub font_size: f32,
+}
+
+impl Default for WindowTitleConfig {
+    fn default() -> Self {
+        Self {
+            show: false,
+            show_app: true,
+            max_length: 60,
+            hide: Vec::new(),
+            redact: Vec::new(),
+            corner: Corner::TopRight,
+            font_size: 14.0,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct ScreenshotConfig {
+    // What the `screenshot` action does with the image of the panel
+    pub copy: bool,
+    pub save: bool,
+    // Where saved images go, named by date and time
+    pub dir: PathBuf,
+}
+
+impl Default for ScreenshotConfig {
+    fn default() -> Self {
+        Self {
+            copy: true,
+            save: false,
+            dir: paths::data_dir().join("screenshots"),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Default, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum ReplayFormat {
+    // Only fully transparent or opaque pixels
+    Gif,
+    // Animated PNG, keeping the panel's translucency
+    #[default]
+    Apng,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct ReplayConfig {
+    // Keep the last few seconds of the panel in memory for `export-replay`
+    pub enabled: bool,
+    pub seconds: u64,
+    // Frames captured a second, at most; only while something changes
+    pub fps: u32,
+    pub format: ReplayFormat,
+}
+
+impl Default for ReplayConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            seconds: 10,
+            fps: 15,
+            format: ReplayFormat::Apng,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct CheatSheetsConfig {
+    // Folder of per-application cheat sheets for the `cheat-sheet` action
+    pub dir: PathBuf,
+}
+
+impl Default for CheatSheetsConfig {
+    fn default() -> Self {
+        Self {
+            dir: paths::config_dir().join("cheat-sheets"),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct WebSocketConfig {
+    // Stream chips as JSON messages to WebSocket clients
+    pub enabled: bool,
+    pub address: String,
+    // Web pages allowed to connect besides localhost ones, e.g. "https://overlay.example.com";
+    // clients that aren't browsers send no origin and are always let in
+    pub allowed_origins: Vec<String>,
+}
+
+impl Default for WebSocketConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            address: "127.0.0.1:7375".to_string(),
+            allowed_origins: Vec::new(),
+        }
+    }
+}
+
+impl Config {
+    pub fn load() -> Self {
+        let result = Self::read().and_then(|config| match config.profile.clone() {
+            Some(profile) => config.with_profile(Some(&profile)),
+            None => Ok(config),
+        });
+
+        match result {
+            Ok(config) => config,
+            Err(error) => {
+                eprintln!(
+                    "Error loading {}: {}; using defaults",
+                    paths::config_file().display(),
+                    error
+                );
+                Self::default()
+            }
+        }
+    }
+
+    // Settings that can't be used are left at their defaults and reported; only a file that
+    // isn't TOML at all is an error
+    fn read() -> Result<Self, String> {
+        let path = paths::config_file();
+        if !path.exists() {
+            validation::report(Vec::new());
+            return Self::from_table(toml::Table::new());
+        }
+
+        let text = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
+        match validation::parse(&text) {
+            Ok((config, problems)) => {
+                for problem in &problems {
+                    eprintln!("{} {}", path.display(), problem);
+                }
+                validation::report(problems);
+                Ok(config)
+            }
+            Err(problem) => {
+                let message = problem.to_string();
+                validation::report(vec![problem]);
+                Err(message)
+            }
+        }
+    }
+
+    // Write the config file's settings with every default filled in, for sharing: RUSTEYS_*
+    // variables aren't included, and neither are tokens, secrets or webhook URLs
+    pub fn export(path: &Path) -> Result<(), String> {
+        let config = Self::read()?;
+        // `read` falls back to the defaults when the settings can't be used together, and those
+        // aren't the user's setup
+        let written = std::fs::read_to_string(paths::config_file())
+            .ok()
+            .and_then(|text| toml::from_str::<toml::Table>(&text).ok())
+            .is_some_and(|table| !table.is_empty());
+        if written && config.raw.is_empty() {
+            return Err(format!(
+                "{} could not be used, so there is nothing to export; fix the problems above first",
+                paths::config_file().display()
+            ));
+        }
+        let mut shared: Config = toml::Value::Table(config.raw.clone())
+            .try_into()
+            .map_err(|e: toml::de::Error| e.to_string())?;
+        let withheld = shared.withhold_secrets();
+        let text = toml::to_string_pretty(&shared).map_err(|e| e.to_string())?;
+        std::fs::write(path, text).map_err(|e| format!("{}: {}", path.display(), e))?;
+        if !withheld.is_empty() {
+            println!(
+                "Left out of {}, to be filled in after importing: {}",
+                path.display(),
+                withheld.join(", ")
+            );
+        }
+        Ok(())
+    }
+
+    // Blank every setting that grants access to something, and return the ones that were set
+    fn withhold_secrets(&mut self) -> Vec<String> {
+        let mut withheld = Vec::new();
+        if !self.http.token.is_empty() {
+            self.http.token.clear();
+            withheld.push("http.token".to_string());
+        }
+        if !self.remote.secret.is_empty() {
+            self.remote.secret.clear();
+            withheld.push("remote.secret".to_string());
+        }
+        if self.webhooks.iter().any(|webhook| !webhook.url.is_empty()) {
+            for webhook in &mut self.webhooks {
+                webhook.url.clear();
+            }
+            withheld.push("webhooks.url".to_string());
+        }
+        for (name, overrides) in &mut self.profiles {
+            for (section, key) in [("http", "token"), ("remote", "secret")] {
+                if let Some(toml::Value::Table(table)) = overrides.get_mut(section)
+                    && table.remove(key).is_some()
+                {
+                    withheld.push(format!("profiles.{}.{}.{}", name, section, key));
+                }
+            }
+            if overrides.remove("webhooks").is_some() {
+                withheld.push(format!("profiles.{}.webhooks", name));
+            }
+        }
+        withheld
+    }
+
+    // Replace the config file with `path` once it is known to parse, keeping a backup
+    pub fn import(path: &Path) -> Result<(), String> {
+        let text =
+            std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        toml::from_str(&text)
+            .map_err(|e: toml::de::Error| format!("{}: {}", path.display(), e))
+            .and_then(Self::from_table)?;
+
+        let config_file = paths::config_file();
+        if config_file.exists() {
+            let backup = paths::backup_file();
+            std::fs::copy(&config_file, &backup)
+                .map_err(|e| format!("{}: {}", backup.display(), e))?;
+        }
+        paths::create_parent(&config_file)
+            .and_then(|()| std::fs::write(&config_file, text))
+            .map_err(|e| format!("{}: {}", config_file.display(), e))
+    }
+
+    // `table` with any RUSTEYS_* environment variables applied over it
+    pub fn from_table(table: toml::Table) -> Result<Self, String> {
+        let mut effective = table.clone();
+        merge(&mut effective, environment::overrides());
+        let mut config: Config = toml::Value::Table(effective)
+            .try_into()
+            .map_err(|e: toml::de::Error| e.to_string())?;
+        config.raw = table;
+        config.resolve_paths();
+        Ok(config)
+    }
+
+    fn resolve_paths(&mut self) {
+        let optional = [
+            &mut self.keyboard_layout,
+            &mut self.script,
+            &mut self.symbol_font,
+            &mut self.which_key.bindings,
+        ];
+        for path in optional.into_iter().flatten() {
+            paths::resolve(path);
+        }
+        for (_, path) in &mut self.icons {
+            paths::resolve(path);
+        }
+        for path in &mut self.plugins {
+            paths::resolve(path);
+        }
+        for path in self.rules.iter_mut().filter_map(|rule| rule.icon.as_mut()) {
+            paths::resolve(path);
+        }
+        paths::resolve(&mut self.history.path);
+        paths::resolve(&mut self.macros.path);
+        paths::resolve(&mut self.screenshot.dir);
+        paths::resolve(&mut self.cheat_sheets.dir);
+    }
+
+    // These settings with a profile's overrides merged in, or without any for `None`
+    pub fn with_profile(&self, name: Option<&str>) -> Result<Self, String> {
+        let mut table = self.raw.clone();
+        if let Some(name) = name {
+            // The presentation profile is built in, unless the file defines its own
+            let overrides = match self.profiles.get(name) {
+                Some(overrides) => overrides.clone(),
+                None if name == PRESENTATION_PROFILE => presentation_profile(),
+                None => return Err(format!("no profile named `{}`", name)),
+            };
+            merge(&mut table, &overrides);
+        }
+
+        let mut config = Self::from_table(table)?;
+        config.raw = self.raw.clone();
+        config.active_profile = name.map(str::to_string);
+        Ok(config)
+    }
+
+    // How a key is written on chips
+    pub fn label(&self, key: Key) -> String {
+        self.labels
+            .iter()
+            .find(|(labeled, _)| *labeled == key)
+            .map(|(_, label)| label.clone())
+            .or_else(|| {
+                key_symbol(key)
+                    .filter(|_| self.key_symbols)
+                    .map(str::to_string)
+            })
+            .unwrap_or_else(|| key_to_string(key))
+    }
+
+    // Icon for a chip part written like a key with an icon, whether it's the key itself or a modifier
+    pub fn icon(&self, part: &str) -> Option<&Path> {
+        self.icons
+            .iter()
+            .find(|(key, _)| self.label(*key) == part)
+            .map(|(_, path)| path.as_path())
+    }
+
+    // Chip prefix and fill for events from the named device
+    pub fn device_style(
+        &self,
+        device: Option<&DeviceInfo>,
+    ) -> (Option<String>, Option<egui::Color32>) {
+        let Some(device) = device else {
+            return (None, None);
+        };
+        let configured = self.device(device);
+        let label = self.show_device_names.then(|| {
+            configured
+                .and_then(|style| style.label.clone())
+                .unwrap_or_else(|| device.name.clone())
+        });
+        (
+            label,
+            configured
+                .and_then(|style| style.color)
+                .map(|color| color.0),
+        )
+    }
+
+    pub fn remapped(&self, key: Key) -> Option<Remap> {
+        self.remap
+            .iter()
+            .find(|(remapped, _)| *remapped == key)
+            .map(|(_, remap)| *remap)
+    }
+
+    // First rule for `combo` pressed while `app` has focus
+    pub fn rule(&self, combo: &Combo, app: Option<&str>) -> Option<&Rule> {
+        self.rules.iter().find(|rule| rule.matches(combo, app))
+    }
+
+    pub fn ignores_device(&self, device: &DeviceInfo) -> bool {
+        self.device(device).is_some_and(|settings| settings.ignore)
+    }
+
+    fn device(&self, device: &DeviceInfo) -> Option<&DeviceConfig> {
+        self.devices
+            .iter()
+            .find(|(pattern, _)| device.matches(pattern))
+            .map(|(_, settings)| settings)
+    }
+
+    pub fn max_chips(&self) -> usize {
+        self.max_chips.unwrap_or(DEFAULT_MAX_CHIPS).max(1)
+    }
+
+    // Whether bare pointer motion changes what is drawn
+    pub fn tracks_pointer(&self) -> bool {
+        self.cursor.enabled() || self.position.placement == Placement::Cursor
+    }
+
+    // Profile after the active one, in name order, wrapping around through no profile
+    pub fn next_profile(&self) -> Option<String> {
+        let mut names = self.profiles.keys();
+        match &self.active_profile {
+            None => names.next().cloned(),
+            Some(active) => names.skip_while(|name| *name != active).nth(1).cloned(),
+        }
+    }
+}
+
+// Bundled profile for conference demos: big text, few long-lived chips, only shortcuts
+pub const PRESENTATION_PROFILE: &str = "presentation";
+
+const PRESENTATION_SETTINGS: &str = r#"
+max_chips = 5
+shortcuts_only = true
+
+[theme]
+font_size = 40.0
+
+[durations]
+shortcut_ms = 6000
+key_ms = 6000
+modifier_ms = 3000
+mouse_ms = 4000
+"#;
+
+fn presentation_profile() -> toml::Table {
+    toml::from_str(PRESENTATION_SETTINGS).expect("built-in profile is valid")
+}
+
+pub fn merge(base: &mut toml::Table, overrides: &toml::Table) {
+    for (key, value) in overrides {
+        match (base.get_mut(key), value) {
+            (Some(toml::Value::Table(base)), toml::Value::Table(overrides)) => {
+                merge(base, overrides)
+            }
+            _ => {
+                base.insert(key.clone(), value.clone());
+            }
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(default)]
+pub struct State {
+    // Chip size relative to the theme, from the zoom hotkeys
+    pub zoom: f32,
+    // Where the panel was last snapped to by dragging it
+    pub anchor: Option<Anchor>,
+}
+
+impl Default for State {
+    fn default() -> Self {
+        Self {
+            zoom: 1.0,
+            anchor: None,
+        }
+    }
+}
+
+impl State {
+    pub fn load() -> Self {
+        let path = paths::state_file();
+        if !path.exists() {
+            return Self::default();
+        }
+
+        let result = std::fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|text| toml::from_str(&text).map_err(|e| e.to_string()));
+        match result {
+            Ok(state) => state,
+            Err(error) => {
+                eprintln!("Error loading {}: {}", path.display(), error);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn save(&self) {
+        let path = paths::state_file();
+        let result = toml::to_string(self)
+            .map_err(|e| e.to_string())
+            .and_then(|text| {
+                paths::create_parent(&path)
+                    .and_then(|()| std::fs::write(&path, text))
+                    .map_err(|e| e.to_string())
+            });
+        if let Err(error) = result {
+            eprintln!("Error saving {}: {}", path.display(), error);
+        }
+    }
+}
+
+// Zoom steps and drags come in bursts; the state is written once they settle, on a thread of its
+// own rather than the input hook's or the UI's
+const STATE_SAVE_DELAY: Duration = Duration::from_millis(500);
+
+pub struct StateSaver {
+    sender: mpsc::Sender<State>,
+}
+
+impl StateSaver {
+    pub fn spawn() -> Self {
+        let (sender, states) = mpsc::channel::<State>();
+        thread::spawn(move || {
+            while let Ok(mut state) = states.recv() {
+                while let Ok(newer) = states.recv_timeout(STATE_SAVE_DELAY) {
+                    state = newer;
+                }
+                state.save();
+            }
+        });
+        Self { sender }
+    }
+
+    pub fn save(&self, state: State) {
+        let _ = self.sender.send(state);
+    }
+}
+
+// Reload the config file whenever it changes, keeping the old settings if it doesn't parse
+pub fn watch(shared: SharedConfig, repaint: Repaint) {
+    thread::spawn(move || {
+        let config_file = paths::config_file();
+        let dir = match config_file.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+        let (sender, events) = mpsc::channel();
+        let watcher = notify::recommended_watcher(sender).and_then(|mut watcher| {
+            // Watch the directory, since editors may replace the file rather than write to it
+            std::fs::create_dir_all(&dir)?;
+            watcher.watch(&dir, RecursiveMode::NonRecursive)?;
+            Ok(watcher)
+        });
+        let _watcher = match watcher {
+            Ok(watcher) => watcher,
+            Err(error) => {
+                eprintln!("Error watching {}: {}", config_file.display(), error);
+                return;
+            }
+        };
+
+        while let Ok(event) = events.recv() {
+            let Ok(event) = event else {
+                continue;
+            };
+            if !event
+                .paths
+                .iter()
+                .any(|path| path.file_name() == config_file.file_name())
+            {
+                continue;
+            }
+
+            thread::sleep(RELOAD_DEBOUNCE);
+            while events.try_recv().is_ok() {}
+
+            // Stay on the same profile across reloads
+            let active = shared.read().active_profile.clone();
+            let result = Config::read().and_then(|config| match &active {
+                Some(profile) => config.with_profile(Some(profile)),
+                None => Ok(config),
+            });
+
+            match result {
+                Ok(config) => {
+                    *shared.write() = Arc::new(config);
+                    repaint.request();
+                    println!("Reloaded {}", config_file.display());
+                }
+                Err(error) => eprintln!(
+                    "Error reloading {}: {}; keeping previous settings",
+                    config_file.display(),
+                    error
+                ),
+            }
+        }
+    });
+}
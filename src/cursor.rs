@@ -0,0 +1,166 @@
+use crate::config::{CursorConfig, Palette};
+use crate::MonitorInfo;
+use crate::window_level;
+use eframe::egui;
+use parking_lot::Mutex;
+use rdev::Button;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+// Mouse state shared between the rdev listener and the cursor overlay
+#[derive(Default)]
+pub struct CursorState {
+    // Last pointer position in screen pixels
+    pub position: Option<egui::Pos2>,
+    pub ripples: Vec<Ripple>,
+    // Recent pointer positions, oldest first
+    pub trail: VecDeque<(egui::Pos2, Instant)>,
+}
+
+pub struct Ripple {
+    pub position: egui::Pos2,
+    pub button: Button,
+    pub timestamp: Instant,
+}
+
+impl CursorState {
+    pub fn move_to(&mut self, position: egui::Pos2, trail_length: usize) {
+        self.position = Some(position);
+        if trail_length > 0 {
+            self.trail.push_back((position, Instant::now()));
+            while self.trail.len() > trail_length {
+                self.trail.pop_front();
+            }
+        }
+    }
+
+    // Start a click ripple at the current pointer position. Expired ripples are dropped here too,
+    // since the overlay that otherwise prunes them may be closed
+    pub fn click(&mut self, button: Button, config: &CursorConfig) {
+        if !config.ripples {
+            return;
+        }
+        let ripple_duration = Duration::from_millis(config.ripple_duration_ms);
+        self.ripples
+            .retain(|ripple| ripple.timestamp.elapsed() < ripple_duration);
+        if let Some(position) = self.position {
+            self.ripples.push(Ripple {
+                position,
+                button,
+                timestamp: Instant::now(),
+            });
+        }
+    }
+}
+
+const HALO_STEPS: usize = 6;
+
+// Show a click-through transparent window over each monitor that draws
+// cursor effects at the pointer position
+pub fn show_cursor_overlays(
+    ctx: &egui::Context,
+    monitors: &[MonitorInfo],
+    cursor: &Arc<Mutex<CursorState>>,
+    config: &CursorConfig,
+    palette: Palette,
+) {
+    let ripple_duration = Duration::from_millis(config.ripple_duration_ms);
+    let trail_duration = Duration::from_millis(config.trail_duration_ms);
+    {
+        let mut state = cursor.lock();
+        state
+            .ripples
+            .retain(|ripple| ripple.timestamp.elapsed() < ripple_duration);
+        state
+            .trail
+            .retain(|(_, timestamp)| timestamp.elapsed() < trail_duration);
+    }
+
+    for (i, monitor) in monitors.iter().enumerate() {
+        ctx.show_viewport_immediate(
+            egui::ViewportId::from_hash_of(format!("cursor_{}", i)),
+            egui::ViewportBuilder::default()
+                .with_title(format!("Cursor Overlay - Monitor {}", i + 1))
+                .with_inner_size(monitor.rect().size())
+                .with_position(monitor.rect().min)
+                .with_decorations(false)
+                .with_transparent(true)
+                .with_always_on_top()
+                .with_resizable(false)
+                .with_taskbar(false)
+                .with_mouse_passthrough(true),
+            |ctx, _class| render_cursor_overlay(ctx, cursor, config, palette),
+        );
+        window_level::raise(&format!("Cursor Overlay - Monitor {}", i + 1));
+    }
+}
+
+fn render_cursor_overlay(
+    ctx: &egui::Context,
+    cursor: &Arc<Mutex<CursorState>>,
+    config: &CursorConfig,
+    palette: Palette,
+) {
+    let state = cursor.lock();
+    let Some(position) = state.position else {
+        return;
+    };
+
+    // rdev reports physical pixels; convert to points relative to this window
+    let window_origin = ctx
+        .input(|i| i.viewport().inner_rect)
+        .map_or(egui::Pos2::ZERO, |rect| rect.min);
+    let pixels_per_point = ctx.pixels_per_point();
+    let to_local = |screen: egui::Pos2| (screen.to_vec2() / pixels_per_point).to_pos2() - window_origin.to_vec2();
+    let center = to_local(position);
+
+    let painter = ctx.layer_painter(egui::LayerId::new(
+        egui::Order::Foreground,
+        egui::Id::new("cursor_overlay"),
+    ));
+
+    if config.trail {
+        let duration = config.trail_duration_ms.max(1) as f32;
+        for ((from, _), (to, timestamp)) in state.trail.iter().zip(state.trail.iter().skip(1)) {
+            // Newer segments are thicker and more opaque
+            let freshness = 1.0 - (timestamp.elapsed().as_millis() as f32 / duration).min(1.0);
+            painter.line_segment(
+                [to_local(*from), to_local(*to)],
+                egui::Stroke::new(
+                    1.0 + 4.0 * freshness,
+                    config.trail_color.0.gamma_multiply(freshness),
+                ),
+            );
+        }
+    }
+
+    if config.halo {
+        // Stack translucent circles of decreasing radius for a soft edge
+        let step_color = config.halo_color.0.gamma_multiply(1.0 / HALO_STEPS as f32);
+        for step in 0..HALO_STEPS {
+            let radius = config.halo_radius * (1.0 - step as f32 / HALO_STEPS as f32 * 0.6);
+            painter.circle_filled(center, radius, step_color);
+        }
+    }
+
+    if config.ripples {
+        let [left, right, middle] = palette.buttons();
+        let duration = config.ripple_duration_ms.max(1) as f32;
+        for ripple in &state.ripples {
+            let progress = (ripple.timestamp.elapsed().as_millis() as f32 / duration).min(1.0);
+            // Ease out so the ring expands quickly and settles
+            let radius = config.ripple_radius * (1.0 - (1.0 - progress).powi(2));
+            let color = match ripple.button {
+                Button::Left => config.left_color.map_or(left, |color| color.0),
+                Button::Right => config.right_color.map_or(right, |color| color.0),
+                _ => config.middle_color.map_or(middle, |color| color.0),
+            };
+            painter.circle_stroke(
+                to_local(ripple.position),
+                radius,
+                egui::Stroke::new(3.0, color.gamma_multiply(1.0 - progress)),
+            );
+        }
+    }
+}
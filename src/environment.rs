@@ -0,0 +1,66 @@
+use crate::config;
+use crate::validation;
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+
+// `RUSTEYS_THEME__PANEL_OPACITY=80` sets `panel_opacity` in [theme]: a double underscore
+// separates sections, and names are the settings' in upper case
+const PREFIX: &str = "RUSTEYS_";
+
+// Settings from RUSTEYS_* variables, which win over the config file and any profile. Read once,
+// since nothing outside can change a running process's environment
+pub fn overrides() -> &'static toml::Table {
+    static OVERRIDES: OnceLock<toml::Table> = OnceLock::new();
+    OVERRIDES.get_or_init(read)
+}
+
+fn read() -> toml::Table {
+    // Sorted, so `RUSTEYS_THEME__MODE` is merged after a whole `RUSTEYS_THEME` table and wins
+    let variables: BTreeMap<String, String> = std::env::vars_os()
+        .filter_map(|(name, value)| Some((name.into_string().ok()?, value.into_string().ok()?)))
+        .filter(|(name, _)| name.starts_with(PREFIX))
+        .collect();
+
+    let mut table = toml::Table::new();
+    let mut names = Vec::new();
+    for (name, value) in &variables {
+        let path: Vec<String> = name[PREFIX.len()..]
+            .split("__")
+            .map(|part| part.to_lowercase())
+            .collect();
+        if path.iter().any(String::is_empty) {
+            eprintln!("{}: not a setting, ignored", name);
+            continue;
+        }
+        let single = path.iter().rev().fold(parse_value(value), |value, key| {
+            toml::Value::Table(toml::Table::from_iter([(key.clone(), value)]))
+        });
+        if let toml::Value::Table(single) = single {
+            config::merge(&mut table, &single);
+        }
+        names.push((path.join("."), name));
+    }
+
+    let (table, problems) = validation::check(table);
+    for problem in problems {
+        // The variable that set it, or one that set the whole section around it
+        let name = names
+            .iter()
+            .rev()
+            .find(|(key, _)| problem.key == *key || problem.key.starts_with(&format!("{}.", key)));
+        match name {
+            Some((_, name)) => eprintln!("{}: {}", name, problem.message),
+            None => eprintln!("{}", problem),
+        }
+    }
+    table
+}
+
+// TOML for numbers, booleans, arrays and inline tables; anything else is taken as a string, so
+// `RUSTEYS_POSITION__PLACEMENT=cursor` needs no quotes
+fn parse_value(text: &str) -> toml::Value {
+    toml::from_str::<toml::Table>(&format!("value = {}", text))
+        .ok()
+        .and_then(|mut table| table.remove("value"))
+        .unwrap_or_else(|| toml::Value::String(text.to_string()))
+}
@@ -0,0 +1,84 @@
+use crate::i18n::{Text, tr, tr_args};
+use eframe::egui;
+use parking_lot::Mutex;
+use std::sync::Arc;
+
+// An input backend that stopped with an error, shown to the user with a fix
+pub struct BackendError {
+    pub backend: &'static str,
+    pub message: String,
+    // Dismissed errors only keep the overlay's "input stopped" marker
+    pub dismissed: bool,
+}
+
+pub type BackendErrors = Arc<Mutex<Vec<BackendError>>>;
+
+// Why rdev can fail differs by platform
+fn rdev_help() -> Text {
+    if cfg!(target_os = "macos") {
+        Text::RdevHelpMacos
+    } else if cfg!(target_os = "linux") {
+        Text::RdevHelpLinux
+    } else if cfg!(windows) {
+        Text::RdevHelpWindows
+    } else {
+        Text::RdevHelpOther
+    }
+}
+
+// Names of the backends no longer capturing, for the overlay's status marker
+pub fn stopped_backends(errors: &BackendErrors) -> Vec<&'static str> {
+    let mut names: Vec<_> = errors.lock().iter().map(|error| error.backend).collect();
+    names.sort_unstable();
+    names.dedup();
+    names
+}
+
+fn remediation(backend: &str) -> &'static str {
+    match backend {
+        "rdev" => tr(rdev_help()),
+        "pen" | "touch" => tr(Text::InputGroupHelp),
+        "gamepad" => tr(Text::GamepadHelp),
+        "remote" => tr(Text::RemoteHelp),
+        _ => "",
+    }
+}
+
+// Window explaining failed input backends, until the user dismisses it
+pub fn show_error_dialog(ctx: &egui::Context, errors: &BackendErrors) {
+    if errors.lock().iter().all(|error| error.dismissed) {
+        return;
+    }
+
+    ctx.show_viewport_immediate(
+        egui::ViewportId::from_hash_of("input_errors"),
+        egui::ViewportBuilder::default()
+            .with_title(tr(Text::InputProblemTitle))
+            .with_inner_size([520.0, 320.0])
+            .with_always_on_top(),
+        |ctx, _class| {
+            let mut dismissed = ctx.input(|i| i.viewport().close_requested());
+
+            egui::CentralPanel::default().show(ctx, |ui| {
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    for error in errors.lock().iter().filter(|error| !error.dismissed) {
+                        ui.heading(tr_args(Text::InputUnavailable, &[&error.backend]));
+                        ui.label(egui::RichText::new(&error.message).monospace());
+                        ui.add_space(4.0);
+                        ui.label(remediation(error.backend));
+                        ui.separator();
+                    }
+                });
+                if ui.button(tr(Text::Dismiss)).clicked() {
+                    dismissed = true;
+                }
+            });
+
+            if dismissed {
+                for error in errors.lock().iter_mut() {
+                    error.dismissed = true;
+                }
+            }
+        },
+    );
+}
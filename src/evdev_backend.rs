@@ -0,0 +1,216 @@
+use crate::hotplug;
+use crate::input::{DeviceInfo, EventSink, InputBackend, InputEvent};
+use evdev::{InputEventKind, Key, RelativeAxisType};
+use parking_lot::Mutex;
+use rdev::{Button, EventType};
+use std::sync::Arc;
+use std::time::SystemTime;
+
+// Keyboards and mice read from /dev/input (Linux only); works where X11 hooks don't, e.g. Wayland.
+// Devices aren't grabbed, so hotkeys can't be suppressed, and mice only report relative motion,
+// so there is no pointer position and no MouseMove
+pub struct EvdevBackend;
+
+impl InputBackend for EvdevBackend {
+    fn name(&self) -> &'static str {
+        "evdev"
+    }
+
+    fn run(self: Box<Self>, sink: EventSink) -> Result<(), String> {
+        hotplug::read_devices("keyboard or mouse", is_keyboard_or_mouse, read_device, sink)
+    }
+}
+
+fn is_keyboard_or_mouse(device: &evdev::Device) -> bool {
+    device
+        .supported_keys()
+        .is_some_and(|keys| keys.contains(Key::KEY_A) || keys.contains(Key::BTN_LEFT))
+}
+
+fn read_device(mut device: evdev::Device, sink: Arc<Mutex<EventSink>>) -> Result<(), String> {
+    // Lets chips tell a macro pad from the laptop keyboard
+    let info = DeviceInfo {
+        name: device.name().unwrap_or("Unknown device").to_string(),
+        id: format!(
+            "{:04x}:{:04x}",
+            device.input_id().vendor(),
+            device.input_id().product()
+        ),
+    };
+    loop {
+        let events = device.fetch_events().map_err(|error| error.to_string())?;
+        for event in events {
+            let value = event.value();
+            let event_type = match event.kind() {
+                // 0 is release, 1 press and 2 autorepeat, which is not shown
+                InputEventKind::Key(key) if value != 2 => match (mouse_button(key), value == 1) {
+                    (Some(button), true) => EventType::ButtonPress(button),
+                    (Some(button), false) => EventType::ButtonRelease(button),
+                    (None, true) => EventType::KeyPress(rdev_key(key)),
+                    (None, false) => EventType::KeyRelease(rdev_key(key)),
+                },
+                InputEventKind::RelAxis(axis) if axis == RelativeAxisType::REL_WHEEL => {
+                    EventType::Wheel {
+                        delta_x: 0,
+                        delta_y: value as i64,
+                    }
+                }
+                InputEventKind::RelAxis(axis) if axis == RelativeAxisType::REL_HWHEEL => {
+                    EventType::Wheel {
+                        delta_x: value as i64,
+                        delta_y: 0,
+                    }
+                }
+                _ => continue,
+            };
+
+            // Whether to suppress is ignored; the event has already reached other applications
+            let mut sink = sink.lock();
+            (*sink)(InputEvent::Tagged(
+                rdev::Event {
+                    time: SystemTime::now(),
+                    name: None,
+                    event_type,
+                },
+                info.clone(),
+            ));
+        }
+    }
+}
+
+fn mouse_button(key: Key) -> Option<Button> {
+    if key == Key::BTN_LEFT {
+        Some(Button::Left)
+    } else if key == Key::BTN_RIGHT {
+        Some(Button::Right)
+    } else if key == Key::BTN_MIDDLE {
+        Some(Button::Middle)
+    } else if key == Key::BTN_SIDE {
+        Some(Button::Unknown(8))
+    } else if key == Key::BTN_EXTRA {
+        Some(Button::Unknown(9))
+    } else {
+        None
+    }
+}
+
+// evdev key codes and the rdev keys the rest of rusteys understands
+const KEYS: &[(Key, rdev::Key)] = &[
+    (Key::KEY_ESC, rdev::Key::Escape),
+    (Key::KEY_1, rdev::Key::Num1),
+    (Key::KEY_2, rdev::Key::Num2),
+    (Key::KEY_3, rdev::Key::Num3),
+    (Key::KEY_4, rdev::Key::Num4),
+    (Key::KEY_5, rdev::Key::Num5),
+    (Key::KEY_6, rdev::Key::Num6),
+    (Key::KEY_7, rdev::Key::Num7),
+    (Key::KEY_8, rdev::Key::Num8),
+    (Key::KEY_9, rdev::Key::Num9),
+    (Key::KEY_0, rdev::Key::Num0),
+    (Key::KEY_MINUS, rdev::Key::Minus),
+    (Key::KEY_EQUAL, rdev::Key::Equal),
+    (Key::KEY_BACKSPACE, rdev::Key::Backspace),
+    (Key::KEY_TAB, rdev::Key::Tab),
+    (Key::KEY_Q, rdev::Key::KeyQ),
+    (Key::KEY_W, rdev::Key::KeyW),
+    (Key::KEY_E, rdev::Key::KeyE),
+    (Key::KEY_R, rdev::Key::KeyR),
+    (Key::KEY_T, rdev::Key::KeyT),
+    (Key::KEY_Y, rdev::Key::KeyY),
+    (Key::KEY_U, rdev::Key::KeyU),
+    (Key::KEY_I, rdev::Key::KeyI),
+    (Key::KEY_O, rdev::Key::KeyO),
+    (Key::KEY_P, rdev::Key::KeyP),
+    (Key::KEY_LEFTBRACE, rdev::Key::LeftBracket),
+    (Key::KEY_RIGHTBRACE, rdev::Key::RightBracket),
+    (Key::KEY_ENTER, rdev::Key::Return),
+    (Key::KEY_LEFTCTRL, rdev::Key::ControlLeft),
+    (Key::KEY_A, rdev::Key::KeyA),
+    (Key::KEY_S, rdev::Key::KeyS),
+    (Key::KEY_D, rdev::Key::KeyD),
+    (Key::KEY_F, rdev::Key::KeyF),
+    (Key::KEY_G, rdev::Key::KeyG),
+    (Key::KEY_H, rdev::Key::KeyH),
+    (Key::KEY_J, rdev::Key::KeyJ),
+    (Key::KEY_K, rdev::Key::KeyK),
+    (Key::KEY_L, rdev::Key::KeyL),
+    (Key::KEY_SEMICOLON, rdev::Key::SemiColon),
+    (Key::KEY_APOSTROPHE, rdev::Key::Quote),
+    (Key::KEY_GRAVE, rdev::Key::BackQuote),
+    (Key::KEY_LEFTSHIFT, rdev::Key::ShiftLeft),
+    (Key::KEY_BACKSLASH, rdev::Key::BackSlash),
+    (Key::KEY_Z, rdev::Key::KeyZ),
+    (Key::KEY_X, rdev::Key::KeyX),
+    (Key::KEY_C, rdev::Key::KeyC),
+    (Key::KEY_V, rdev::Key::KeyV),
+    (Key::KEY_B, rdev::Key::KeyB),
+    (Key::KEY_N, rdev::Key::KeyN),
+    (Key::KEY_M, rdev::Key::KeyM),
+    (Key::KEY_COMMA, rdev::Key::Comma),
+    (Key::KEY_DOT, rdev::Key::Dot),
+    (Key::KEY_SLASH, rdev::Key::Slash),
+    (Key::KEY_RIGHTSHIFT, rdev::Key::ShiftRight),
+    (Key::KEY_KPASTERISK, rdev::Key::KpMultiply),
+    (Key::KEY_LEFTALT, rdev::Key::Alt),
+    (Key::KEY_SPACE, rdev::Key::Space),
+    (Key::KEY_CAPSLOCK, rdev::Key::CapsLock),
+    (Key::KEY_F1, rdev::Key::F1),
+    (Key::KEY_F2, rdev::Key::F2),
+    (Key::KEY_F3, rdev::Key::F3),
+    (Key::KEY_F4, rdev::Key::F4),
+    (Key::KEY_F5, rdev::Key::F5),
+    (Key::KEY_F6, rdev::Key::F6),
+    (Key::KEY_F7, rdev::Key::F7),
+    (Key::KEY_F8, rdev::Key::F8),
+    (Key::KEY_F9, rdev::Key::F9),
+    (Key::KEY_F10, rdev::Key::F10),
+    (Key::KEY_NUMLOCK, rdev::Key::NumLock),
+    (Key::KEY_SCROLLLOCK, rdev::Key::ScrollLock),
+    (Key::KEY_KP7, rdev::Key::Kp7),
+    (Key::KEY_KP8, rdev::Key::Kp8),
+    (Key::KEY_KP9, rdev::Key::Kp9),
+    (Key::KEY_KPMINUS, rdev::Key::KpMinus),
+    (Key::KEY_KP4, rdev::Key::Kp4),
+    (Key::KEY_KP5, rdev::Key::Kp5),
+    (Key::KEY_KP6, rdev::Key::Kp6),
+    (Key::KEY_KPPLUS, rdev::Key::KpPlus),
+    (Key::KEY_KP1, rdev::Key::Kp1),
+    (Key::KEY_KP2, rdev::Key::Kp2),
+    (Key::KEY_KP3, rdev::Key::Kp3),
+    (Key::KEY_KP0, rdev::Key::Kp0),
+    (Key::KEY_KPDOT, rdev::Key::KpDelete),
+    (Key::KEY_102ND, rdev::Key::IntlBackslash),
+    (Key::KEY_F11, rdev::Key::F11),
+    (Key::KEY_F12, rdev::Key::F12),
+    (Key::KEY_KPENTER, rdev::Key::KpReturn),
+    (Key::KEY_RIGHTCTRL, rdev::Key::ControlRight),
+    (Key::KEY_KPSLASH, rdev::Key::KpDivide),
+    (Key::KEY_SYSRQ, rdev::Key::PrintScreen),
+    (Key::KEY_RIGHTALT, rdev::Key::AltGr),
+    (Key::KEY_HOME, rdev::Key::Home),
+    (Key::KEY_UP, rdev::Key::UpArrow),
+    (Key::KEY_PAGEUP, rdev::Key::PageUp),
+    (Key::KEY_LEFT, rdev::Key::LeftArrow),
+    (Key::KEY_RIGHT, rdev::Key::RightArrow),
+    (Key::KEY_END, rdev::Key::End),
+    (Key::KEY_DOWN, rdev::Key::DownArrow),
+    (Key::KEY_PAGEDOWN, rdev::Key::PageDown),
+    (Key::KEY_INSERT, rdev::Key::Insert),
+    (Key::KEY_DELETE, rdev::Key::Delete),
+    (Key::KEY_PAUSE, rdev::Key::Pause),
+    (Key::KEY_LEFTMETA, rdev::Key::MetaLeft),
+    (Key::KEY_RIGHTMETA, rdev::Key::MetaRight),
+];
+
+// Linux input event code for a key rdev knows, the inverse of `rdev_key`
+pub fn scancode(key: rdev::Key) -> Option<u16> {
+    KEYS.iter()
+        .find(|(_, known)| *known == key)
+        .map(|(code, _)| code.code())
+}
+
+fn rdev_key(key: Key) -> rdev::Key {
+    KEYS.iter()
+        .find(|(code, _)| *code == key)
+        .map_or(rdev::Key::Unknown(key.code() as u32), |(_, key)| *key)
+}
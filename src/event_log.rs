@@ -0,0 +1,135 @@
+use crate::paths;
+use crate::session::{SessionEvent, SessionRecord};
+use parking_lot::Mutex;
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+// When a new log file is started regardless of its size
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Rotation {
+    Never,
+    Hourly,
+    Daily,
+}
+
+impl Rotation {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        match text {
+            "never" => Ok(Rotation::Never),
+            "hourly" => Ok(Rotation::Hourly),
+            "daily" => Ok(Rotation::Daily),
+            other => Err(format!(
+                "invalid log rotation `{}`, expected never, hourly or daily",
+                other
+            )),
+        }
+    }
+
+    // Number of the hour or day (UTC) a time falls in
+    fn period(self, unix_secs: u64) -> u64 {
+        match self {
+            Rotation::Never => 0,
+            Rotation::Hourly => unix_secs / 3600,
+            Rotation::Daily => unix_secs / 86_400,
+        }
+    }
+}
+
+pub type SharedEventLog = Arc<Mutex<EventLog>>;
+
+pub struct LogOptions {
+    pub path: PathBuf,
+    // Start a new file past this size
+    pub max_size_mb: u64,
+    pub rotation: Rotation,
+}
+
+// `--log-file`: every input event as a JSON line, in the session format so any log file
+// can be replayed or analyzed. Full files are renamed to `<path>.<unix time>`, with `.1`, `.2`...
+// after it when several are rotated in the same second.
+pub struct EventLog {
+    options: LogOptions,
+    file: BufWriter<File>,
+    size: u64,
+    // Rotation period the current file belongs to
+    period: u64,
+    // Each file's `elapsed_ms` counts from its own start, so it replays on its own
+    started: Instant,
+}
+
+impl EventLog {
+    pub fn open(options: LogOptions) -> Result<Self, String> {
+        // Every key typed, so only for this user's eyes
+        let file = paths::private(OpenOptions::new().create(true).append(true))
+            .open(&options.path)
+            .and_then(|file| paths::restrict(&options.path).map(|()| file))
+            .map_err(|e| format!("{}: {}", options.path.display(), e))?;
+        let size = file.metadata().map_or(0, |metadata| metadata.len());
+        Ok(Self {
+            period: options.rotation.period(unix_secs()),
+            options,
+            file: BufWriter::new(file),
+            size,
+            started: Instant::now(),
+        })
+    }
+
+    pub fn write(&mut self, event: SessionEvent) {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        let full = self.size >= self.options.max_size_mb.saturating_mul(1024 * 1024);
+        if (full || self.options.rotation.period(now.as_secs()) != self.period)
+            && let Err(error) = self.rotate(now.as_secs())
+        {
+            eprintln!("Error rotating log file: {}", error);
+        }
+
+        let record = SessionRecord {
+            elapsed_ms: self.started.elapsed().as_millis() as u64,
+            time_ms: Some(now.as_millis() as u64),
+            event,
+        };
+        // Flush every line so a crash loses nothing, which is what the log is for
+        let result = serde_json::to_string(&record)
+            .map_err(|e| e.to_string())
+            .and_then(|line| {
+                writeln!(self.file, "{}", line).map_err(|e| e.to_string())?;
+                self.size += line.len() as u64 + 1;
+                self.file.flush().map_err(|e| e.to_string())
+            });
+        if let Err(error) = result {
+            eprintln!("Error writing log file: {}", error);
+        }
+    }
+
+    fn rotate(&mut self, unix_secs: u64) -> std::io::Result<()> {
+        self.file.flush()?;
+        let mut stem = self.options.path.clone().into_os_string();
+        stem.push(format!(".{}", unix_secs));
+        let mut rotated = PathBuf::from(&stem);
+        let mut sequence = 0;
+        while rotated.exists() {
+            sequence += 1;
+            let mut name = stem.clone();
+            name.push(format!(".{}", sequence));
+            rotated = PathBuf::from(name);
+        }
+        std::fs::rename(&self.options.path, rotated)?;
+
+        self.file = BufWriter::new(paths::create_private(&self.options.path)?);
+        self.size = 0;
+        self.period = self.options.rotation.period(unix_secs);
+        self.started = Instant::now();
+        Ok(())
+    }
+}
+
+fn unix_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
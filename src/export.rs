@@ -0,0 +1,378 @@
+use crate::analyze::is_modifier;
+use crate::config::ModifiersConfig;
+use crate::hotkeys::RECORDING_START;
+use crate::input::Source;
+use crate::session::{SessionEvent, SessionRecord};
+use crate::{Modifiers, key_to_string};
+use rdev::{Button, EventType};
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+// One shown press in a session, as written by the exporters
+pub struct ExportEvent {
+    // Milliseconds since the Unix epoch; only in sessions from newer versions
+    pub time_ms: Option<u64>,
+    pub elapsed_ms: u64,
+    // Time in the screen recording: since the last `recording-start` mark, or since the file
+    // started without one, less the export's offset
+    pub video_ms: i64,
+    pub key: String,
+    // Held modifiers in the default spelling, e.g. "Ctrl + Shift"
+    pub modifiers: String,
+    // shortcut, key, mouse, keyboard-chip or pointer-chip
+    pub category: &'static str,
+}
+
+impl ExportEvent {
+    // "Ctrl + Shift + K", as the overlay would show it
+    pub fn text(&self) -> String {
+        if self.modifiers.is_empty() {
+            self.key.clone()
+        } else {
+            format!("{} + {}", self.modifiers, self.key)
+        }
+    }
+}
+
+// `--offset` and `--fps` of `rusteys export`
+pub struct ExportOptions {
+    pub offset_ms: i64,
+    // Frame rate of the video the markers are placed in
+    pub fps: u32,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        Self {
+            offset_ms: 0,
+            fps: 30,
+        }
+    }
+}
+
+// Where a recording started, as written in the session
+#[derive(Clone, Copy)]
+struct RecordingMark {
+    time_ms: Option<u64>,
+    elapsed_ms: u64,
+    file: usize,
+}
+
+// Key presses, clicks and ready-made chips from session or `--log-file` files, in order.
+// `offset_ms` is how long after the mark (or file start) the video itself starts.
+pub fn read_events(paths: &[String], offset_ms: i64) -> Result<Vec<ExportEvent>, String> {
+    let mut events = Vec::new();
+    let mut mark: Option<RecordingMark> = None;
+    for (file_index, path) in paths.iter().enumerate() {
+        let file = File::open(path).map_err(|e| format!("{}: {}", path, e))?;
+        let mut modifiers = Modifiers::default();
+
+        for (number, line) in BufReader::new(file).lines().enumerate() {
+            let line = line.map_err(|e| format!("{}: {}", path, e))?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let record: SessionRecord = serde_json::from_str(&line)
+                .map_err(|e| format!("{} line {}: {}", path, number + 1, e))?;
+
+            let (key, category) = match record.event {
+                SessionEvent::Device(event_type) | SessionEvent::Tagged(event_type, _) => {
+                    match event_type {
+                        EventType::KeyPress(key) if is_modifier(key) => {
+                            modifiers.set(key, true);
+                            continue;
+                        }
+                        EventType::KeyRelease(key) => {
+                            modifiers.set(key, false);
+                            continue;
+                        }
+                        EventType::KeyPress(key) => {
+                            let shortcut = modifiers.ctrl || modifiers.alt || modifiers.meta;
+                            (
+                                key_to_string(key),
+                                if shortcut { "shortcut" } else { "key" },
+                            )
+                        }
+                        EventType::ButtonPress(button) => (button_name(button), "mouse"),
+                        _ => continue,
+                    }
+                }
+                SessionEvent::Chip(text, Source::Keyboard) => (text, "keyboard-chip"),
+                SessionEvent::Chip(text, Source::Pointer) => (text, "pointer-chip"),
+                SessionEvent::Remote(_) => continue,
+                SessionEvent::Mark(name) => {
+                    if name == RECORDING_START {
+                        mark = Some(RecordingMark {
+                            time_ms: record.time_ms,
+                            elapsed_ms: record.elapsed_ms,
+                            file: file_index,
+                        });
+                    }
+                    continue;
+                }
+            };
+
+            // Wall-clock time works across files; elapsed time only within the marked one
+            let since_start = match (mark, record.time_ms) {
+                (
+                    Some(RecordingMark {
+                        time_ms: Some(start),
+                        ..
+                    }),
+                    Some(time),
+                ) => time as i64 - start as i64,
+                (Some(mark), _) if mark.file == file_index => {
+                    record.elapsed_ms as i64 - mark.elapsed_ms as i64
+                }
+                _ => record.elapsed_ms as i64,
+            };
+
+            let held = if category == "keyboard-chip" || category == "pointer-chip" {
+                String::new()
+            } else {
+                modifiers.format(&ModifiersConfig::default())
+            };
+            events.push(ExportEvent {
+                time_ms: record.time_ms,
+                elapsed_ms: record.elapsed_ms,
+                video_ms: since_start - offset_ms,
+                key,
+                modifiers: held,
+                category,
+            });
+        }
+    }
+    Ok(events)
+}
+
+// `rusteys export <format> <out> <session.jsonl>...`
+pub fn run(
+    format: &str,
+    paths: &[String],
+    out: &Path,
+    options: &ExportOptions,
+) -> Result<(), String> {
+    let events = read_events(paths, options.offset_ms)?;
+    let file = File::create(out).map_err(|e| format!("{}: {}", out.display(), e))?;
+    let mut writer = BufWriter::new(file);
+    let written = match format {
+        "csv" => csv(&mut writer, &events).map(|()| events.len()),
+        // Editors only get the shortcuts, and none from before the video starts
+        "edl" | "fcpxml" => {
+            let markers: Vec<&ExportEvent> = events
+                .iter()
+                .filter(|event| event.category == "shortcut" && event.video_ms >= 0)
+                .collect();
+            let result = if format == "edl" {
+                edl(&mut writer, &markers, options.fps)
+            } else {
+                fcpxml(&mut writer, &markers, options.fps)
+            };
+            result.map(|()| markers.len())
+        }
+        other => {
+            return Err(format!(
+                "unknown export format `{}`, expected csv, edl or fcpxml",
+                other
+            ));
+        }
+    };
+    let written = written
+        .and_then(|count| writer.flush().map(|()| count))
+        .map_err(|e| format!("{}: {}", out.display(), e))?;
+    println!("Wrote {} events to {}", written, out.display());
+    Ok(())
+}
+
+fn csv(writer: &mut impl Write, events: &[ExportEvent]) -> std::io::Result<()> {
+    // `app` is left empty until the focused application is recorded
+    writeln!(
+        writer,
+        "timestamp,elapsed_s,video_s,key,modifiers,category,app"
+    )?;
+    for event in events {
+        writeln!(
+            writer,
+            "{},{:.3},{:.3},{},{},{},",
+            event.time_ms.map(iso_time).unwrap_or_default(),
+            event.elapsed_ms as f64 / 1000.0,
+            event.video_ms as f64 / 1000.0,
+            csv_field(&event.key),
+            csv_field(&event.modifiers),
+            event.category,
+        )?;
+    }
+    Ok(())
+}
+
+// CMX 3600 edit decision list with one marker per shortcut, in the form DaVinci Resolve
+// writes and reads back (`|M:` is the marker name); timecodes start at 00:00:00:00
+fn edl(writer: &mut impl Write, markers: &[&ExportEvent], fps: u32) -> std::io::Result<()> {
+    writeln!(writer, "TITLE: rusteys")?;
+    writeln!(writer, "FCM: NON-DROP FRAME")?;
+    for (index, marker) in markers.iter().enumerate() {
+        let frame = frame(marker.video_ms, fps);
+        let (start, end) = (timecode(frame, fps), timecode(frame + 1, fps));
+        writeln!(writer)?;
+        writeln!(
+            writer,
+            "{:03}  001      V     C        {} {} {} {}",
+            index + 1,
+            start,
+            end,
+            start,
+            end
+        )?;
+        writeln!(
+            writer,
+            " |C:ResolveColorBlue |M:{} |D:1",
+            marker.text().replace('|', "")
+        )?;
+    }
+    Ok(())
+}
+
+// Final Cut Pro XML: an empty project the length of the last shortcut, with a marker on each
+fn fcpxml(writer: &mut impl Write, markers: &[&ExportEvent], fps: u32) -> std::io::Result<()> {
+    let frames = markers
+        .last()
+        .map_or(0, |marker| frame(marker.video_ms, fps))
+        + fps as u64;
+    writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(writer, "<!DOCTYPE fcpxml>")?;
+    writeln!(writer, r#"<fcpxml version="1.9">"#)?;
+    writeln!(writer, "  <resources>")?;
+    writeln!(
+        writer,
+        r#"    <format id="r1" name="FFVideoFormat{}p" frameDuration="1/{}s"/>"#,
+        fps, fps
+    )?;
+    writeln!(writer, "  </resources>")?;
+    writeln!(writer, "  <library>")?;
+    writeln!(writer, r#"    <event name="rusteys">"#)?;
+    writeln!(writer, r#"      <project name="rusteys shortcuts">"#)?;
+    writeln!(
+        writer,
+        r#"        <sequence format="r1" duration="{}/{}s" tcStart="0s" tcFormat="NDF">"#,
+        frames, fps
+    )?;
+    writeln!(writer, "          <spine>")?;
+    writeln!(
+        writer,
+        r#"            <gap name="Gap" offset="0s" start="0s" duration="{}/{}s">"#,
+        frames, fps
+    )?;
+    for marker in markers {
+        writeln!(
+            writer,
+            r#"              <marker start="{}/{}s" duration="1/{}s" value="{}"/>"#,
+            frame(marker.video_ms, fps),
+            fps,
+            fps,
+            xml_escape(&marker.text())
+        )?;
+    }
+    writeln!(writer, "            </gap>")?;
+    writeln!(writer, "          </spine>")?;
+    writeln!(writer, "        </sequence>")?;
+    writeln!(writer, "      </project>")?;
+    writeln!(writer, "    </event>")?;
+    writeln!(writer, "  </library>")?;
+    writeln!(writer, "</fcpxml>")
+}
+
+// Frame a non-negative video time falls in
+fn frame(video_ms: i64, fps: u32) -> u64 {
+    video_ms.max(0) as u64 * fps as u64 / 1000
+}
+
+// "01:02:03:04" (hours, minutes, seconds, frames)
+fn timecode(frame: u64, fps: u32) -> String {
+    let fps = fps as u64;
+    let secs = frame / fps;
+    format!(
+        "{:02}:{:02}:{:02}:{:02}",
+        secs / 3600,
+        secs / 60 % 60,
+        secs % 60,
+        frame % fps
+    )
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// Quoted when it holds a comma, quote or line break ("," is a key too)
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+// "2024-05-01T12:34:56.789Z"
+pub fn iso_time(unix_ms: u64) -> String {
+    chrono::DateTime::from_timestamp_millis(unix_ms as i64)
+        .unwrap_or_default()
+        .to_rfc3339_opts(chrono::SecondsFormat::Millis, true)
+}
+
+fn button_name(button: Button) -> String {
+    match button {
+        Button::Left => "Left click".to_string(),
+        Button::Right => "Right click".to_string(),
+        Button::Middle => "Middle click".to_string(),
+        Button::Unknown(number) => format!("Button {}", number),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn iso_time_from_unix_ms() {
+        assert_eq!(iso_time(0), "1970-01-01T00:00:00.000Z");
+        assert_eq!(iso_time(1_714_566_896_789), "2024-05-01T12:34:56.789Z");
+        assert_eq!(iso_time(946_684_799_999), "1999-12-31T23:59:59.999Z");
+    }
+
+    #[test]
+    fn frame_counts_whole_frames_and_clamps_before_zero() {
+        assert_eq!(frame(0, 30), 0);
+        assert_eq!(frame(999, 30), 29);
+        assert_eq!(frame(1000, 30), 30);
+        assert_eq!(frame(-500, 30), 0);
+    }
+
+    #[test]
+    fn timecode_rolls_over_at_fps() {
+        assert_eq!(timecode(0, 30), "00:00:00:00");
+        assert_eq!(timecode(29, 30), "00:00:00:29");
+        assert_eq!(timecode(30, 30), "00:00:01:00");
+        assert_eq!(timecode(25, 24), "00:00:01:01");
+        assert_eq!(timecode(60 * 60 * 25 - 1, 25), "00:59:59:24");
+        assert_eq!(timecode(60 * 60 * 25, 25), "01:00:00:00");
+    }
+
+    #[test]
+    fn csv_field_quotes_only_when_needed() {
+        assert_eq!(csv_field("Ctrl + S"), "Ctrl + S");
+        assert_eq!(csv_field(","), "\",\"");
+        assert_eq!(csv_field("\""), "\"\"\"\"");
+        assert_eq!(csv_field("Shift + \"a,b\""), "\"Shift + \"\"a,b\"\"\"");
+        assert_eq!(csv_field("a\nb"), "\"a\nb\"");
+    }
+
+    #[test]
+    fn xml_escape_replaces_markup() {
+        assert_eq!(xml_escape("Ctrl + <"), "Ctrl + &lt;");
+        assert_eq!(xml_escape("\"&\""), "&quot;&amp;&quot;");
+    }
+}
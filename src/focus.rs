@@ -0,0 +1,323 @@
+// Which application and window have keyboard focus, for per-application cheat sheets, rules,
+// the window title element and placing the overlay on the focused window. Looked up on a
+// background thread, as some platforms need a subprocess for it.
+
+use eframe::egui;
+use parking_lot::Mutex;
+use std::sync::Once;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+
+// How often the focused application is looked up
+pub const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Clone, Default)]
+struct Focused {
+    app: Option<String>,
+    title: Option<String>,
+    bounds: Option<egui::Rect>,
+}
+
+// Details that cost another lookup, so they are only fetched once something asked for them
+#[derive(Clone, Copy)]
+struct Wanted {
+    title: bool,
+    bounds: bool,
+}
+
+static FOCUSED: Mutex<Focused> = Mutex::new(Focused {
+    app: None,
+    title: None,
+    bounds: None,
+});
+static POLLING: Once = Once::new();
+static WANT_TITLE: AtomicBool = AtomicBool::new(false);
+static WANT_BOUNDS: AtomicBool = AtomicBool::new(false);
+
+// Name of the focused application: the executable name on Windows ("Code"), the application
+// name on macOS ("Visual Studio Code") and the window class on Linux X11 ("Code"). None on
+// Wayland, which doesn't tell other clients.
+pub fn focused_app() -> Option<String> {
+    start_polling();
+    FOCUSED.lock().app.clone()
+}
+
+// Title of the focused window, e.g. "README.md - rusteys - Visual Studio Code". On macOS this
+// goes through System Events, which asks once for permission to control it.
+pub fn focused_title() -> Option<String> {
+    WANT_TITLE.store(true, Ordering::Relaxed);
+    start_polling();
+    FOCUSED.lock().title.clone()
+}
+
+// Screen area of the focused window, in the same coordinates as the pointer
+pub fn focused_bounds() -> Option<egui::Rect> {
+    WANT_BOUNDS.store(true, Ordering::Relaxed);
+    start_polling();
+    FOCUSED.lock().bounds
+}
+
+fn start_polling() {
+    POLLING.call_once(|| {
+        update();
+        thread::spawn(|| {
+            loop {
+                thread::sleep(POLL_INTERVAL);
+                update();
+            }
+        });
+    });
+}
+
+fn update() {
+    let wanted = Wanted {
+        title: WANT_TITLE.load(Ordering::Relaxed),
+        bounds: WANT_BOUNDS.load(Ordering::Relaxed),
+    };
+    // Clicking the overlay keeps whatever was focused before
+    if let Some(focused) = query(wanted) {
+        *FOCUSED.lock() = focused;
+    }
+}
+
+// None while one of rusteys' own windows has focus
+#[cfg(target_os = "windows")]
+fn query(wanted: Wanted) -> Option<Focused> {
+    use std::ffi::OsString;
+    use std::os::windows::ffi::OsStringExt;
+    use std::path::Path;
+    use winapi::shared::windef::RECT;
+    use winapi::um::handleapi::CloseHandle;
+    use winapi::um::processthreadsapi::OpenProcess;
+    use winapi::um::winbase::QueryFullProcessImageNameW;
+    use winapi::um::winnt::PROCESS_QUERY_LIMITED_INFORMATION;
+    use winapi::um::winuser::{
+        GetForegroundWindow, GetWindowRect, GetWindowTextW, GetWindowThreadProcessId,
+    };
+
+    unsafe {
+        let window = GetForegroundWindow();
+        if window.is_null() {
+            return Some(Focused::default());
+        }
+        let mut process_id = 0;
+        GetWindowThreadProcessId(window, &mut process_id);
+        if process_id == std::process::id() {
+            return None;
+        }
+
+        let title = wanted.title.then(|| {
+            let mut buffer = [0u16; 512];
+            let length = GetWindowTextW(window, buffer.as_mut_ptr(), buffer.len() as i32);
+            String::from_utf16_lossy(&buffer[..length.max(0) as usize])
+        });
+        let bounds = wanted
+            .bounds
+            .then(|| {
+                let mut rect: RECT = std::mem::zeroed();
+                (GetWindowRect(window, &mut rect) != 0).then(|| {
+                    egui::Rect::from_min_max(
+                        egui::pos2(rect.left as f32, rect.top as f32),
+                        egui::pos2(rect.right as f32, rect.bottom as f32),
+                    )
+                })
+            })
+            .flatten();
+
+        let process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, process_id);
+        if process.is_null() {
+            return Some(Focused {
+                app: None,
+                title,
+                bounds,
+            });
+        }
+        let mut buffer = [0u16; 1024];
+        let mut length = buffer.len() as u32;
+        let found = QueryFullProcessImageNameW(process, 0, buffer.as_mut_ptr(), &mut length) != 0;
+        CloseHandle(process);
+        let app = found
+            .then(|| OsString::from_wide(&buffer[..length as usize]))
+            .and_then(|path| {
+                Path::new(&path)
+                    .file_stem()
+                    .map(|name| name.to_string_lossy().into_owned())
+            });
+        Some(Focused { app, title, bounds })
+    }
+}
+
+// rusteys never becomes the frontmost application on macOS, see window_level::raise
+#[cfg(target_os = "macos")]
+fn query(wanted: Wanted) -> Option<Focused> {
+    // Window titles and frames are only reachable through the Accessibility API; System Events
+    // wraps it
+    let title = wanted
+        .title
+        .then(|| {
+            osascript(
+                "get name of front window of (first application process whose frontmost is true)",
+            )
+        })
+        .flatten();
+    // "120, 80, 1400, 900": position, then size
+    let bounds = wanted
+        .bounds
+        .then(|| osascript("get {position, size} of front window of (first application process whose frontmost is true)"))
+        .flatten()
+        .and_then(|frame| {
+            let numbers: Vec<f32> = frame
+                .split(',')
+                .map(|number| number.trim().parse().ok())
+                .collect::<Option<_>>()?;
+            let [x, y, width, height] = numbers[..] else {
+                return None;
+            };
+            Some(egui::Rect::from_min_size(
+                egui::pos2(x, y),
+                egui::vec2(width, height),
+            ))
+        });
+    Some(Focused {
+        app: frontmost_app(),
+        title,
+        bounds,
+    })
+}
+
+#[cfg(target_os = "macos")]
+fn osascript(command: &str) -> Option<String> {
+    let output = std::process::Command::new("osascript")
+        .args([
+            "-e",
+            &format!("tell application \"System Events\" to {}", command),
+        ])
+        .stderr(std::process::Stdio::null())
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+#[cfg(target_os = "macos")]
+fn frontmost_app() -> Option<String> {
+    use std::ffi::{CStr, c_char, c_void};
+
+    type Id = *mut c_void;
+    type Sel = *const c_void;
+
+    #[link(name = "objc")]
+    unsafe extern "C" {
+        fn objc_getClass(name: *const c_char) -> Id;
+        fn sel_registerName(name: *const c_char) -> Sel;
+        fn objc_msgSend();
+    }
+
+    unsafe {
+        let selector = |name: &CStr| sel_registerName(name.as_ptr());
+        let send = objc_msgSend as unsafe extern "C" fn();
+        let send_id: unsafe extern "C" fn(Id, Sel) -> Id = std::mem::transmute(send);
+        let send_str: unsafe extern "C" fn(Id, Sel) -> *const c_char = std::mem::transmute(send);
+
+        let workspace = send_id(
+            objc_getClass(c"NSWorkspace".as_ptr()),
+            selector(c"sharedWorkspace"),
+        );
+        let app = send_id(workspace, selector(c"frontmostApplication"));
+        if app.is_null() {
+            return None;
+        }
+        let name = send_id(app, selector(c"localizedName"));
+        if name.is_null() {
+            return None;
+        }
+        let name = CStr::from_ptr(send_str(name, selector(c"UTF8String")));
+        Some(name.to_string_lossy().into_owned())
+    }
+}
+
+// Through xprop and xwininfo, like the blur property in window_level
+#[cfg(target_os = "linux")]
+fn query(wanted: Wanted) -> Option<Focused> {
+    let Some(window) = active_window() else {
+        return Some(Focused::default());
+    };
+    let properties = run(
+        "xprop",
+        &["-id", &window, "_NET_WM_PID", "WM_CLASS", "_NET_WM_NAME"],
+    )
+    .unwrap_or_default();
+    let property = |name: &str| {
+        properties
+            .lines()
+            .find(|line| line.starts_with(&format!("{}(", name)))
+    };
+
+    // "_NET_WM_PID(CARDINAL) = 4242"
+    if property("_NET_WM_PID").and_then(|line| line.rsplit(' ').next()?.trim().parse::<u32>().ok())
+        == Some(std::process::id())
+    {
+        return None;
+    }
+    // `WM_CLASS(STRING) = "code", "Code"`: instance, then class
+    let app = property("WM_CLASS").and_then(|class| class.split('"').nth(3).map(str::to_string));
+    // `_NET_WM_NAME(UTF8_STRING) = "README.md - Code"`, with quotes inside escaped
+    let title = property("_NET_WM_NAME")
+        .filter(|_| wanted.title)
+        .and_then(|name| {
+            let (_, quoted) = name.split_once('"')?;
+            let quoted = quoted.trim_end().strip_suffix('"')?;
+            Some(quoted.replace("\\\"", "\"").replace("\\\\", "\\"))
+        });
+    let bounds = wanted.bounds.then(|| window_bounds(&window)).flatten();
+    Some(Focused { app, title, bounds })
+}
+
+#[cfg(target_os = "linux")]
+fn active_window() -> Option<String> {
+    let active = run("xprop", &["-root", "_NET_ACTIVE_WINDOW"])?;
+    // "_NET_ACTIVE_WINDOW(WINDOW): window id # 0x3a00007"
+    let window = active.rsplit(' ').next()?.trim().to_string();
+    (window.starts_with("0x") && window != "0x0").then_some(window)
+}
+
+// "  Absolute upper-left X:  120" and so on, for the window's client area
+#[cfg(target_os = "linux")]
+fn window_bounds(window: &str) -> Option<egui::Rect> {
+    let info = run("xwininfo", &["-id", window])?;
+    let value = |label: &str| -> Option<f32> {
+        info.lines()
+            .find_map(|line| line.trim().strip_prefix(label))?
+            .trim()
+            .parse()
+            .ok()
+    };
+    Some(egui::Rect::from_min_size(
+        egui::pos2(
+            value("Absolute upper-left X:")?,
+            value("Absolute upper-left Y:")?,
+        ),
+        egui::vec2(value("Width:")?, value("Height:")?),
+    ))
+}
+
+#[cfg(target_os = "linux")]
+fn run(program: &str, args: &[&str]) -> Option<String> {
+    let output = std::process::Command::new(program)
+        .args(args)
+        .stderr(std::process::Stdio::null())
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+fn query(_wanted: Wanted) -> Option<Focused> {
+    Some(Focused::default())
+}
@@ -0,0 +1,123 @@
+use crate::config::{GamepadConfig, GamepadGlyphs};
+use crate::gestures::direction_arrow;
+use crate::input::{EventSink, InputBackend, InputEvent, Source};
+use eframe::egui;
+use gilrs::{Axis, Button, EventType, Gilrs};
+
+// Game controllers via gilrs, shown as button and stick chips
+pub struct GamepadBackend {
+    config: GamepadConfig,
+}
+
+impl GamepadBackend {
+    pub fn new(config: GamepadConfig) -> Self {
+        Self { config }
+    }
+}
+
+// Last reported position and shown direction for one analog stick
+#[derive(Default)]
+struct Stick {
+    x: f32,
+    y: f32,
+    arrow: Option<&'static str>,
+}
+
+impl Stick {
+    // New direction arrow once the stick leaves the dead zone or changes sector
+    fn update(&mut self, threshold: f32) -> Option<&'static str> {
+        // gilrs reports y up, screen arrows expect y down
+        let delta = egui::vec2(self.x, -self.y);
+        let arrow = (delta.length() >= threshold).then(|| direction_arrow(delta));
+        let changed = arrow.is_some() && arrow != self.arrow;
+        self.arrow = arrow;
+        if changed { arrow } else { None }
+    }
+}
+
+impl InputBackend for GamepadBackend {
+    fn name(&self) -> &'static str {
+        "gamepad"
+    }
+
+    fn run(self: Box<Self>, mut sink: EventSink) -> Result<(), String> {
+        let mut gilrs = Gilrs::new().map_err(|error| error.to_string())?;
+        let (mut left, mut right) = (Stick::default(), Stick::default());
+
+        loop {
+            let Some(event) = gilrs.next_event_blocking(None) else {
+                continue;
+            };
+
+            let glyphs = match self.config.glyphs {
+                GamepadGlyphs::Auto => detect_glyphs(gilrs.gamepad(event.id).name()),
+                glyphs => glyphs,
+            };
+
+            match event.event {
+                EventType::ButtonPressed(button, _) => {
+                    if let Some(label) = button_label(button, glyphs) {
+                        sink(InputEvent::Chip(label, Source::Keyboard));
+                    }
+                }
+                EventType::AxisChanged(axis, value, _) => {
+                    let (stick, name) = match axis {
+                        Axis::LeftStickX | Axis::LeftStickY => (&mut left, "L Stick"),
+                        Axis::RightStickX | Axis::RightStickY => (&mut right, "R Stick"),
+                        _ => continue,
+                    };
+                    match axis {
+                        Axis::LeftStickX | Axis::RightStickX => stick.x = value,
+                        _ => stick.y = value,
+                    }
+                    if let Some(arrow) = stick.update(self.config.stick_threshold) {
+                        sink(InputEvent::Chip(
+                            format!("{} {}", name, arrow),
+                            Source::Keyboard,
+                        ));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+fn detect_glyphs(name: &str) -> GamepadGlyphs {
+    let name = name.to_lowercase();
+    let hints = ["playstation", "dualshock", "dualsense", "sony"];
+    if hints.iter().any(|hint| name.contains(hint)) {
+        GamepadGlyphs::PlayStation
+    } else {
+        GamepadGlyphs::Xbox
+    }
+}
+
+fn button_label(button: Button, glyphs: GamepadGlyphs) -> Option<String> {
+    let (xbox, playstation) = match button {
+        Button::South => ("A", "✕"),
+        Button::East => ("B", "○"),
+        Button::North => ("Y", "△"),
+        Button::West => ("X", "□"),
+        Button::LeftTrigger => ("LB", "L1"),
+        Button::LeftTrigger2 => ("LT", "L2"),
+        Button::RightTrigger => ("RB", "R1"),
+        Button::RightTrigger2 => ("RT", "R2"),
+        Button::Select => ("View", "Share"),
+        Button::Start => ("Menu", "Options"),
+        Button::Mode => ("Xbox", "PS"),
+        Button::LeftThumb => ("LS", "L3"),
+        Button::RightThumb => ("RS", "R3"),
+        Button::DPadUp => ("D-Pad ↑", "D-Pad ↑"),
+        Button::DPadDown => ("D-Pad ↓", "D-Pad ↓"),
+        Button::DPadLeft => ("D-Pad ←", "D-Pad ←"),
+        Button::DPadRight => ("D-Pad →", "D-Pad →"),
+        _ => return None,
+    };
+    let label = if glyphs == GamepadGlyphs::PlayStation {
+        playstation
+    } else {
+        xbox
+    };
+    Some(label.to_string())
+}
@@ -0,0 +1,104 @@
+use eframe::egui;
+use rdev::Button;
+use std::time::{Duration, Instant};
+
+// Pointer travel (in pixels) beyond which a press counts as a drag
+const DRAG_THRESHOLD: f32 = 12.0;
+
+// Wheel ticks closer together than this are summed into one chip
+const SCROLL_PAUSE: Duration = Duration::from_millis(500);
+
+// Mouse button held down, waiting to see whether it becomes a click or a drag
+struct PendingPress {
+    button: Button,
+    start: Option<egui::Pos2>,
+    prefix: String,
+}
+
+#[derive(Default)]
+pub struct DragTracker {
+    // One per button held, so a right click during a left drag ends neither early
+    pending: Vec<PendingPress>,
+}
+
+impl DragTracker {
+    // `prefix` is the modifier text ("Ctrl +") captured while the button went down
+    pub fn press(&mut self, button: Button, position: Option<egui::Pos2>, prefix: String) {
+        self.pending.retain(|pending| pending.button != button);
+        self.pending.push(PendingPress {
+            button,
+            start: position,
+            prefix,
+        });
+    }
+
+    // Chip text for the finished click ("Ctrl +") or drag ("Drag ↘ 240px")
+    pub fn release(&mut self, button: Button, position: Option<egui::Pos2>) -> Option<String> {
+        let index = self
+            .pending
+            .iter()
+            .position(|pending| pending.button == button)?;
+        let pending = self.pending.remove(index);
+
+        let gesture = match (pending.start, position) {
+            (Some(start), Some(end)) if start.distance(end) >= DRAG_THRESHOLD => format!(
+                "Drag {} {}px",
+                direction_arrow(end - start),
+                start.distance(end).round()
+            ),
+            _ => String::new(),
+        };
+
+        Some(format!("{} {}", pending.prefix, gesture).trim().to_string())
+    }
+}
+
+#[derive(Default)]
+pub struct ScrollAccumulator {
+    prefix: String,
+    arrow: &'static str,
+    ticks: i64,
+    last: Option<Instant>,
+}
+
+impl ScrollAccumulator {
+    // Chip text for the scroll so far ("Scroll ↓ ×24"), and whether it
+    // continues the previous scroll rather than starting a new one
+    pub fn scroll(&mut self, prefix: &str, delta_x: i64, delta_y: i64) -> (String, bool) {
+        // rdev reports positive deltas for up and right
+        let (arrow, ticks) = if delta_y != 0 {
+            (if delta_y > 0 { "↑" } else { "↓" }, delta_y.abs())
+        } else {
+            (if delta_x > 0 { "→" } else { "←" }, delta_x.abs())
+        };
+
+        let now = Instant::now();
+        let continues = self.arrow == arrow
+            && self.prefix == prefix
+            && self.last.is_some_and(|last| now - last < SCROLL_PAUSE);
+        if !continues {
+            self.prefix = prefix.to_string();
+            self.arrow = arrow;
+            self.ticks = 0;
+        }
+        self.ticks += ticks;
+        self.last = Some(now);
+
+        let gesture = if self.ticks > 1 {
+            format!("Scroll {} ×{}", arrow, self.ticks)
+        } else {
+            format!("Scroll {}", arrow)
+        };
+        (
+            format!("{} {}", prefix, gesture).trim().to_string(),
+            continues,
+        )
+    }
+}
+
+// Nearest of eight arrows for a screen-space movement (y grows downwards)
+pub fn direction_arrow(delta: egui::Vec2) -> &'static str {
+    let angle = (-delta.y).atan2(delta.x);
+    let sector = ((angle / std::f32::consts::FRAC_PI_4).round() as i32).rem_euclid(8);
+    ["→", "↗", "↑", "↖", "←", "↙", "↓", "↘"][sector as usize]
+}
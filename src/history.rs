@@ -0,0 +1,444 @@
+use crate::config::HistoryConfig;
+use crate::hotkeys::Controls;
+use crate::i18n::{Text, tr, tr_args};
+use crate::paths;
+use eframe::egui;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, mpsc};
+use std::thread;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+// Chips kept for the history window and GET /history
+const HISTORY_LENGTH: usize = 10_000;
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct HistoryEntry {
+    pub text: String,
+    // Milliseconds since the Unix epoch
+    pub time: u128,
+}
+
+#[derive(Serialize)]
+pub struct Stats {
+    pub total: u64,
+    pub per_minute: f64,
+    pub uptime_secs: u64,
+    // Most used chips, most frequent first
+    pub top: Vec<(String, u64)>,
+}
+
+// Every chip shown since startup, beyond the few on screen
+pub struct History {
+    entries: VecDeque<HistoryEntry>,
+    counts: HashMap<String, u64>,
+    total: u64,
+    started: Instant,
+    // Startup in milliseconds since the Unix epoch, to show entry times relative to it
+    started_time: u128,
+    // Only with `[history] persist`
+    log: Option<HistoryLog>,
+}
+
+// Entries appended to disk as JSON lines, moved to `<path>.1` when the file grows too large.
+// Chips are recorded from the input hook, so the file is written on a thread of its own.
+struct HistoryLog {
+    sender: mpsc::Sender<LogCommand>,
+    // Cleared by the writer once the file can't be written anymore
+    recording: Arc<AtomicBool>,
+}
+
+enum LogCommand {
+    Append(HistoryEntry),
+    Clear,
+}
+
+impl HistoryLog {
+    fn open(path: PathBuf, max_size: u64) -> std::io::Result<Self> {
+        let writer = LogWriter::open(path, max_size)?;
+        let (sender, commands) = mpsc::channel();
+        let recording = Arc::new(AtomicBool::new(true));
+        let writing = Arc::clone(&recording);
+        thread::spawn(move || writer.run(commands, &writing));
+        Ok(Self { sender, recording })
+    }
+
+    fn append(&self, entry: HistoryEntry) {
+        let _ = self.sender.send(LogCommand::Append(entry));
+    }
+
+    // Forget everything on disk too, not just in memory
+    fn clear(&self) {
+        let _ = self.sender.send(LogCommand::Clear);
+    }
+
+    fn is_recording(&self) -> bool {
+        self.recording.load(Ordering::Relaxed)
+    }
+}
+
+struct LogWriter {
+    path: PathBuf,
+    max_size: u64,
+    file: File,
+    size: u64,
+}
+
+impl LogWriter {
+    fn open(path: PathBuf, max_size: u64) -> std::io::Result<Self> {
+        paths::create_parent(&path)?;
+        // Typed text, so only for this user's eyes
+        let file = paths::private(OpenOptions::new().create(true).append(true)).open(&path)?;
+        paths::restrict(&path)?;
+        let size = file.metadata()?.len();
+        Ok(Self {
+            path,
+            max_size,
+            file,
+            size,
+        })
+    }
+
+    fn run(mut self, commands: mpsc::Receiver<LogCommand>, recording: &AtomicBool) {
+        for command in commands {
+            match command {
+                LogCommand::Append(entry) => {
+                    if let Err(error) = self.append(&entry) {
+                        eprintln!(
+                            "Error writing history file, no longer recording to disk: {}",
+                            error
+                        );
+                        recording.store(false, Ordering::Relaxed);
+                        return;
+                    }
+                }
+                LogCommand::Clear => {
+                    if let Err(error) = self.clear() {
+                        eprintln!("Error clearing history file: {}", error);
+                    }
+                }
+            }
+        }
+    }
+
+    fn append(&mut self, entry: &HistoryEntry) -> std::io::Result<()> {
+        let line = serde_json::to_string(entry).map_err(std::io::Error::other)?;
+        writeln!(self.file, "{}", line)?;
+        self.size += line.len() as u64 + 1;
+        if self.size > self.max_size {
+            std::fs::rename(&self.path, self.rotated())?;
+            self.file = paths::create_private(&self.path)?;
+            self.size = 0;
+        }
+        Ok(())
+    }
+
+    fn clear(&mut self) -> std::io::Result<()> {
+        let _ = std::fs::remove_file(self.rotated());
+        self.file = paths::create_private(&self.path)?;
+        self.size = 0;
+        Ok(())
+    }
+
+    fn rotated(&self) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(".1");
+        PathBuf::from(path)
+    }
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self {
+            entries: VecDeque::new(),
+            counts: HashMap::new(),
+            total: 0,
+            started: Instant::now(),
+            started_time: now_millis(),
+            log: None,
+        }
+    }
+}
+
+impl History {
+    // With `persist`, starts from the entries kept on disk and keeps appending to them
+    pub fn new(config: &HistoryConfig) -> Self {
+        let mut history = Self::default();
+        if !config.persist {
+            return history;
+        }
+
+        if let Ok(file) = File::open(&config.path) {
+            let entries = BufReader::new(file)
+                .lines()
+                .map_while(Result::ok)
+                .filter_map(|line| serde_json::from_str(&line).ok());
+            history.entries.extend(entries);
+            let excess = history.entries.len().saturating_sub(HISTORY_LENGTH);
+            history.entries.drain(..excess);
+        }
+
+        match HistoryLog::open(
+            config.path.clone(),
+            config.max_size_mb.saturating_mul(1024 * 1024),
+        ) {
+            Ok(log) => history.log = Some(log),
+            Err(error) => eprintln!(
+                "Error opening history file {}: {}",
+                config.path.display(),
+                error
+            ),
+        }
+        history
+    }
+
+    pub fn is_persistent(&self) -> bool {
+        self.log.as_ref().is_some_and(HistoryLog::is_recording)
+    }
+
+    pub fn record(&mut self, text: &str) {
+        let entry = HistoryEntry {
+            text: text.to_string(),
+            time: now_millis(),
+        };
+        if let Some(log) = &self.log {
+            log.append(entry.clone());
+        }
+        self.entries.push_back(entry);
+        while self.entries.len() > HISTORY_LENGTH {
+            self.entries.pop_front();
+        }
+
+        *self.counts.entry(text.to_string()).or_default() += 1;
+        self.total += 1;
+    }
+
+    // The last `count` chips as one line for documentation, with typed characters joined into
+    // words: "Ctrl + Shift + P, 'rename', Enter"
+    pub fn recent_text(&self, count: usize) -> String {
+        let start = self.entries.len().saturating_sub(count);
+        let mut parts: Vec<String> = Vec::new();
+        let mut word = String::new();
+        for entry in self.entries.range(start..) {
+            let text = entry.text.trim();
+            let mut chars = text.chars();
+            match (chars.next(), chars.next()) {
+                (Some(character), None) if !character.is_whitespace() => {
+                    word.extend(character.to_lowercase())
+                }
+                _ => {
+                    if !word.is_empty() {
+                        parts.push(format!("'{}'", std::mem::take(&mut word)));
+                    }
+                    if !text.is_empty() {
+                        parts.push(text.to_string());
+                    }
+                }
+            }
+        }
+        if !word.is_empty() {
+            parts.push(format!("'{}'", word));
+        }
+        parts.join(", ")
+    }
+
+    // The newest `count` chips, oldest first
+    pub fn latest(&self, count: usize) -> Vec<HistoryEntry> {
+        let skip = self.entries.len().saturating_sub(count);
+        self.entries.iter().skip(skip).cloned().collect()
+    }
+
+    // Empty the list; stats keep counting the whole session
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        if let Some(log) = &self.log {
+            log.clear();
+        }
+    }
+
+    pub fn stats(&self) -> Stats {
+        let uptime = self.started.elapsed();
+        let mut top: Vec<(String, u64)> = self
+            .counts
+            .iter()
+            .map(|(text, count)| (text.clone(), *count))
+            .collect();
+        top.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        top.truncate(10);
+
+        Stats {
+            total: self.total,
+            per_minute: self.total as f64 / (uptime.as_secs_f64() / 60.0).max(1.0 / 60.0),
+            uptime_secs: uptime.as_secs(),
+            top,
+        }
+    }
+}
+
+fn now_millis() -> u128 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_millis())
+}
+
+// "1:02:03.4" since startup
+fn session_time(millis: u128) -> String {
+    let tenths = millis / 100;
+    format!(
+        "{}:{:02}:{:02}.{}",
+        tenths / 36_000,
+        tenths / 600 % 60,
+        tenths / 10 % 60,
+        tenths % 10
+    )
+}
+
+// What the history window lists, kept in egui's memory between frames
+#[derive(Clone, Default)]
+struct Filter {
+    search: String,
+    // Match the search against whole keys, so "C" finds "Ctrl + C" but not "Cmd"
+    exact_key: bool,
+    combos_only: bool,
+    // Only the last this many minutes; everything when unset
+    last_minutes: Option<u64>,
+}
+
+const TIME_RANGES: &[(Option<u64>, Text)] = &[
+    (None, Text::AllTime),
+    (Some(1), Text::LastMinute),
+    (Some(5), Text::LastFiveMinutes),
+    (Some(15), Text::LastFifteenMinutes),
+    (Some(60), Text::LastHour),
+];
+
+impl Filter {
+    fn matches(&self, entry: &HistoryEntry, separator: &str, now: u128) -> bool {
+        let search = self.search.trim().to_lowercase();
+        let text = entry.text.to_lowercase();
+        let found = search.is_empty()
+            || if self.exact_key {
+                text.split(separator.trim())
+                    .any(|part| part.trim() == search)
+            } else {
+                text.contains(&search)
+            };
+        let combo = !separator.trim().is_empty() && entry.text.contains(separator.trim());
+        let recent = self
+            .last_minutes
+            .is_none_or(|minutes| now.saturating_sub(entry.time) <= u128::from(minutes) * 60_000);
+        found && (combo || !self.combos_only) && recent
+    }
+}
+
+// Scrollable, searchable list of every chip this session, opened with the `history` action
+pub fn show_window(ctx: &egui::Context, controls: &Controls, history: &Mutex<History>) {
+    if !controls.history_open() {
+        return;
+    }
+    let separator = controls.config.read().modifiers.separator.clone();
+
+    ctx.show_viewport_immediate(
+        egui::ViewportId::from_hash_of("history"),
+        egui::ViewportBuilder::default()
+            .with_title(tr(Text::HistoryTitle))
+            .with_inner_size([420.0, 520.0]),
+        |ctx, _class| {
+            if ctx.input(|i| i.viewport().close_requested()) {
+                controls.apply(&crate::hotkeys::Action::History);
+            }
+
+            let filter_id = egui::Id::new("history_filter");
+            let mut filter: Filter = ctx.data(|d| d.get_temp(filter_id)).unwrap_or_default();
+
+            egui::TopBottomPanel::top("history_filters").show(ctx, |ui| {
+                ui.add(
+                    egui::TextEdit::singleline(&mut filter.search)
+                        .hint_text(tr(Text::Search))
+                        .desired_width(f32::INFINITY),
+                );
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut filter.exact_key, tr(Text::ExactKey));
+                    ui.checkbox(&mut filter.combos_only, tr(Text::OnlyCombos));
+                    let range = TIME_RANGES
+                        .iter()
+                        .find(|(minutes, _)| *minutes == filter.last_minutes)
+                        .map_or(Text::AllTime, |(_, name)| *name);
+                    egui::ComboBox::from_id_salt("history_range")
+                        .selected_text(tr(range))
+                        .show_ui(ui, |ui| {
+                            for (minutes, name) in TIME_RANGES {
+                                ui.selectable_value(&mut filter.last_minutes, *minutes, tr(*name));
+                            }
+                        });
+                });
+            });
+
+            // Copied out so the lock isn't held while drawing
+            let now = now_millis();
+            let (shown, total, started_time, persistent) = {
+                let history = history.lock();
+                let shown: Vec<HistoryEntry> = history
+                    .entries
+                    .iter()
+                    .filter(|entry| filter.matches(entry, &separator, now))
+                    .cloned()
+                    .collect();
+                (
+                    shown,
+                    history.entries.len(),
+                    history.started_time,
+                    history.is_persistent(),
+                )
+            };
+
+            egui::TopBottomPanel::bottom("history_actions").show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    if shown.len() == total {
+                        ui.label(tr_args(Text::Entries, &[&total]));
+                    } else {
+                        ui.label(tr_args(Text::EntriesOf, &[&shown.len(), &total]));
+                    }
+                    if persistent {
+                        ui.colored_label(
+                            egui::Color32::from_rgb(255, 110, 110),
+                            format!("● {}", tr(Text::RecordingToDisk)),
+                        );
+                    }
+                    if ui.button(tr(Text::Clear)).clicked() {
+                        history.lock().clear();
+                    }
+                });
+            });
+
+            egui::CentralPanel::default().show(ctx, |ui| {
+                let row_height = ui.text_style_height(&egui::TextStyle::Body);
+                // Newest at the bottom, following new entries like a terminal
+                egui::ScrollArea::vertical()
+                    .auto_shrink(false)
+                    .stick_to_bottom(true)
+                    .show_rows(ui, row_height, shown.len(), |ui, rows| {
+                        for entry in &shown[rows] {
+                            ui.horizontal(|ui| {
+                                // Entries kept on disk from earlier runs count back from startup
+                                let time = if entry.time < started_time {
+                                    format!("-{}", session_time(started_time - entry.time))
+                                } else {
+                                    session_time(entry.time - started_time)
+                                };
+                                ui.label(egui::RichText::new(time).monospace().weak());
+                                ui.label(&entry.text);
+                            });
+                        }
+                    });
+            });
+
+            ctx.data_mut(|d| d.insert_temp(filter_id, filter));
+        },
+    );
+}
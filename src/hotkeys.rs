@@ -0,0 +1,540 @@
+use crate::cheat_sheet::{self, CheatSheet};
+use crate::config::Combo;
+use crate::config::{
+    Anchor, ClockMode, PRESENTATION_PROFILE, SharedConfig, State, StateSaver, ThemeMode,
+};
+use crate::event_log::SharedEventLog;
+use crate::focus;
+use crate::i18n::{Text, tr, tr_args};
+use crate::input::Source;
+use crate::macros::{self, Capture, Macro};
+use crate::screenshot;
+use crate::session::SessionEvent;
+use crate::timer::{self, Timer, TimerKind};
+use crate::ui::Repaint;
+use crate::which_key;
+use crate::{ChipSender, ChipUpdate, KeyPress};
+use parking_lot::Mutex;
+use serde::{Deserialize, Deserializer};
+use std::fmt;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+// Something a hotkey can trigger
+#[derive(Clone, PartialEq, Debug)]
+pub enum Action {
+    // Empty the overlay immediately
+    Clear,
+    // Hide or show the overlay windows; capture keeps running while hidden
+    ToggleVisibility,
+    // Stop or resume adding chips
+    Pause,
+    // Cycle through the config's profiles in name order
+    NextProfile,
+    // Switch to a named profile
+    Profile(String),
+    // Switch to the presentation profile, or back to the startup profile
+    Presentation,
+    // Burst of confetti over the overlay
+    Confetti,
+    // Switch between the configured theme and high contrast
+    HighContrast,
+    // Dark, light or system chips, whatever the config says
+    SetTheme(ThemeMode),
+    // Make the chips larger or smaller, or back to the configured size
+    ZoomIn,
+    ZoomOut,
+    ZoomReset,
+    // Open or close the window listing every chip this session
+    History,
+    // Note in the `--log-file` that a screen recording starts now, so exports line up with the video
+    RecordingStart,
+    // Start collecting the combos pressed into a macro with this name (or a numbered one),
+    // or save the one being collected to the macro library
+    MacroRecord(Option<String>),
+    // Show or hide the shortcuts of the focused application over the whole screen
+    CheatSheet,
+    // Count down from a duration in the panel
+    Countdown(Duration),
+    // Start a stopwatch in the panel, or stop the running one
+    Stopwatch,
+    // Hide the countdown or stopwatch
+    TimerStop,
+    // Stop or allow moving the overlay by dragging it
+    LockPosition,
+    // Put the last few chips on the clipboard as text; `history.copy_count` of them by default
+    CopyKeys(Option<usize>),
+    // Image of the panel to the clipboard or a PNG, as configured, or to the given PNG
+    Screenshot(Option<PathBuf>),
+    // The last few seconds of the panel as an animation, to the screenshot folder or the given file
+    ExportReplay(Option<PathBuf>),
+}
+
+// Mark written by `recording-start`, which exports count video time from
+pub const RECORDING_START: &str = "recording-start";
+
+// Each zoom step scales the chips by this much
+const ZOOM_STEP: f32 = 1.1;
+const MIN_ZOOM: f32 = 0.5;
+const MAX_ZOOM: f32 = 3.0;
+
+impl Action {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let (name, argument) = match text.trim().split_once(' ') {
+            Some((name, argument)) => (name, Some(argument.trim())),
+            None => (text.trim(), None),
+        };
+
+        match (name, argument) {
+            ("clear", None) => Ok(Action::Clear),
+            ("toggle-visibility" | "hide", None) => Ok(Action::ToggleVisibility),
+            ("pause", None) => Ok(Action::Pause),
+            ("next-profile", None) => Ok(Action::NextProfile),
+            ("presentation", None) => Ok(Action::Presentation),
+            ("confetti", None) => Ok(Action::Confetti),
+            ("high-contrast", None) => Ok(Action::HighContrast),
+            ("set-theme", Some(mode)) => match mode {
+                "dark" => Ok(Action::SetTheme(ThemeMode::Dark)),
+                "light" => Ok(Action::SetTheme(ThemeMode::Light)),
+                "system" => Ok(Action::SetTheme(ThemeMode::System)),
+                _ => Err(format!(
+                    "unknown theme `{}`, expected dark, light or system",
+                    mode
+                )),
+            },
+            ("zoom-in", None) => Ok(Action::ZoomIn),
+            ("zoom-out", None) => Ok(Action::ZoomOut),
+            ("zoom-reset", None) => Ok(Action::ZoomReset),
+            ("history", None) => Ok(Action::History),
+            ("recording-start", None) => Ok(Action::RecordingStart),
+            ("cheat-sheet", None) => Ok(Action::CheatSheet),
+            ("countdown", Some(duration)) => timer::parse_duration(duration).map(Action::Countdown),
+            ("stopwatch", None) => Ok(Action::Stopwatch),
+            ("timer-stop", None) => Ok(Action::TimerStop),
+            ("lock-position", None) => Ok(Action::LockPosition),
+            ("copy-keys", None) => Ok(Action::CopyKeys(None)),
+            ("screenshot", path) => Ok(Action::Screenshot(path.map(PathBuf::from))),
+            ("export-replay", path) => Ok(Action::ExportReplay(path.map(PathBuf::from))),
+            ("copy-keys", Some(count)) => match count.parse() {
+                Ok(count) if count > 0 => Ok(Action::CopyKeys(Some(count))),
+                _ => Err(format!("invalid number of keys `{}`", count)),
+            },
+            ("macro-record", name) => Ok(Action::MacroRecord(name.map(str::to_string))),
+            ("profile", Some(profile)) => Ok(Action::Profile(profile.to_string())),
+            _ => Err(format!("unknown action `{}`", text.trim())),
+        }
+    }
+
+    // For actions from the HTTP API and plugins, which mustn't write anywhere they like: a
+    // screenshot or replay may only be given a file name, and goes in `dir`
+    pub fn confined(self, dir: &Path) -> Result<Self, String> {
+        let confine = |path: PathBuf| {
+            let mut components = path.components();
+            match (components.next(), components.next()) {
+                (Some(Component::Normal(name)), None) => Ok(dir.join(name)),
+                _ => Err(format!("`{}` is not a file name", path.display())),
+            }
+        };
+        match self {
+            Action::Screenshot(Some(path)) => {
+                confine(path).map(|path| Action::Screenshot(Some(path)))
+            }
+            Action::ExportReplay(Some(path)) => {
+                confine(path).map(|path| Action::ExportReplay(Some(path)))
+            }
+            action => Ok(action),
+        }
+    }
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Action::Clear => f.write_str("clear"),
+            Action::ToggleVisibility => f.write_str("toggle-visibility"),
+            Action::Pause => f.write_str("pause"),
+            Action::NextProfile => f.write_str("next-profile"),
+            Action::Profile(profile) => write!(f, "profile {}", profile),
+            Action::Presentation => f.write_str("presentation"),
+            Action::Confetti => f.write_str("confetti"),
+            Action::HighContrast => f.write_str("high-contrast"),
+            Action::SetTheme(ThemeMode::Dark) => f.write_str("set-theme dark"),
+            Action::SetTheme(ThemeMode::Light) => f.write_str("set-theme light"),
+            Action::SetTheme(ThemeMode::System) => f.write_str("set-theme system"),
+            Action::ZoomIn => f.write_str("zoom-in"),
+            Action::ZoomOut => f.write_str("zoom-out"),
+            Action::ZoomReset => f.write_str("zoom-reset"),
+            Action::History => f.write_str("history"),
+            Action::RecordingStart => f.write_str("recording-start"),
+            Action::CheatSheet => f.write_str("cheat-sheet"),
+            Action::Countdown(duration) => {
+                write!(f, "countdown {}", timer::format_duration(*duration))
+            }
+            Action::Stopwatch => f.write_str("stopwatch"),
+            Action::TimerStop => f.write_str("timer-stop"),
+            Action::LockPosition => f.write_str("lock-position"),
+            Action::CopyKeys(None) => f.write_str("copy-keys"),
+            Action::CopyKeys(Some(count)) => write!(f, "copy-keys {}", count),
+            Action::Screenshot(None) => f.write_str("screenshot"),
+            Action::Screenshot(Some(path)) => write!(f, "screenshot {}", path.display()),
+            Action::ExportReplay(None) => f.write_str("export-replay"),
+            Action::ExportReplay(Some(path)) => write!(f, "export-replay {}", path.display()),
+            Action::MacroRecord(None) => f.write_str("macro-record"),
+            Action::MacroRecord(Some(name)) => write!(f, "macro-record {}", name),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Action {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        Action::parse(&text).map_err(serde::de::Error::custom)
+    }
+}
+
+// Runtime state that actions change, shared by the listener and the UI
+pub struct Controls {
+    pub chips: ChipSender,
+    pub config: SharedConfig,
+    pub visible: AtomicBool,
+    pub paused: AtomicBool,
+    // Set until the UI starts the requested burst
+    pub confetti: AtomicBool,
+    // Inverts the config's `theme.high_contrast`
+    pub high_contrast: AtomicBool,
+    // Replaces the config's `theme.mode`, across reloads and profile switches
+    pub theme_mode: Mutex<Option<ThemeMode>>,
+    // Inverts the config's `position.locked`
+    pub position_lock: AtomicBool,
+    // How many chips to copy to the clipboard
+    pub copy_keys: Mutex<Option<usize>>,
+    pub screenshot: Mutex<Option<screenshot::Request>>,
+    pub export_replay: Mutex<Option<screenshot::Request>>,
+    // Kept across restarts in the state file
+    pub zoom: Mutex<f32>,
+    // Where the panel was snapped to; kept across restarts too
+    pub anchor: Mutex<Option<Anchor>>,
+    state_saver: StateSaver,
+    pub history: AtomicBool,
+    // `--log-file`, where recording marks go
+    pub event_log: Option<SharedEventLog>,
+    // Macro being recorded with `macro-record`
+    pub capture: Mutex<Option<Capture>>,
+    // The macro library, kept up to date as macros are saved
+    pub macros: Mutex<Vec<Macro>>,
+    macro_saver: macros::Saver,
+    // Hints shown after a which-key prefix
+    pub which_key: Mutex<Option<which_key::Pending>>,
+    // Cheat sheet on screen
+    pub cheat_sheet: Mutex<Option<CheatSheet>>,
+    // Set until the UI shows or hides the cheat sheet
+    pub cheat_sheet_toggle: AtomicBool,
+    // Countdown or stopwatch in the panel
+    pub timer: Mutex<Option<Timer>>,
+    // Last `recording-start`, for the recording clock
+    pub recording_started: Mutex<Option<Instant>>,
+    pub repaint: Repaint,
+}
+
+impl Controls {
+    pub fn new(
+        chips: ChipSender,
+        config: SharedConfig,
+        event_log: Option<SharedEventLog>,
+        repaint: Repaint,
+    ) -> Self {
+        let macros = macros::load(&config.read().macros.path).unwrap_or_else(|error| {
+            eprintln!("Error loading macros: {}", error);
+            Vec::new()
+        });
+        let state = State::load();
+        Self {
+            chips,
+            config,
+            visible: AtomicBool::new(true),
+            paused: AtomicBool::new(false),
+            confetti: AtomicBool::new(false),
+            high_contrast: AtomicBool::new(false),
+            theme_mode: Mutex::new(None),
+            position_lock: AtomicBool::new(false),
+            copy_keys: Mutex::new(None),
+            screenshot: Mutex::new(None),
+            export_replay: Mutex::new(None),
+            zoom: Mutex::new(state.zoom.clamp(MIN_ZOOM, MAX_ZOOM)),
+            anchor: Mutex::new(state.anchor),
+            state_saver: StateSaver::spawn(),
+            history: AtomicBool::new(false),
+            event_log,
+            capture: Mutex::new(None),
+            macros: Mutex::new(macros),
+            macro_saver: macros::Saver::spawn(),
+            which_key: Mutex::new(None),
+            cheat_sheet: Mutex::new(None),
+            cheat_sheet_toggle: AtomicBool::new(false),
+            timer: Mutex::new(None),
+            recording_started: Mutex::new(None),
+            repaint,
+        }
+    }
+
+    pub fn apply(&self, action: &Action) {
+        match action {
+            Action::Clear => {
+                let _ = self.chips.send(ChipUpdate::Clear);
+            }
+            Action::ToggleVisibility => {
+                self.visible.fetch_xor(true, Ordering::Relaxed);
+            }
+            Action::Pause => {
+                self.paused.fetch_xor(true, Ordering::Relaxed);
+            }
+            Action::NextProfile => {
+                let next = self.config.read().next_profile();
+                self.switch_profile(next.as_deref());
+            }
+            Action::Profile(profile) => self.switch_profile(Some(profile.as_str())),
+            Action::Presentation => {
+                let config = self.config.read();
+                let target = if config.active_profile.as_deref() == Some(PRESENTATION_PROFILE) {
+                    config.profile.clone()
+                } else {
+                    Some(PRESENTATION_PROFILE.to_string())
+                };
+                drop(config);
+                self.switch_profile(target.as_deref());
+            }
+            Action::Confetti => self.confetti.store(true, Ordering::Relaxed),
+            Action::HighContrast => {
+                self.high_contrast.fetch_xor(true, Ordering::Relaxed);
+            }
+            Action::SetTheme(mode) => *self.theme_mode.lock() = Some(*mode),
+            Action::ZoomIn => self.set_zoom(self.zoom() * ZOOM_STEP),
+            Action::ZoomOut => self.set_zoom(self.zoom() / ZOOM_STEP),
+            Action::ZoomReset => self.set_zoom(1.0),
+            Action::History => {
+                self.history.fetch_xor(true, Ordering::Relaxed);
+            }
+            // Not shown as a chip, which would end up in the recording
+            Action::RecordingStart => {
+                *self.recording_started.lock() = Some(Instant::now());
+                match &self.event_log {
+                    Some(log) => {
+                        log.lock()
+                            .write(SessionEvent::Mark(RECORDING_START.to_string()));
+                        eprintln!("Marked the start of a recording");
+                    }
+                    // The recording clock needs no log
+                    None if self.config.read().clock.show == ClockMode::Recording => {}
+                    None => eprintln!("Marking a recording start needs --log-file"),
+                }
+            }
+            Action::MacroRecord(name) => self.toggle_capture(name.as_deref()),
+            // Reading the sheets and asking which app has focus can take a while, so that
+            // happens on the UI thread rather than the input hook's
+            Action::CheatSheet => {
+                self.cheat_sheet_toggle.fetch_xor(true, Ordering::Relaxed);
+            }
+            Action::Countdown(duration) => {
+                *self.timer.lock() = Some(Timer::new(TimerKind::Countdown(*duration)));
+            }
+            Action::Stopwatch => {
+                let mut timer = self.timer.lock();
+                match timer.as_mut() {
+                    Some(running) if running.is_running_stopwatch() => running.stop(),
+                    _ => *timer = Some(Timer::new(TimerKind::Stopwatch)),
+                }
+            }
+            Action::TimerStop => *self.timer.lock() = None,
+            Action::LockPosition => {
+                self.position_lock.fetch_xor(true, Ordering::Relaxed);
+            }
+            // The clipboard belongs to the UI thread, which picks this up on its next frame
+            Action::CopyKeys(count) => {
+                let count = count.unwrap_or(self.config.read().history.copy_count);
+                *self.copy_keys.lock() = Some(count);
+            }
+            Action::Screenshot(path) => {
+                *self.screenshot.lock() = Some(screenshot::Request { path: path.clone() });
+            }
+            // The frames live on the UI thread too
+            Action::ExportReplay(path) => {
+                *self.export_replay.lock() = Some(screenshot::Request { path: path.clone() });
+            }
+        }
+        self.repaint.request();
+    }
+
+    fn set_zoom(&self, zoom: f32) {
+        let zoom = zoom.clamp(MIN_ZOOM, MAX_ZOOM);
+        *self.zoom.lock() = zoom;
+        self.save_state();
+    }
+
+    // Remember where the panel was snapped to, across restarts
+    pub fn set_anchor(&self, anchor: Anchor) {
+        *self.anchor.lock() = Some(anchor);
+        self.save_state();
+    }
+
+    fn save_state(&self) {
+        self.state_saver.save(State {
+            zoom: *self.zoom.lock(),
+            anchor: *self.anchor.lock(),
+        });
+    }
+
+    // The new config is built without holding the lock, which the UI reads every frame; if the
+    // file was reloaded meanwhile, it is built again from that
+    fn switch_profile(&self, profile: Option<&str>) {
+        loop {
+            let current = self.config.read().clone();
+            let switched = match current.with_profile(profile) {
+                Ok(switched) => switched,
+                Err(error) => {
+                    eprintln!("Error switching profile: {}", error);
+                    return;
+                }
+            };
+            let mut config = self.config.write();
+            if Arc::ptr_eq(&*config, &current) {
+                *config = Arc::new(switched);
+                break;
+            }
+        }
+
+        // Show which profile is now active
+        let name = profile.unwrap_or(tr(Text::DefaultProfile));
+        self.notify(tr_args(Text::Profile, &[&name]));
+    }
+
+    fn toggle_capture(&self, name: Option<&str>) {
+        let path = self.config.read().macros.path.clone();
+        let mut capture = self.capture.lock();
+        match capture.take() {
+            Some(Capture { name, combos }) if combos.is_empty() => {
+                eprintln!("Macro `{}` not saved: no combos were pressed", name);
+                self.notify(tr_args(Text::MacroDiscarded, &[&name]));
+            }
+            Some(Capture { name, combos }) => {
+                let count = combos.len();
+                let saved = Macro {
+                    name: name.clone(),
+                    combos,
+                };
+                let mut library = self.macros.lock();
+                library.retain(|known| known.name != saved.name);
+                library.push(saved.clone());
+                drop(library);
+                self.macro_saver.save(path, saved);
+                self.notify(tr_args(Text::MacroSaved, &[&name, &count]));
+            }
+            None => {
+                let name = match name {
+                    Some(name) => name.to_string(),
+                    None => format!("Macro {}", self.macros.lock().len() + 1),
+                };
+                self.notify(tr_args(Text::RecordingMacro, &[&name]));
+                *capture = Some(Capture {
+                    name,
+                    combos: Vec::new(),
+                });
+            }
+        }
+    }
+
+    pub fn toggle_cheat_sheet(&self) {
+        let mut shown = self.cheat_sheet.lock();
+        if shown.take().is_some() {
+            return;
+        }
+        let dir = self.config.read().cheat_sheets.dir.clone();
+        let app = focus::focused_app();
+        match cheat_sheet::load(&dir, app.as_deref()) {
+            Ok(Some(sheet)) => *shown = Some(sheet),
+            Ok(None) => {
+                let app = app.as_deref().unwrap_or(tr(Text::ThisApplication));
+                self.notify(tr_args(Text::NoCheatSheet, &[&app]));
+            }
+            Err(error) => eprintln!("Error loading cheat sheet: {}", error),
+        }
+    }
+
+    // Add a combo to the macro being recorded, if any
+    pub fn capture_combo(&self, combo: Combo) {
+        if let Some(capture) = self.capture.lock().as_mut() {
+            capture.combos.push(combo);
+        }
+    }
+
+    // Name of the macro being recorded
+    pub fn capturing(&self) -> Option<String> {
+        self.capture
+            .lock()
+            .as_ref()
+            .map(|capture| capture.name.clone())
+    }
+
+    // Short message chip, e.g. which profile is now active
+    pub fn notify(&self, text: String) {
+        let key_press = KeyPress {
+            text,
+            timestamp: Instant::now(),
+            mouse: None,
+            source: Source::Keyboard,
+            display: Duration::from_millis(self.config.read().durations.other_ms),
+            color: None,
+            icon: None,
+        };
+        let _ = self.chips.send(ChipUpdate::Push(key_press));
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible.load(Ordering::Relaxed)
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+
+    pub fn zoom(&self) -> f32 {
+        *self.zoom.lock()
+    }
+
+    pub fn anchor(&self) -> Option<Anchor> {
+        *self.anchor.lock()
+    }
+
+    pub fn high_contrast_toggled(&self) -> bool {
+        self.high_contrast.load(Ordering::Relaxed)
+    }
+
+    pub fn position_lock_toggled(&self) -> bool {
+        self.position_lock.load(Ordering::Relaxed)
+    }
+
+    pub fn history_open(&self) -> bool {
+        self.history.load(Ordering::Relaxed)
+    }
+
+    pub fn take_confetti(&self) -> bool {
+        self.confetti.swap(false, Ordering::Relaxed)
+    }
+
+    pub fn take_cheat_sheet_toggle(&self) -> bool {
+        self.cheat_sheet_toggle.swap(false, Ordering::Relaxed)
+    }
+
+    pub fn take_copy_keys(&self) -> Option<usize> {
+        self.copy_keys.lock().take()
+    }
+
+    pub fn take_screenshot(&self) -> Option<screenshot::Request> {
+        self.screenshot.lock().take()
+    }
+
+    pub fn take_export_replay(&self) -> Option<screenshot::Request> {
+        self.export_replay.lock().take()
+    }
+}
@@ -0,0 +1,87 @@
+use crate::input::EventSink;
+use parking_lot::Mutex;
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+// How often /dev/input is checked for newly plugged devices
+const RESCAN_INTERVAL: Duration = Duration::from_secs(2);
+
+type Reader = fn(evdev::Device, Arc<Mutex<EventSink>>) -> Result<(), String>;
+
+// Read every device passing `wanted` on its own thread, picking up devices plugged in
+// later and dropping unplugged ones. Fails only if none is there at startup.
+pub fn read_devices(
+    kind: &'static str,
+    wanted: fn(&evdev::Device) -> bool,
+    read: Reader,
+    sink: EventSink,
+) -> Result<(), String> {
+    let sink = Arc::new(Mutex::new(sink));
+    let open: Arc<Mutex<HashSet<PathBuf>>> = Arc::default();
+    // Devices we've looked at that aren't of this kind, so they aren't reopened every scan
+    let mut skipped = HashSet::new();
+    let mut first_scan = true;
+
+    loop {
+        let Ok(entries) = std::fs::read_dir("/dev/input") else {
+            return Err("cannot list /dev/input".to_string());
+        };
+        let mut present = HashSet::new();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let is_event_node = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with("event"));
+            if !is_event_node {
+                continue;
+            }
+            present.insert(path.clone());
+            if skipped.contains(&path) || open.lock().contains(&path) {
+                continue;
+            }
+            let device = match evdev::Device::open(&path) {
+                Ok(device) if wanted(&device) => device,
+                Ok(_) => {
+                    skipped.insert(path);
+                    continue;
+                }
+                // Tried again next scan: permissions can change, e.g. once udev has applied its
+                // rules to a new node or the user joined the input group
+                Err(_) => continue,
+            };
+
+            if !first_scan {
+                eprintln!(
+                    "Input device connected: {}",
+                    device.name().unwrap_or("unknown")
+                );
+            }
+            open.lock().insert(path.clone());
+            let (sink, open) = (Arc::clone(&sink), Arc::clone(&open));
+            thread::spawn(move || {
+                // Unplugging ends the read with ENODEV
+                if let Err(error) = read(device, sink) {
+                    eprintln!(
+                        "Stopped reading {} device {}: {}",
+                        kind,
+                        path.display(),
+                        error
+                    );
+                }
+                open.lock().remove(&path);
+            });
+        }
+        // A node number freed by an unplugged device may come back as something else
+        skipped.retain(|path| present.contains(path));
+
+        if first_scan && open.lock().is_empty() {
+            return Err(format!("no readable {} devices in /dev/input", kind));
+        }
+        first_scan = false;
+        thread::sleep(RESCAN_INTERVAL);
+    }
+}
@@ -0,0 +1,277 @@
+use crate::config::HttpConfig;
+use crate::history::History;
+use crate::hotkeys::{Action, Controls};
+use crate::paths;
+use parking_lot::Mutex;
+use std::io::Write;
+use std::sync::Arc;
+use std::thread;
+use tiny_http::{Header, Method, Request, Response, Server};
+
+// Chips GET /history returns unless `?limit=` asks for more, up to the whole scrollback
+const HISTORY_LIMIT: usize = 200;
+
+// Serve the REST API on localhost: POST /<action>, GET /history, GET /stats
+pub fn serve(config: &HttpConfig, controls: Arc<Controls>, history: Arc<Mutex<History>>) {
+    let port = config.port;
+    let token = match token(config) {
+        Ok(token) => token,
+        Err(error) => {
+            eprintln!("Error setting up the HTTP API token: {}", error);
+            return;
+        }
+    };
+    thread::spawn(move || {
+        let server = match Server::http(("127.0.0.1", port)) {
+            Ok(server) => server,
+            Err(error) => {
+                eprintln!("Error starting HTTP API on port {}: {}", port, error);
+                return;
+            }
+        };
+
+        let json = Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..])
+            .expect("static header is valid");
+
+        for request in server.incoming_requests() {
+            let (status, body) = match authorize(&request, port, &token) {
+                Ok(()) => route(request.method(), request.url(), &controls, &history),
+                Err(status) => (status, r#"{"error":"forbidden"}"#.to_string()),
+            };
+            let response = Response::from_string(body)
+                .with_status_code(status)
+                .with_header(json.clone());
+            let _ = request.respond(response);
+        }
+    });
+}
+
+// The configured token, or one generated on first use and kept in the data folder for scripts
+// to read
+fn token(config: &HttpConfig) -> std::io::Result<String> {
+    if !config.token.is_empty() {
+        return Ok(config.token.clone());
+    }
+    let path = paths::data_dir().join("http-token");
+    if let Ok(token) = std::fs::read_to_string(&path)
+        && !token.trim().is_empty()
+    {
+        // Earlier versions left the file readable by everyone
+        paths::restrict(&path)?;
+        return Ok(token.trim().to_string());
+    }
+    let mut bytes = [0u8; 16];
+    getrandom::fill(&mut bytes).map_err(|error| std::io::Error::other(error.to_string()))?;
+    let token: String = bytes.iter().map(|byte| format!("{:02x}", byte)).collect();
+    paths::create_parent(&path)?;
+    let mut file = paths::private(
+        std::fs::OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true),
+    )
+    .open(&path)?;
+    // An empty file left from before is opened with the permissions it already has
+    paths::restrict(&path)?;
+    file.write_all(token.as_bytes())?;
+    println!("HTTP API token written to {}", path.display());
+    Ok(token)
+}
+
+// Only local clients that know the token. The Host check stops DNS rebinding and the Origin check
+// stops web pages; the token keeps out other local users and forged form posts.
+fn authorize(request: &Request, port: u16, token: &str) -> Result<(), u16> {
+    let header = |name: &'static str| {
+        request
+            .headers()
+            .iter()
+            .find(|header| header.field.equiv(name))
+            .map(|header| header.value.as_str())
+    };
+
+    let local = |host: &str| {
+        let port = port.to_string();
+        ["127.0.0.1", "localhost", "[::1]"].iter().any(|name| {
+            host == *name
+                || host
+                    .strip_prefix(name)
+                    .and_then(|rest| rest.strip_prefix(':'))
+                    == Some(port.as_str())
+        })
+    };
+    if !header("Host").is_some_and(local) {
+        return Err(403);
+    }
+    if let Some(origin) = header("Origin")
+        && !origin
+            .split_once("://")
+            .is_some_and(|(scheme, host)| scheme == "http" && local(host))
+    {
+        return Err(403);
+    }
+
+    let bearer = header("Authorization").and_then(|value| value.strip_prefix("Bearer "));
+    if bearer
+        .or(query(request.url(), "token"))
+        .is_some_and(|given| same_token(given, token))
+    {
+        Ok(())
+    } else {
+        Err(401)
+    }
+}
+
+// Compares every byte whatever the first difference, so response times don't give the token
+// away a byte at a time
+fn same_token(given: &str, token: &str) -> bool {
+    given.len() == token.len()
+        && given
+            .bytes()
+            .zip(token.bytes())
+            .fold(0, |difference, (a, b)| difference | (a ^ b))
+            == 0
+}
+
+// Value of `name` in the URL's query string
+fn query<'a>(url: &'a str, name: &str) -> Option<&'a str> {
+    let (_, query) = url.split_once('?')?;
+    query.split('&').find_map(|pair| {
+        pair.strip_prefix(name)
+            .and_then(|rest| rest.strip_prefix('='))
+    })
+}
+
+fn route(
+    method: &Method,
+    url: &str,
+    controls: &Controls,
+    history: &Mutex<History>,
+) -> (u16, String) {
+    let path = url.split('?').next().unwrap_or("").trim_matches('/');
+
+    match (method, path) {
+        (Method::Get, "history") => {
+            let limit = query(url, "limit").and_then(|limit| limit.parse().ok());
+            ok_json(&history.lock().latest(limit.unwrap_or(HISTORY_LIMIT)))
+        }
+        (Method::Get, "stats") => ok_json(&history.lock().stats()),
+        // POST /profile/gaming is the `profile gaming` action
+        (Method::Post, action) => match Action::parse(&action.replace('/', " "))
+            .and_then(|action| action.confined(&controls.config.read().screenshot.dir))
+        {
+            Ok(action) => {
+                controls.apply(&action);
+                (200, r#"{"ok":true}"#.to_string())
+            }
+            Err(error) => (404, serde_json::json!({ "error": error }).to_string()),
+        },
+        _ => (404, r#"{"error":"not found"}"#.to_string()),
+    }
+}
+
+fn ok_json<T: serde::Serialize>(value: &T) -> (u16, String) {
+    match serde_json::to_string(value) {
+        Ok(body) => (200, body),
+        Err(error) => (
+            500,
+            serde_json::json!({ "error": error.to_string() }).to_string(),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tiny_http::TestRequest;
+
+    const PORT: u16 = 7373;
+    const TOKEN: &str = "secret";
+
+    fn request(url: &str, headers: &[(&str, &str)]) -> Request {
+        headers
+            .iter()
+            .fold(
+                TestRequest::new().with_method(Method::Post).with_path(url),
+                |request, (name, value)| {
+                    request.with_header(
+                        Header::from_bytes(name.as_bytes(), value.as_bytes())
+                            .expect("test header is valid"),
+                    )
+                },
+            )
+            .into()
+    }
+
+    fn check(url: &str, headers: &[(&str, &str)]) -> Result<(), u16> {
+        authorize(&request(url, headers), PORT, TOKEN)
+    }
+
+    #[test]
+    fn host_must_be_local_with_the_right_port() {
+        let bearer = ("Authorization", "Bearer secret");
+        assert_eq!(check("/pause", &[bearer]), Err(403));
+        assert_eq!(
+            check("/pause", &[("Host", "example.com"), bearer]),
+            Err(403)
+        );
+        assert_eq!(
+            check("/pause", &[("Host", "localhost.evil.com:7373"), bearer]),
+            Err(403)
+        );
+        assert_eq!(
+            check("/pause", &[("Host", "localhost:7374"), bearer]),
+            Err(403)
+        );
+        assert_eq!(
+            check("/pause", &[("Host", "localhost:7373"), bearer]),
+            Ok(())
+        );
+        assert_eq!(check("/pause", &[("Host", "127.0.0.1"), bearer]), Ok(()));
+        assert_eq!(check("/pause", &[("Host", "[::1]:7373"), bearer]), Ok(()));
+    }
+
+    #[test]
+    fn origin_must_be_local_when_sent() {
+        let host = ("Host", "localhost:7373");
+        let bearer = ("Authorization", "Bearer secret");
+        let origin = |origin| check("/pause", &[host, bearer, ("Origin", origin)]);
+        assert_eq!(origin("http://localhost.evil.com"), Err(403));
+        assert_eq!(origin("http://localhost.evil.com:7373"), Err(403));
+        assert_eq!(origin("http://evil.com"), Err(403));
+        assert_eq!(origin("http://localhost:8080"), Err(403));
+        assert_eq!(origin("https://localhost:7373"), Err(403));
+        assert_eq!(origin("null"), Err(403));
+        assert_eq!(origin("http://localhost:7373"), Ok(()));
+        assert_eq!(origin("http://127.0.0.1:7373"), Ok(()));
+    }
+
+    #[test]
+    fn token_from_bearer_header_or_query() {
+        let host = ("Host", "localhost:7373");
+        assert_eq!(check("/pause", &[host]), Err(401));
+        assert_eq!(
+            check("/pause", &[host, ("Authorization", "Bearer wrong")]),
+            Err(401)
+        );
+        assert_eq!(
+            check("/pause", &[host, ("Authorization", "secret")]),
+            Err(401)
+        );
+        assert_eq!(
+            check("/pause", &[host, ("Authorization", "Bearer secret")]),
+            Ok(())
+        );
+        assert_eq!(check("/pause?token=secret", &[host]), Ok(()));
+        assert_eq!(check("/history?limit=5&token=secret", &[host]), Ok(()));
+        assert_eq!(check("/pause?token=wrong", &[host]), Err(401));
+        assert_eq!(check("/pause?token=secrets", &[host]), Err(401));
+    }
+
+    #[test]
+    fn same_token_needs_every_byte() {
+        assert!(same_token("secret", "secret"));
+        assert!(!same_token("secreT", "secret"));
+        assert!(!same_token("secre", "secret"));
+        assert!(!same_token("", "secret"));
+    }
+}
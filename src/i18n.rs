@@ -0,0 +1,572 @@
+// Translations of the overlay's own strings: markers, menus, notices and dialogs. Chip text, key
+// names and messages on the terminal stay as they are.
+
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Default, Debug)]
+pub enum Language {
+    // The system's language when there is a translation for it, English otherwise
+    #[default]
+    #[serde(rename = "auto")]
+    Auto,
+    #[serde(rename = "en")]
+    English,
+    #[serde(rename = "de")]
+    German,
+    #[serde(rename = "fr")]
+    French,
+    #[serde(rename = "es")]
+    Spanish,
+    #[serde(rename = "pt")]
+    Portuguese,
+}
+
+const LANGUAGES: [Language; 6] = [
+    Language::Auto,
+    Language::English,
+    Language::German,
+    Language::French,
+    Language::Spanish,
+    Language::Portuguese,
+];
+
+// Set from the config by the UI thread each frame, read wherever a string is shown
+static CURRENT: AtomicU8 = AtomicU8::new(Language::English as u8);
+
+pub fn set(language: Language) {
+    let language = match language {
+        Language::Auto => detect(),
+        language => language,
+    };
+    CURRENT.store(language as u8, Ordering::Relaxed);
+}
+
+fn current() -> Language {
+    LANGUAGES[CURRENT.load(Ordering::Relaxed) as usize]
+}
+
+fn detect() -> Language {
+    match locale_language().as_deref() {
+        Some("de") => Language::German,
+        Some("fr") => Language::French,
+        Some("es") => Language::Spanish,
+        Some("pt") => Language::Portuguese,
+        _ => Language::English,
+    }
+}
+
+// ISO 639-1 code of the user's language, e.g. "pt" for pt_PT.UTF-8, from the environment as set on
+// Linux and in macOS terminals, or else from the system settings. Read once.
+pub fn locale_language() -> Option<String> {
+    static LANGUAGE: OnceLock<Option<String>> = OnceLock::new();
+    LANGUAGE
+        .get_or_init(|| {
+            let locale = ["LC_ALL", "LC_MESSAGES", "LANG"]
+                .iter()
+                .filter_map(|name| std::env::var(name).ok())
+                .find(|value| !value.is_empty())
+                .or_else(system_locale)?;
+            let language = locale
+                .to_lowercase()
+                .split(['_', '-', '.', '@'])
+                .next()
+                .unwrap_or_default()
+                .to_string();
+            // The C and POSIX locales say nothing about the language
+            (!language.is_empty() && language != "c" && language != "posix").then_some(language)
+        })
+        .clone()
+}
+
+#[cfg(windows)]
+fn system_locale() -> Option<String> {
+    use winapi::um::winnls::GetUserDefaultLocaleName;
+
+    const LOCALE_NAME_MAX_LENGTH: usize = 85;
+
+    let mut name = [0u16; LOCALE_NAME_MAX_LENGTH];
+    let length = unsafe { GetUserDefaultLocaleName(name.as_mut_ptr(), name.len() as i32) };
+    if length <= 1 {
+        return None;
+    }
+    Some(String::from_utf16_lossy(&name[..length as usize - 1]))
+}
+
+// Apps started from the Dock get no LANG, so ask for the first preferred language, printed as
+// `( "pt-PT", "en-US" )` over several lines
+#[cfg(target_os = "macos")]
+fn system_locale() -> Option<String> {
+    let output = std::process::Command::new("defaults")
+        .args(["read", "-g", "AppleLanguages"])
+        .output()
+        .ok()?;
+    String::from_utf8_lossy(&output.stdout)
+        .split(['(', ',', '\n'])
+        .map(|entry| entry.trim().trim_matches('"'))
+        .find(|entry| !entry.is_empty())
+        .map(str::to_string)
+}
+
+#[cfg(not(any(windows, target_os = "macos")))]
+fn system_locale() -> Option<String> {
+    None
+}
+
+// Every translatable string; `{0}`, `{1}` are filled in by `tr_args`
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Text {
+    // Overlay markers and menu
+    Paused,
+    RecordingToDisk,
+    RecordingMacro,
+    InputStopped,
+    FocusHint,
+    CopyRecentKeys,
+    Screenshot,
+    LockPosition,
+    UnlockPosition,
+    // Notices shown as chips
+    NothingToCopy,
+    Copied,
+    ScreenshotCopiedAndSaved,
+    ScreenshotCopied,
+    Saved,
+    SavingReplay,
+    NothingToExport,
+    Profile,
+    DefaultProfile,
+    MacroDiscarded,
+    MacroSaved,
+    NoCheatSheet,
+    ThisApplication,
+    // Input error dialog
+    InputProblemTitle,
+    InputUnavailable,
+    Dismiss,
+    RdevHelpMacos,
+    RdevHelpLinux,
+    RdevHelpWindows,
+    RdevHelpOther,
+    InputGroupHelp,
+    GamepadHelp,
+    RemoteHelp,
+    // macOS permissions prompt
+    PermissionsTitle,
+    PermissionsHeading,
+    PermissionsIntro,
+    Accessibility,
+    InputMonitoring,
+    OpenSettings,
+    // History window
+    HistoryTitle,
+    Search,
+    ExactKey,
+    OnlyCombos,
+    AllTime,
+    LastMinute,
+    LastFiveMinutes,
+    LastFifteenMinutes,
+    LastHour,
+    Entries,
+    EntriesOf,
+    Clear,
+    // Config problems dialog
+    ConfigProblemsTitle,
+    ConfigProblemsIntro,
+}
+
+// The string in the current language; English where a translation is missing
+pub fn tr(text: Text) -> &'static str {
+    let translated = match current() {
+        Language::Auto | Language::English => None,
+        Language::German => german(text),
+        Language::French => french(text),
+        Language::Spanish => spanish(text),
+        Language::Portuguese => portuguese(text),
+    };
+    translated.unwrap_or_else(|| english(text))
+}
+
+// `tr` with `{0}`, `{1}`... replaced by the arguments, in whatever order the language needs them
+pub fn tr_args(text: Text, args: &[&dyn Display]) -> String {
+    args.iter()
+        .enumerate()
+        .fold(tr(text).to_string(), |text, (index, arg)| {
+            text.replace(&format!("{{{}}}", index), &arg.to_string())
+        })
+}
+
+fn english(text: Text) -> &'static str {
+    match text {
+        Text::Paused => "Paused",
+        Text::RecordingToDisk => "Recording to disk",
+        Text::RecordingMacro => "Recording macro: {0}",
+        Text::InputStopped => "Input stopped: {0}",
+        Text::FocusHint => "Window in focus; press Esc to exit",
+        Text::CopyRecentKeys => "Copy recent keys",
+        Text::Screenshot => "Screenshot",
+        Text::LockPosition => "Lock position",
+        Text::UnlockPosition => "Unlock position",
+        Text::NothingToCopy => "Nothing to copy yet",
+        Text::Copied => "Copied to clipboard",
+        Text::ScreenshotCopiedAndSaved => "Screenshot copied and saved",
+        Text::ScreenshotCopied => "Screenshot copied",
+        Text::Saved => "Saved {0}",
+        Text::SavingReplay => "Saving replay",
+        Text::NothingToExport => "Nothing to export",
+        Text::Profile => "Profile: {0}",
+        Text::DefaultProfile => "default",
+        Text::MacroDiscarded => "Macro discarded: {0}",
+        Text::MacroSaved => "Macro saved: {0} ({1} combos)",
+        Text::NoCheatSheet => "No cheat sheet for {0}",
+        Text::ThisApplication => "this application",
+        Text::InputProblemTitle => "Rusteys - Input problem",
+        Text::InputUnavailable => "{0} input is not available",
+        Text::Dismiss => "Dismiss",
+        Text::RdevHelpMacos => {
+            "macOS only lets apps read the keyboard with permission. Open System Settings → Privacy & Security → Accessibility (and Input Monitoring), enable rusteys or the terminal you started it from, then restart rusteys."
+        }
+        Text::RdevHelpLinux => {
+            "Global input is read through X11, so rusteys needs an X11 session (or XWayland). With hotkey suppression enabled it reads /dev/input instead: add your user to the input group with `sudo usermod -aG input $USER`, then log out and back in."
+        }
+        Text::RdevHelpWindows => {
+            "Windows refused the global keyboard hook. Try running rusteys as administrator, especially if the focused application runs elevated."
+        }
+        Text::RdevHelpOther => "Global keyboard capture is not supported on this platform.",
+        Text::InputGroupHelp => {
+            "rusteys reads /dev/input directly. Add your user to the input group with `sudo usermod -aG input $USER`, then log out and back in."
+        }
+        Text::GamepadHelp => {
+            "Check that the controller is connected and, on Linux, that libudev is installed."
+        }
+        Text::RemoteHelp => {
+            "Check that nothing else is listening on the configured address and that the firewall allows incoming connections."
+        }
+        Text::PermissionsTitle => "Rusteys - Permissions",
+        Text::PermissionsHeading => "Rusteys needs permission to see your input",
+        Text::PermissionsIntro => {
+            "Enable rusteys (or the terminal you started it from) in both panes below. Capturing starts automatically once both are granted."
+        }
+        Text::Accessibility => "Accessibility",
+        Text::InputMonitoring => "Input Monitoring",
+        Text::OpenSettings => "Open {0} settings",
+        Text::HistoryTitle => "Rusteys - History",
+        Text::Search => "Search",
+        Text::ExactKey => "Exact key",
+        Text::OnlyCombos => "Only combos",
+        Text::AllTime => "All",
+        Text::LastMinute => "Last minute",
+        Text::LastFiveMinutes => "Last 5 minutes",
+        Text::LastFifteenMinutes => "Last 15 minutes",
+        Text::LastHour => "Last hour",
+        Text::Entries => "{0} entries",
+        Text::EntriesOf => "{0} of {1} entries",
+        Text::Clear => "Clear",
+        Text::ConfigProblemsTitle => "Rusteys - Config problems",
+        Text::ConfigProblemsIntro => {
+            "These settings in {0} were not used and their defaults apply. Fix them and save the file to reload it."
+        }
+    }
+}
+
+fn german(text: Text) -> Option<&'static str> {
+    Some(match text {
+        Text::Paused => "Pausiert",
+        Text::RecordingToDisk => "Aufzeichnung auf Festplatte",
+        Text::RecordingMacro => "Makro wird aufgezeichnet: {0}",
+        Text::InputStopped => "Eingabe gestoppt: {0}",
+        Text::FocusHint => "Fenster im Fokus; Esc zum Beenden",
+        Text::CopyRecentKeys => "Letzte Tasten kopieren",
+        Text::Screenshot => "Bildschirmfoto",
+        Text::LockPosition => "Position sperren",
+        Text::UnlockPosition => "Position entsperren",
+        Text::NothingToCopy => "Noch nichts zu kopieren",
+        Text::Copied => "In die Zwischenablage kopiert",
+        Text::ScreenshotCopiedAndSaved => "Bildschirmfoto kopiert und gespeichert",
+        Text::ScreenshotCopied => "Bildschirmfoto kopiert",
+        Text::Saved => "{0} gespeichert",
+        Text::SavingReplay => "Wiederholung wird gespeichert",
+        Text::NothingToExport => "Nichts zu exportieren",
+        Text::Profile => "Profil: {0}",
+        Text::DefaultProfile => "Standard",
+        Text::MacroDiscarded => "Makro verworfen: {0}",
+        Text::MacroSaved => "Makro gespeichert: {0} ({1} Kombinationen)",
+        Text::NoCheatSheet => "Kein Spickzettel für {0}",
+        Text::ThisApplication => "diese Anwendung",
+        Text::InputProblemTitle => "Rusteys - Eingabeproblem",
+        Text::InputUnavailable => "{0}-Eingabe ist nicht verfügbar",
+        Text::Dismiss => "Schließen",
+        Text::RdevHelpMacos => {
+            "macOS erlaubt Apps das Lesen der Tastatur nur mit Berechtigung. Öffne Systemeinstellungen → Datenschutz & Sicherheit → Bedienungshilfen (und Eingabeüberwachung), aktiviere rusteys oder das Terminal, aus dem du es gestartet hast, und starte rusteys dann neu."
+        }
+        Text::RdevHelpLinux => {
+            "Globale Eingaben werden über X11 gelesen, daher braucht rusteys eine X11-Sitzung (oder XWayland). Mit aktivierter Hotkey-Unterdrückung liest es stattdessen /dev/input: Füge deinen Benutzer mit `sudo usermod -aG input $USER` der Gruppe input hinzu, melde dich dann ab und wieder an."
+        }
+        Text::RdevHelpWindows => {
+            "Windows hat den globalen Tastatur-Hook abgelehnt. Starte rusteys als Administrator, besonders wenn die Anwendung im Fokus mit erhöhten Rechten läuft."
+        }
+        Text::RdevHelpOther => {
+            "Globale Tastaturerfassung wird auf dieser Plattform nicht unterstützt."
+        }
+        Text::InputGroupHelp => {
+            "rusteys liest /dev/input direkt. Füge deinen Benutzer mit `sudo usermod -aG input $USER` der Gruppe input hinzu, melde dich dann ab und wieder an."
+        }
+        Text::GamepadHelp => {
+            "Prüfe, ob der Controller verbunden und unter Linux libudev installiert ist."
+        }
+        Text::RemoteHelp => {
+            "Prüfe, dass nichts anderes auf der eingestellten Adresse lauscht und die Firewall eingehende Verbindungen erlaubt."
+        }
+        Text::PermissionsTitle => "Rusteys - Berechtigungen",
+        Text::PermissionsHeading => "Rusteys braucht die Berechtigung, deine Eingaben zu sehen",
+        Text::PermissionsIntro => {
+            "Aktiviere rusteys (oder das Terminal, aus dem du es gestartet hast) in beiden Bereichen unten. Die Erfassung beginnt automatisch, sobald beide erteilt sind."
+        }
+        Text::Accessibility => "Bedienungshilfen",
+        Text::InputMonitoring => "Eingabeüberwachung",
+        Text::OpenSettings => "Einstellungen für {0} öffnen",
+        Text::HistoryTitle => "Rusteys - Verlauf",
+        Text::Search => "Suchen",
+        Text::ExactKey => "Exakte Taste",
+        Text::OnlyCombos => "Nur Kombinationen",
+        Text::AllTime => "Alle",
+        Text::LastMinute => "Letzte Minute",
+        Text::LastFiveMinutes => "Letzte 5 Minuten",
+        Text::LastFifteenMinutes => "Letzte 15 Minuten",
+        Text::LastHour => "Letzte Stunde",
+        Text::Entries => "{0} Einträge",
+        Text::EntriesOf => "{0} von {1} Einträgen",
+        Text::Clear => "Leeren",
+        Text::ConfigProblemsTitle => "Rusteys - Probleme in der Konfiguration",
+        Text::ConfigProblemsIntro => {
+            "Diese Einstellungen in {0} wurden nicht übernommen, stattdessen gelten die Standardwerte. Korrigiere sie und speichere die Datei, um sie neu zu laden."
+        }
+    })
+}
+
+fn french(text: Text) -> Option<&'static str> {
+    Some(match text {
+        Text::Paused => "En pause",
+        Text::RecordingToDisk => "Enregistrement sur disque",
+        Text::RecordingMacro => "Enregistrement de la macro : {0}",
+        Text::InputStopped => "Saisie arrêtée : {0}",
+        Text::FocusHint => "Fenêtre active ; appuyez sur Échap pour quitter",
+        Text::CopyRecentKeys => "Copier les dernières touches",
+        Text::Screenshot => "Capture d'écran",
+        Text::LockPosition => "Verrouiller la position",
+        Text::UnlockPosition => "Déverrouiller la position",
+        Text::NothingToCopy => "Rien à copier pour l'instant",
+        Text::Copied => "Copié dans le presse-papiers",
+        Text::ScreenshotCopiedAndSaved => "Capture copiée et enregistrée",
+        Text::ScreenshotCopied => "Capture copiée",
+        Text::Saved => "{0} enregistré",
+        Text::SavingReplay => "Enregistrement de la rediffusion",
+        Text::NothingToExport => "Rien à exporter",
+        Text::Profile => "Profil : {0}",
+        Text::DefaultProfile => "par défaut",
+        Text::MacroDiscarded => "Macro abandonnée : {0}",
+        Text::MacroSaved => "Macro enregistrée : {0} ({1} combinaisons)",
+        Text::NoCheatSheet => "Aucun aide-mémoire pour {0}",
+        Text::ThisApplication => "cette application",
+        Text::InputProblemTitle => "Rusteys - Problème de saisie",
+        Text::InputUnavailable => "L'entrée {0} n'est pas disponible",
+        Text::Dismiss => "Fermer",
+        Text::RdevHelpMacos => {
+            "macOS n'autorise la lecture du clavier qu'avec une autorisation. Ouvrez Réglages Système → Confidentialité et sécurité → Accessibilité (et Surveillance de l'entrée), activez rusteys ou le terminal depuis lequel vous l'avez lancé, puis redémarrez rusteys."
+        }
+        Text::RdevHelpLinux => {
+            "La saisie globale est lue via X11, rusteys a donc besoin d'une session X11 (ou XWayland). Avec la suppression des raccourcis activée, il lit /dev/input à la place : ajoutez votre utilisateur au groupe input avec `sudo usermod -aG input $USER`, puis déconnectez-vous et reconnectez-vous."
+        }
+        Text::RdevHelpWindows => {
+            "Windows a refusé le hook clavier global. Essayez de lancer rusteys en tant qu'administrateur, surtout si l'application active s'exécute avec des droits élevés."
+        }
+        Text::RdevHelpOther => {
+            "La capture globale du clavier n'est pas prise en charge sur cette plateforme."
+        }
+        Text::InputGroupHelp => {
+            "rusteys lit /dev/input directement. Ajoutez votre utilisateur au groupe input avec `sudo usermod -aG input $USER`, puis déconnectez-vous et reconnectez-vous."
+        }
+        Text::GamepadHelp => {
+            "Vérifiez que la manette est connectée et, sous Linux, que libudev est installé."
+        }
+        Text::RemoteHelp => {
+            "Vérifiez que rien d'autre n'écoute sur l'adresse configurée et que le pare-feu autorise les connexions entrantes."
+        }
+        Text::PermissionsTitle => "Rusteys - Autorisations",
+        Text::PermissionsHeading => "Rusteys a besoin d'une autorisation pour voir vos saisies",
+        Text::PermissionsIntro => {
+            "Activez rusteys (ou le terminal depuis lequel vous l'avez lancé) dans les deux panneaux ci-dessous. La capture démarre automatiquement dès que les deux sont accordées."
+        }
+        Text::Accessibility => "Accessibilité",
+        Text::InputMonitoring => "Surveillance de l'entrée",
+        Text::OpenSettings => "Ouvrir les réglages « {0} »",
+        Text::HistoryTitle => "Rusteys - Historique",
+        Text::Search => "Rechercher",
+        Text::ExactKey => "Touche exacte",
+        Text::OnlyCombos => "Combinaisons uniquement",
+        Text::AllTime => "Tout",
+        Text::LastMinute => "Dernière minute",
+        Text::LastFiveMinutes => "5 dernières minutes",
+        Text::LastFifteenMinutes => "15 dernières minutes",
+        Text::LastHour => "Dernière heure",
+        Text::Entries => "{0} entrées",
+        Text::EntriesOf => "{0} entrées sur {1}",
+        Text::Clear => "Effacer",
+        Text::ConfigProblemsTitle => "Rusteys - Problèmes de configuration",
+        Text::ConfigProblemsIntro => {
+            "Ces réglages de {0} n'ont pas été utilisés et leurs valeurs par défaut s'appliquent. Corrigez-les et enregistrez le fichier pour le recharger."
+        }
+    })
+}
+
+fn spanish(text: Text) -> Option<&'static str> {
+    Some(match text {
+        Text::Paused => "En pausa",
+        Text::RecordingToDisk => "Grabando en disco",
+        Text::RecordingMacro => "Grabando macro: {0}",
+        Text::InputStopped => "Entrada detenida: {0}",
+        Text::FocusHint => "Ventana enfocada; pulsa Esc para salir",
+        Text::CopyRecentKeys => "Copiar teclas recientes",
+        Text::Screenshot => "Captura de pantalla",
+        Text::LockPosition => "Bloquear posición",
+        Text::UnlockPosition => "Desbloquear posición",
+        Text::NothingToCopy => "Aún no hay nada que copiar",
+        Text::Copied => "Copiado al portapapeles",
+        Text::ScreenshotCopiedAndSaved => "Captura copiada y guardada",
+        Text::ScreenshotCopied => "Captura copiada",
+        Text::Saved => "Guardado {0}",
+        Text::SavingReplay => "Guardando repetición",
+        Text::NothingToExport => "Nada que exportar",
+        Text::Profile => "Perfil: {0}",
+        Text::DefaultProfile => "predeterminado",
+        Text::MacroDiscarded => "Macro descartada: {0}",
+        Text::MacroSaved => "Macro guardada: {0} ({1} combinaciones)",
+        Text::NoCheatSheet => "No hay chuleta para {0}",
+        Text::ThisApplication => "esta aplicación",
+        Text::InputProblemTitle => "Rusteys - Problema de entrada",
+        Text::InputUnavailable => "La entrada {0} no está disponible",
+        Text::Dismiss => "Cerrar",
+        Text::RdevHelpMacos => {
+            "macOS solo permite a las apps leer el teclado con permiso. Abre Ajustes del Sistema → Privacidad y seguridad → Accesibilidad (y Monitorización de entrada), activa rusteys o el terminal desde el que lo iniciaste y reinicia rusteys."
+        }
+        Text::RdevHelpLinux => {
+            "La entrada global se lee a través de X11, así que rusteys necesita una sesión X11 (o XWayland). Con la supresión de atajos activada lee /dev/input en su lugar: añade tu usuario al grupo input con `sudo usermod -aG input $USER` y cierra sesión y vuelve a entrar."
+        }
+        Text::RdevHelpWindows => {
+            "Windows rechazó el hook global de teclado. Prueba a ejecutar rusteys como administrador, sobre todo si la aplicación enfocada se ejecuta con privilegios elevados."
+        }
+        Text::RdevHelpOther => {
+            "La captura global del teclado no es compatible con esta plataforma."
+        }
+        Text::InputGroupHelp => {
+            "rusteys lee /dev/input directamente. Añade tu usuario al grupo input con `sudo usermod -aG input $USER` y cierra sesión y vuelve a entrar."
+        }
+        Text::GamepadHelp => {
+            "Comprueba que el mando está conectado y, en Linux, que libudev está instalado."
+        }
+        Text::RemoteHelp => {
+            "Comprueba que nada más esté escuchando en la dirección configurada y que el cortafuegos permita conexiones entrantes."
+        }
+        Text::PermissionsTitle => "Rusteys - Permisos",
+        Text::PermissionsHeading => "Rusteys necesita permiso para ver tu entrada",
+        Text::PermissionsIntro => {
+            "Activa rusteys (o el terminal desde el que lo iniciaste) en los dos paneles de abajo. La captura empieza automáticamente en cuanto ambos estén concedidos."
+        }
+        Text::Accessibility => "Accesibilidad",
+        Text::InputMonitoring => "Monitorización de entrada",
+        Text::OpenSettings => "Abrir ajustes de {0}",
+        Text::HistoryTitle => "Rusteys - Historial",
+        Text::Search => "Buscar",
+        Text::ExactKey => "Tecla exacta",
+        Text::OnlyCombos => "Solo combinaciones",
+        Text::AllTime => "Todo",
+        Text::LastMinute => "Último minuto",
+        Text::LastFiveMinutes => "Últimos 5 minutos",
+        Text::LastFifteenMinutes => "Últimos 15 minutos",
+        Text::LastHour => "Última hora",
+        Text::Entries => "{0} entradas",
+        Text::EntriesOf => "{0} de {1} entradas",
+        Text::Clear => "Borrar",
+        Text::ConfigProblemsTitle => "Rusteys - Problemas de configuración",
+        Text::ConfigProblemsIntro => {
+            "Estos ajustes de {0} no se han usado y se aplican sus valores predeterminados. Corrígelos y guarda el archivo para recargarlo."
+        }
+    })
+}
+
+// European Portuguese; Brazilian locales get it too
+fn portuguese(text: Text) -> Option<&'static str> {
+    Some(match text {
+        Text::Paused => "Em pausa",
+        Text::RecordingToDisk => "A gravar no disco",
+        Text::RecordingMacro => "A gravar macro: {0}",
+        Text::InputStopped => "Entrada parada: {0}",
+        Text::FocusHint => "Janela em foco; prima Esc para sair",
+        Text::CopyRecentKeys => "Copiar teclas recentes",
+        Text::Screenshot => "Captura de ecrã",
+        Text::LockPosition => "Bloquear posição",
+        Text::UnlockPosition => "Desbloquear posição",
+        Text::NothingToCopy => "Ainda não há nada para copiar",
+        Text::Copied => "Copiado para a área de transferência",
+        Text::ScreenshotCopiedAndSaved => "Captura copiada e guardada",
+        Text::ScreenshotCopied => "Captura copiada",
+        Text::Saved => "{0} guardado",
+        Text::SavingReplay => "A guardar repetição",
+        Text::NothingToExport => "Nada para exportar",
+        Text::Profile => "Perfil: {0}",
+        Text::DefaultProfile => "predefinido",
+        Text::MacroDiscarded => "Macro descartada: {0}",
+        Text::MacroSaved => "Macro guardada: {0} ({1} combinações)",
+        Text::NoCheatSheet => "Sem cábula para {0}",
+        Text::ThisApplication => "esta aplicação",
+        Text::InputProblemTitle => "Rusteys - Problema de entrada",
+        Text::InputUnavailable => "A entrada {0} não está disponível",
+        Text::Dismiss => "Fechar",
+        Text::RdevHelpMacos => {
+            "O macOS só deixa as apps lerem o teclado com autorização. Abra Definições do Sistema → Privacidade e segurança → Acessibilidade (e Monitorização de entrada), ative o rusteys ou o terminal a partir do qual o iniciou e reinicie o rusteys."
+        }
+        Text::RdevHelpLinux => {
+            "A entrada global é lida através do X11, por isso o rusteys precisa de uma sessão X11 (ou XWayland). Com a supressão de atalhos ativada, lê /dev/input em vez disso: adicione o seu utilizador ao grupo input com `sudo usermod -aG input $USER`, termine a sessão e volte a entrar."
+        }
+        Text::RdevHelpWindows => {
+            "O Windows recusou o hook global do teclado. Experimente executar o rusteys como administrador, sobretudo se a aplicação em foco estiver a correr com privilégios elevados."
+        }
+        Text::RdevHelpOther => "A captura global do teclado não é suportada nesta plataforma.",
+        Text::InputGroupHelp => {
+            "O rusteys lê /dev/input diretamente. Adicione o seu utilizador ao grupo input com `sudo usermod -aG input $USER`, termine a sessão e volte a entrar."
+        }
+        Text::GamepadHelp => {
+            "Verifique se o comando está ligado e, no Linux, se o libudev está instalado."
+        }
+        Text::RemoteHelp => {
+            "Verifique se nada mais está à escuta no endereço configurado e se a firewall permite ligações de entrada."
+        }
+        Text::PermissionsTitle => "Rusteys - Permissões",
+        Text::PermissionsHeading => "O rusteys precisa de permissão para ver a sua entrada",
+        Text::PermissionsIntro => {
+            "Ative o rusteys (ou o terminal a partir do qual o iniciou) nos dois painéis abaixo. A captura começa automaticamente assim que ambas forem concedidas."
+        }
+        Text::Accessibility => "Acessibilidade",
+        Text::InputMonitoring => "Monitorização de entrada",
+        Text::OpenSettings => "Abrir definições de {0}",
+        Text::HistoryTitle => "Rusteys - Histórico",
+        Text::Search => "Pesquisar",
+        Text::ExactKey => "Tecla exata",
+        Text::OnlyCombos => "Só combinações",
+        Text::AllTime => "Tudo",
+        Text::LastMinute => "Último minuto",
+        Text::LastFiveMinutes => "Últimos 5 minutos",
+        Text::LastFifteenMinutes => "Últimos 15 minutos",
+        Text::LastHour => "Última hora",
+        Text::Entries => "{0} entradas",
+        Text::EntriesOf => "{0} de {1} entradas",
+        Text::Clear => "Limpar",
+        Text::ConfigProblemsTitle => "Rusteys - Problemas de configuração",
+        Text::ConfigProblemsIntro => {
+            "Estas definições em {0} não foram usadas e aplicam-se os valores predefinidos. Corrija-as e guarde o ficheiro para o recarregar."
+        }
+    })
+}
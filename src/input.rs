@@ -0,0 +1,139 @@
+use crate::errors::{BackendError, BackendErrors};
+use crate::remote::RemoteChip;
+use crate::ui::Repaint;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+// A hook that ran at least this long before failing is set up again instead of giving up
+const HOOK_RESTART_AFTER: Duration = Duration::from_secs(5);
+
+// Events delivered by input backends to the shared event handler
+#[derive(Clone)]
+pub enum InputEvent {
+    // Keyboard and mouse events, in rdev's representation whatever the backend
+    Device(rdev::Event),
+    // The same, from a backend that knows which device sent it (evdev)
+    Tagged(rdev::Event, DeviceInfo),
+    // Ready-made chip text from devices without a keyboard/mouse equivalent
+    Chip(String, Source),
+    // Chips captured by another instance on the network
+    Remote(RemoteChip),
+}
+
+// Keyboard or mouse an event came from
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DeviceInfo {
+    pub name: String,
+    // USB-style vendor:product, e.g. "046d:c52b"
+    pub id: String,
+}
+
+impl DeviceInfo {
+    // Config patterns are part of the name or the whole id
+    pub fn matches(&self, pattern: &str) -> bool {
+        self.name.contains(pattern) || self.id.eq_ignore_ascii_case(pattern)
+    }
+}
+
+// Where a chip came from, which decides its row when lanes are split
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Source {
+    // Keys and key-like buttons (gamepads)
+    Keyboard,
+    // Mouse, pen and touch
+    Pointer,
+}
+
+// Returns true when the event triggered a hotkey and may be suppressed
+pub type EventSink = Box<dyn FnMut(InputEvent) -> bool + Send>;
+
+pub trait InputBackend: Send {
+    fn name(&self) -> &'static str;
+
+    // Deliver events to `sink` until the backend stops or fails. Ok means there is nothing more to
+    // deliver, like a replay at its end or layout changes this system can't report; a backend
+    // meant to run until rusteys exits returns an error when it stops.
+    fn run(self: Box<Self>, sink: EventSink) -> Result<(), String>;
+}
+
+// Global keyboard and mouse hook
+pub struct RdevBackend {
+    // Grab events instead of listening, so hotkeys can be swallowed
+    pub suppress_hotkeys: bool,
+}
+
+impl InputBackend for RdevBackend {
+    fn name(&self) -> &'static str {
+        "rdev"
+    }
+
+    fn run(self: Box<Self>, sink: EventSink) -> Result<(), String> {
+        if self.suppress_hotkeys && !cfg!(feature = "grab") {
+            eprintln!(
+                "hotkeys.suppress needs rusteys built with the `grab` feature; hotkeys reach other applications"
+            );
+        }
+        let sink = Arc::new(Mutex::new(sink));
+        // Hooks can drop when input devices or the display server go away; a failure right
+        // at startup is a real error (missing permission, no X11) and is reported
+        loop {
+            let started = Instant::now();
+            let result = self.hook(Arc::clone(&sink));
+            if started.elapsed() < HOOK_RESTART_AFTER {
+                return Err(result.err().unwrap_or_else(|| "hook returned".to_string()));
+            }
+            let reason = result.err().unwrap_or_else(|| "hook returned".to_string());
+            eprintln!("rdev input hook stopped ({}), restarting", reason);
+            thread::sleep(Duration::from_secs(1));
+        }
+    }
+}
+
+impl RdevBackend {
+    fn hook(&self, sink: Arc<Mutex<EventSink>>) -> Result<(), String> {
+        // Grabbing needs rdev's unstable_grab, behind the `grab` feature
+        #[cfg(feature = "grab")]
+        if self.suppress_hotkeys {
+            return rdev::grab(move |event: rdev::Event| {
+                let mut sink = sink.lock();
+                if (*sink)(InputEvent::Device(event.clone())) {
+                    None
+                } else {
+                    Some(event)
+                }
+            })
+            .map_err(|error| format!("{:?}", error));
+        }
+
+        rdev::listen(move |event| {
+            (*sink.lock())(InputEvent::Device(event));
+        })
+        .map_err(|error| format!("{:?}", error))
+    }
+}
+
+// Run a backend on its own thread, collecting its error for the UI
+pub fn spawn(
+    backend: Box<dyn InputBackend>,
+    sink: EventSink,
+    errors: BackendErrors,
+    repaint: Repaint,
+) {
+    thread::spawn(move || {
+        let name = backend.name();
+        let Err(message) = backend.run(sink) else {
+            return;
+        };
+        eprintln!("Error listening to {} input events: {}", name, message);
+        errors.lock().push(BackendError {
+            backend: name,
+            message,
+            dismissed: false,
+        });
+        repaint.request();
+    });
+}
@@ -0,0 +1,112 @@
+use crate::input::{EventSink, InputBackend, InputEvent, Source};
+use std::thread;
+use std::time::Duration;
+
+// Layout switches (Win+Space, Alt+Shift, the menu bar) send no event we can hook, so poll
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+// Shows a chip like "Layout: de-DE" whenever the active keyboard layout changes,
+// so viewers understand why the characters typed next are different
+pub struct InputSourceBackend;
+
+impl InputBackend for InputSourceBackend {
+    fn name(&self) -> &'static str {
+        "keyboard layout"
+    }
+
+    fn run(self: Box<Self>, mut sink: EventSink) -> Result<(), String> {
+        let Some(mut current) = current_layout() else {
+            eprintln!("Keyboard layout changes can't be detected on this system");
+            return Ok(());
+        };
+
+        loop {
+            thread::sleep(POLL_INTERVAL);
+            let Some(layout) = current_layout() else {
+                continue;
+            };
+            if layout != current {
+                sink(InputEvent::Chip(
+                    format!("Layout: {}", layout),
+                    Source::Keyboard,
+                ));
+                current = layout;
+            }
+        }
+    }
+}
+
+// Layout of the foreground window; each window keeps its own
+#[cfg(windows)]
+fn current_layout() -> Option<String> {
+    use winapi::um::winnls::LCIDToLocaleName;
+    use winapi::um::winuser::{GetForegroundWindow, GetKeyboardLayout, GetWindowThreadProcessId};
+
+    const LOCALE_NAME_MAX_LENGTH: usize = 85;
+
+    unsafe {
+        let window = GetForegroundWindow();
+        if window.is_null() {
+            return None;
+        }
+        let thread = GetWindowThreadProcessId(window, std::ptr::null_mut());
+        let layout = GetKeyboardLayout(thread) as usize;
+        if layout == 0 {
+            return None;
+        }
+
+        // The low word is the language; the sort order bits stay 0
+        let language = (layout & 0xFFFF) as u32;
+        let mut name = [0u16; LOCALE_NAME_MAX_LENGTH];
+        let length = LCIDToLocaleName(language, name.as_mut_ptr(), name.len() as i32, 0);
+        if length <= 1 {
+            return None;
+        }
+        Some(String::from_utf16_lossy(&name[..length as usize - 1]))
+    }
+}
+
+// The Text Input Sources API must run on the main thread, which eframe owns, so ask the
+// preferences daemon instead
+#[cfg(target_os = "macos")]
+fn current_layout() -> Option<String> {
+    let output = std::process::Command::new("defaults")
+        .args(["read", "com.apple.HIToolbox", "AppleSelectedInputSources"])
+        .output()
+        .ok()?;
+    let text = String::from_utf8_lossy(&output.stdout);
+
+    // Entries look like `"KeyboardLayout Name" = German;` or `"Input Mode" = "com.apple.inputmethod.Kotoeri.Japanese";`
+    ["\"KeyboardLayout Name\" = ", "\"Input Mode\" = "]
+        .iter()
+        .find_map(|field| {
+            let start = text.find(field)? + field.len();
+            let value = text[start..].split(';').next()?;
+            Some(value.trim().trim_matches('"').to_string())
+        })
+        .map(|name| name.rsplit('.').next().unwrap_or(&name).to_string())
+}
+
+// GNOME lists the input sources most recently used first
+#[cfg(target_os = "linux")]
+fn current_layout() -> Option<String> {
+    let output = std::process::Command::new("gsettings")
+        .args(["get", "org.gnome.desktop.input-sources", "mru-sources"])
+        .output()
+        .ok()?;
+    let text = String::from_utf8_lossy(&output.stdout);
+
+    // [('xkb', 'de'), ('xkb', 'us')]
+    let first = text.split("),").next()?;
+    let layout = first
+        .split(',')
+        .nth(1)?
+        .trim()
+        .trim_matches(|c| "')] ".contains(c));
+    (!layout.is_empty()).then(|| layout.to_uppercase())
+}
+
+#[cfg(not(any(windows, target_os = "macos", target_os = "linux")))]
+fn current_layout() -> Option<String> {
+    None
+}
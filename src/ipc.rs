@@ -0,0 +1,117 @@
+use crate::hotkeys::{Action, Controls};
+use crate::paths;
+use interprocess::local_socket::{
+    GenericFilePath, GenericNamespaced, Listener, ListenerOptions, Name, Stream, prelude::*,
+};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+// A socket file that only this user can reach on unix, and a named pipe with the user's name in it
+// on Windows, so each user gets their own instance
+const SOCKET_NAME: &str = "rusteys.sock";
+
+// Commands are served one at a time, so a client that doesn't send its line or read the reply in
+// this long is dropped; `ctl` waits as long for an instance that has stopped answering
+const TIMEOUT: Duration = Duration::from_secs(2);
+
+// In the per-user runtime folder when there is one, otherwise the user's cache folder
+fn socket_file() -> PathBuf {
+    match std::env::var_os("XDG_RUNTIME_DIR") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir).join(SOCKET_NAME),
+        _ => paths::cache_dir().join(SOCKET_NAME),
+    }
+}
+
+fn socket_name() -> io::Result<Name<'static>> {
+    if cfg!(unix) {
+        socket_file().to_fs_name::<GenericFilePath>()
+    } else {
+        let user = std::env::var("USERNAME").unwrap_or_default();
+        format!("rusteys-{}.sock", user).to_ns_name::<GenericNamespaced>()
+    }
+}
+
+// Holding the socket is what makes this the running instance: binding fails while another one
+// has it, so two starting at once can't both take over
+pub fn bind() -> io::Result<Listener> {
+    let name = socket_name()?;
+    if !cfg!(unix) {
+        return ListenerOptions::new().name(name).create_sync();
+    }
+
+    let file = socket_file();
+    paths::create_parent(&file)?;
+    let listener = match ListenerOptions::new().name(name.clone()).create_sync() {
+        // A socket file left behind by a crashed instance blocks binding, but nothing answers on it
+        Err(error)
+            if error.kind() == io::ErrorKind::AddrInUse
+                && Stream::connect(name.clone()).is_err() =>
+        {
+            std::fs::remove_file(&file)?;
+            ListenerOptions::new().name(name).create_sync()
+        }
+        result => result,
+    }?;
+    paths::restrict(&file)?;
+    Ok(listener)
+}
+
+// Accept one command per line from `rusteys ctl` and answer "ok" or "error: ..."
+pub fn serve(listener: Listener, controls: Arc<Controls>) {
+    thread::spawn(move || listen(listener, &controls));
+}
+
+fn listen(listener: Listener, controls: &Controls) {
+    for connection in listener.incoming() {
+        let connection = match connection {
+            Ok(connection) => connection,
+            Err(error) => {
+                eprintln!("Error accepting control connection: {}", error);
+                continue;
+            }
+        };
+
+        if let Err(error) = connection
+            .set_recv_timeout(Some(TIMEOUT))
+            .and_then(|()| connection.set_send_timeout(Some(TIMEOUT)))
+        {
+            eprintln!("Error accepting control connection: {}", error);
+            continue;
+        }
+        let mut connection = BufReader::new(connection);
+        let mut line = String::new();
+        if connection.read_line(&mut line).is_err() {
+            continue;
+        }
+
+        // `ping` lets a new instance find this one
+        let reply = match Action::parse(&line) {
+            _ if line.trim() == "ping" => "ok".to_string(),
+            Ok(action) => match action.confined(&controls.config.read().screenshot.dir) {
+                Ok(action) => {
+                    controls.apply(&action);
+                    "ok".to_string()
+                }
+                Err(error) => format!("error: {}", error),
+            },
+            Err(error) => format!("error: {}", error),
+        };
+        let _ = writeln!(connection.get_mut(), "{}", reply);
+    }
+}
+
+// Send a command to the running instance and return its reply
+pub fn send(command: &str) -> io::Result<String> {
+    let connection = Stream::connect(socket_name()?)?;
+    connection.set_recv_timeout(Some(TIMEOUT))?;
+    connection.set_send_timeout(Some(TIMEOUT))?;
+    let mut connection = BufReader::new(connection);
+    writeln!(connection.get_mut(), "{}", command)?;
+
+    let mut reply = String::new();
+    connection.read_line(&mut reply)?;
+    Ok(reply.trim().to_string())
+}
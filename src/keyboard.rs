@@ -0,0 +1,372 @@
+use crate::config::ThemeConfig;
+use crate::{key_from_name, key_to_string};
+use eframe::egui;
+use rdev::Key;
+use serde::Deserialize;
+use serde_json::Value;
+use std::path::Path;
+
+// A single key cap, positioned in key units (1.0 = width of a letter key)
+#[derive(Clone)]
+pub struct LayoutKey {
+    pub key: Option<Key>,
+    pub label: String,
+    pub x: f32,
+    pub y: f32,
+    pub w: f32,
+    pub h: f32,
+}
+
+#[derive(Clone)]
+pub struct KeyboardLayout {
+    pub keys: Vec<LayoutKey>,
+    pub width: f32,
+    pub height: f32,
+}
+
+// Native layout file format:
+// { "keys": [ { "key": "KeyA", "label": "A", "x": 1.75, "y": 3.25 } ] }
+#[derive(Deserialize)]
+struct LayoutFile {
+    keys: Vec<LayoutFileKey>,
+}
+
+#[derive(Deserialize)]
+struct LayoutFileKey {
+    key: Option<String>,
+    label: Option<String>,
+    x: f32,
+    y: f32,
+    #[serde(default = "one")]
+    w: f32,
+    #[serde(default = "one")]
+    h: f32,
+}
+
+fn one() -> f32 {
+    1.0
+}
+
+// Standard ANSI rows as (key, width); `None` is an empty gap
+const ANSI_ROWS: &[&[(Option<Key>, f32)]] = &[
+    &[
+        (Some(Key::Escape), 1.0),
+        (None, 1.0),
+        (Some(Key::F1), 1.0),
+        (Some(Key::F2), 1.0),
+        (Some(Key::F3), 1.0),
+        (Some(Key::F4), 1.0),
+        (None, 0.5),
+        (Some(Key::F5), 1.0),
+        (Some(Key::F6), 1.0),
+        (Some(Key::F7), 1.0),
+        (Some(Key::F8), 1.0),
+        (None, 0.5),
+        (Some(Key::F9), 1.0),
+        (Some(Key::F10), 1.0),
+        (Some(Key::F11), 1.0),
+        (Some(Key::F12), 1.0),
+    ],
+    &[
+        (Some(Key::BackQuote), 1.0),
+        (Some(Key::Num1), 1.0),
+        (Some(Key::Num2), 1.0),
+        (Some(Key::Num3), 1.0),
+        (Some(Key::Num4), 1.0),
+        (Some(Key::Num5), 1.0),
+        (Some(Key::Num6), 1.0),
+        (Some(Key::Num7), 1.0),
+        (Some(Key::Num8), 1.0),
+        (Some(Key::Num9), 1.0),
+        (Some(Key::Num0), 1.0),
+        (Some(Key::Minus), 1.0),
+        (Some(Key::Equal), 1.0),
+        (Some(Key::Backspace), 2.0),
+    ],
+    &[
+        (Some(Key::Tab), 1.5),
+        (Some(Key::KeyQ), 1.0),
+        (Some(Key::KeyW), 1.0),
+        (Some(Key::KeyE), 1.0),
+        (Some(Key::KeyR), 1.0),
+        (Some(Key::KeyT), 1.0),
+        (Some(Key::KeyY), 1.0),
+        (Some(Key::KeyU), 1.0),
+        (Some(Key::KeyI), 1.0),
+        (Some(Key::KeyO), 1.0),
+        (Some(Key::KeyP), 1.0),
+        (Some(Key::LeftBracket), 1.0),
+        (Some(Key::RightBracket), 1.0),
+        (Some(Key::BackSlash), 1.5),
+    ],
+    &[
+        (Some(Key::CapsLock), 1.75),
+        (Some(Key::KeyA), 1.0),
+        (Some(Key::KeyS), 1.0),
+        (Some(Key::KeyD), 1.0),
+        (Some(Key::KeyF), 1.0),
+        (Some(Key::KeyG), 1.0),
+        (Some(Key::KeyH), 1.0),
+        (Some(Key::KeyJ), 1.0),
+        (Some(Key::KeyK), 1.0),
+        (Some(Key::KeyL), 1.0),
+        (Some(Key::SemiColon), 1.0),
+        (Some(Key::Quote), 1.0),
+        (Some(Key::Return), 2.25),
+    ],
+    &[
+        (Some(Key::ShiftLeft), 2.25),
+        (Some(Key::KeyZ), 1.0),
+        (Some(Key::KeyX), 1.0),
+        (Some(Key::KeyC), 1.0),
+        (Some(Key::KeyV), 1.0),
+        (Some(Key::KeyB), 1.0),
+        (Some(Key::KeyN), 1.0),
+        (Some(Key::KeyM), 1.0),
+        (Some(Key::Comma), 1.0),
+        (Some(Key::Dot), 1.0),
+        (Some(Key::Slash), 1.0),
+        (Some(Key::ShiftRight), 2.75),
+    ],
+    &[
+        (Some(Key::ControlLeft), 1.25),
+        (Some(Key::MetaLeft), 1.25),
+        (Some(Key::Alt), 1.25),
+        (Some(Key::Space), 6.25),
+        (Some(Key::AltGr), 1.25),
+        (Some(Key::MetaRight), 1.25),
+        (None, 1.25),
+        (Some(Key::ControlRight), 1.25),
+    ],
+];
+
+// Short legends for punctuation keys, which `key_to_string` spells out
+fn legend(key: Key) -> String {
+    match key {
+        Key::BackQuote => "`".to_string(),
+        Key::Minus => "-".to_string(),
+        Key::Equal => "=".to_string(),
+        Key::LeftBracket => "[".to_string(),
+        Key::RightBracket => "]".to_string(),
+        Key::BackSlash => "\\".to_string(),
+        Key::SemiColon => ";".to_string(),
+        Key::Quote => "'".to_string(),
+        Key::Comma => ",".to_string(),
+        Key::Dot => ".".to_string(),
+        Key::Slash => "/".to_string(),
+        _ => key_to_string(key),
+    }
+}
+
+// Resolve a key cap legend, accepting punctuation symbols and the usual
+// spelled-out names found in KLE layouts
+fn key_from_legend(legend_text: &str) -> Option<Key> {
+    let text = legend_text.trim();
+    if text.is_empty() {
+        return None;
+    }
+    let aliases: &[(&str, Key)] = &[
+        ("`", Key::BackQuote),
+        ("-", Key::Minus),
+        ("=", Key::Equal),
+        ("[", Key::LeftBracket),
+        ("]", Key::RightBracket),
+        ("\\", Key::BackSlash),
+        (";", Key::SemiColon),
+        ("'", Key::Quote),
+        (",", Key::Comma),
+        (".", Key::Dot),
+        ("/", Key::Slash),
+        ("caps lock", Key::CapsLock),
+        ("return", Key::Return),
+        ("enter", Key::Return),
+        ("esc", Key::Escape),
+        ("control", Key::ControlLeft),
+        ("ctrl", Key::ControlLeft),
+        ("shift", Key::ShiftLeft),
+        ("alt", Key::Alt),
+        ("altgr", Key::AltGr),
+        ("win", Key::MetaLeft),
+        ("super", Key::MetaLeft),
+        ("cmd", Key::MetaLeft),
+        ("backspace", Key::Backspace),
+        ("space", Key::Space),
+    ];
+    let lower = text.to_lowercase();
+    aliases
+        .iter()
+        .find(|(alias, _)| *alias == lower)
+        .map(|(_, key)| *key)
+        .or_else(|| key_from_name(text))
+}
+
+impl KeyboardLayout {
+    pub fn ansi() -> Self {
+        let mut keys = Vec::new();
+        for (row_index, row) in ANSI_ROWS.iter().enumerate() {
+            // Leave a small gap below the function row
+            let y = row_index as f32 + if row_index > 0 { 0.25 } else { 0.0 };
+            let mut x = 0.0;
+            for &(key, w) in row.iter() {
+                if let Some(key) = key {
+                    keys.push(LayoutKey {
+                        key: Some(key),
+                        label: legend(key),
+                        x,
+                        y,
+                        w,
+                        h: 1.0,
+                    });
+                }
+                x += w;
+            }
+        }
+        Self::from_keys(keys)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let value: Value = serde_json::from_str(&text).map_err(|e| e.to_string())?;
+
+        let keys = match value {
+            // KLE raw data: an array of rows, optionally led by a metadata object
+            Value::Array(rows) => parse_kle(&rows),
+            _ => {
+                let file: LayoutFile = serde_json::from_value(value).map_err(|e| e.to_string())?;
+                file.keys
+                    .into_iter()
+                    .map(|k| {
+                        let key = k.key.as_deref().and_then(key_from_name);
+                        LayoutKey {
+                            label: k.label.unwrap_or_else(|| key.map(legend).unwrap_or_default()),
+                            key,
+                            x: k.x,
+                            y: k.y,
+                            w: k.w,
+                            h: k.h,
+                        }
+                    })
+                    .collect()
+            }
+        };
+
+        if keys.is_empty() {
+            return Err("layout contains no keys".to_string());
+        }
+        Ok(Self::from_keys(keys))
+    }
+
+    fn from_keys(keys: Vec<LayoutKey>) -> Self {
+        let width = keys.iter().map(|k| k.x + k.w).fold(0.0, f32::max);
+        let height = keys.iter().map(|k| k.y + k.h).fold(0.0, f32::max);
+        Self { keys, width, height }
+    }
+}
+
+// Parse keyboard-layout-editor.com raw data. Property objects apply to the
+// key that follows them; rotation is not supported.
+fn parse_kle(rows: &[Value]) -> Vec<LayoutKey> {
+    let mut keys = Vec::new();
+    let mut y = 0.0;
+
+    for row in rows {
+        let Some(items) = row.as_array() else {
+            continue; // Keyboard metadata
+        };
+
+        let mut x = 0.0;
+        let (mut w, mut h) = (1.0, 1.0);
+        for item in items {
+            match item {
+                Value::Object(props) => {
+                    let prop = |name: &str| props.get(name).and_then(Value::as_f64).map(|v| v as f32);
+                    if let Some(v) = prop("x") {
+                        x += v;
+                    }
+                    if let Some(v) = prop("y") {
+                        y += v;
+                    }
+                    if let Some(v) = prop("w") {
+                        w = v;
+                    }
+                    if let Some(v) = prop("h") {
+                        h = v;
+                    }
+                }
+                Value::String(text) => {
+                    // Legends are newline-separated; the bottom one is usually the unshifted key
+                    let key = text.lines().rev().find_map(key_from_legend).or_else(|| {
+                        // Space bars are conventionally left blank
+                        (text.trim().is_empty() && w >= 4.0).then_some(Key::Space)
+                    });
+                    let label = match key {
+                        Some(key) => legend(key),
+                        None => text.lines().last().unwrap_or_default().to_string(),
+                    };
+                    keys.push(LayoutKey { key, label, x, y, w, h });
+                    x += w;
+                    w = 1.0;
+                    h = 1.0;
+                }
+                _ => {}
+            }
+        }
+        y += 1.0;
+    }
+
+    keys
+}
+
+pub fn render_keyboard(
+    ui: &mut egui::Ui,
+    layout: &KeyboardLayout,
+    held_keys: &[Key],
+    theme: &ThemeConfig,
+) {
+    // A layout file with no keys has nothing to draw
+    if layout.width <= 0.0 {
+        return;
+    }
+    let available_width = ui.available_width();
+    let unit = available_width / layout.width;
+    let (rect, _) = ui.allocate_exact_size(
+        egui::vec2(available_width, unit * layout.height),
+        egui::Sense::hover(),
+    );
+    let painter = ui.painter();
+
+    for layout_key in &layout.keys {
+        let key_rect = egui::Rect::from_min_size(
+            rect.min + egui::vec2(layout_key.x * unit, layout_key.y * unit),
+            egui::vec2(layout_key.w * unit, layout_key.h * unit),
+        )
+        .shrink(unit * 0.06);
+
+        let is_held = layout_key
+            .key
+            .is_some_and(|key| held_keys.contains(&key));
+
+        // Match the chip colors: accent when held, gray otherwise
+        let (bg_color, border_color, text_color) = if is_held {
+            let (fill, stroke) = theme.palette.accent();
+            (fill, stroke, egui::Color32::WHITE)
+        } else {
+            theme.idle_colors()
+        };
+
+        painter.rect(
+            key_rect,
+            egui::CornerRadius::same(4),
+            bg_color,
+            egui::Stroke::new(1.0, border_color),
+            egui::StrokeKind::Inside,
+        );
+        painter.text(
+            key_rect.center(),
+            egui::Align2::CENTER_CENTER,
+            &layout_key.label,
+            egui::FontId::proportional(unit * 0.3),
+            text_color,
+        );
+    }
+}
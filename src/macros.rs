@@ -0,0 +1,131 @@
+use crate::config::Combo;
+use crate::paths;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+// A named sequence of combos, e.g. the keys of a refactoring workflow. Only shown, never
+// played back.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Macro {
+    pub name: String,
+    pub combos: Vec<Combo>,
+}
+
+// The library file: `[[macro]]` tables, so it can be edited by hand
+#[derive(Serialize, Deserialize, Default)]
+struct Library {
+    #[serde(default, rename = "macro")]
+    macros: Vec<Macro>,
+}
+
+// Macros in the library file; none if it doesn't exist yet
+pub fn load(path: &Path) -> Result<Vec<Macro>, String> {
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(error) => return Err(format!("{}: {}", path.display(), error)),
+    };
+    let library: Library =
+        toml::from_str(&text).map_err(|e| format!("{}: {}", path.display(), e))?;
+    Ok(library.macros)
+}
+
+// Add a macro to the library file, replacing one with the same name
+pub fn save(path: &Path, saved: Macro) -> Result<(), String> {
+    let mut macros = load(path)?;
+    match macros
+        .iter_mut()
+        .find(|existing| existing.name == saved.name)
+    {
+        Some(existing) => *existing = saved,
+        None => macros.push(saved),
+    }
+    let text = toml::to_string(&Library { macros }).map_err(|e| e.to_string())?;
+    paths::create_parent(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    std::fs::write(path, text).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+// Writes recorded macros to the library file on a thread of its own, so stopping a recording
+// from a hotkey never leaves the input hook waiting on the disk
+pub struct Saver {
+    sender: mpsc::Sender<(PathBuf, Macro)>,
+}
+
+impl Saver {
+    pub fn spawn() -> Self {
+        let (sender, saves) = mpsc::channel::<(PathBuf, Macro)>();
+        thread::spawn(move || {
+            for (path, saved) in saves {
+                let name = saved.name.clone();
+                match save(&path, saved) {
+                    Ok(()) => eprintln!("Saved macro `{}` to {}", name, path.display()),
+                    Err(error) => eprintln!("Error saving macro: {}", error),
+                }
+            }
+        });
+        Self { sender }
+    }
+
+    pub fn save(&self, path: PathBuf, saved: Macro) {
+        let _ = self.sender.send((path, saved));
+    }
+}
+
+// Combos collected between starting and stopping `macro-record`
+pub struct Capture {
+    pub name: String,
+    pub combos: Vec<Combo>,
+}
+
+// Spots library macros in the live combos
+#[derive(Default)]
+pub struct Matcher {
+    // Latest combos, oldest first, as long as the longest macro
+    recent: VecDeque<(Combo, Instant)>,
+}
+
+impl Matcher {
+    // Name of the macro the combos pressed so far end with, if any. Combos more than `max_gap`
+    // apart don't belong to the same sequence.
+    pub fn feed(&mut self, combo: Combo, macros: &[Macro], max_gap: Duration) -> Option<String> {
+        let now = Instant::now();
+        if self
+            .recent
+            .back()
+            .is_some_and(|(_, pressed)| now.duration_since(*pressed) > max_gap)
+        {
+            self.recent.clear();
+        }
+        self.recent.push_back((combo, now));
+        let longest = macros
+            .iter()
+            .map(|known| known.combos.len())
+            .max()
+            .unwrap_or(0);
+        while self.recent.len() > longest {
+            self.recent.pop_front();
+        }
+
+        // The longest match wins, so "Refactor" beats a macro that is just its last two combos
+        let found = macros
+            .iter()
+            .filter(|known| {
+                !known.combos.is_empty()
+                    && known.combos.len() <= self.recent.len()
+                    && self
+                        .recent
+                        .iter()
+                        .skip(self.recent.len() - known.combos.len())
+                        .map(|(combo, _)| combo)
+                        .eq(known.combos.iter())
+            })
+            .max_by_key(|known| known.combos.len())?;
+        // A finished sequence doesn't start the next one
+        self.recent.clear();
+        Some(found.name.clone())
+    }
+}
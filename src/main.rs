@@ -1,17 +1,95 @@
+mod analyze;
+mod autostart;
+mod big_key;
+mod cheat_sheet;
+mod clock;
+mod command;
+mod confetti;
+mod config;
+mod cursor;
+mod environment;
+mod errors;
+#[cfg(target_os = "linux")]
+mod evdev_backend;
+mod event_log;
+mod export;
+mod focus;
+#[cfg(feature = "gamepad")]
+mod gamepad;
+mod gestures;
+mod history;
+mod hotkeys;
+#[cfg(target_os = "linux")]
+mod hotplug;
+mod http;
+mod i18n;
+mod input;
+mod input_source;
+mod ipc;
+mod keyboard;
+mod macros;
+mod osc;
+mod paths;
+#[cfg(target_os = "linux")]
+mod pen;
+mod permissions;
+mod placement;
+#[cfg(feature = "plugins")]
+mod plugins;
+mod power;
+mod remote;
+mod render;
+mod replay;
+mod screenshot;
+#[cfg(feature = "scripting")]
+mod scripting;
+mod session;
+mod timer;
+#[cfg(target_os = "linux")]
+mod touch;
+mod ui;
+mod validation;
+mod webhook;
+mod websocket;
+mod which_key;
+mod window_level;
+mod window_title;
+
+use config::{
+    Anchor, AnimationConfig, ClockMode, Combo, Config, Direction, DisplayMode, KeyDisplay,
+    Modifier, ModifiersConfig, Placement, PowerSaving, Remap, RemoteMode, SharedConfig,
+};
+use cursor::CursorState;
 use eframe::egui;
-use parking_lot::Mutex;
-use rdev::{listen, Event, EventType, Key};
+use errors::BackendErrors;
+use event_log::{EventLog, LogOptions, Rotation};
+use export::ExportOptions;
+use gestures::{DragTracker, ScrollAccumulator};
+use history::History;
+use hotkeys::{Action, Controls};
+use i18n::{Text, tr, tr_args};
+use input::{DeviceInfo, InputBackend, InputEvent, Source};
+use keyboard::KeyboardLayout;
+use parking_lot::{Mutex, RwLock};
+use rdev::{EventType, Key};
+use remote::{RemoteBackend, RemoteChip, RemoteSender};
+use render::RenderOptions;
+use session::ReplayBackend;
 use std::collections::VecDeque;
-use std::sync::Arc;
-use std::thread;
-use std::time::{Duration, Instant};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, mpsc};
+use std::time::{Duration, Instant, SystemTime};
+use ui::{MouseIcon, Repaint};
 
-const MAX_KEYS: usize = 15;
-const KEY_DISPLAY_DURATION: Duration = Duration::from_millis(4000);
-const FADE_OUT_DURATION: Duration = Duration::from_millis(800);
+// Horizontal gap between chips
+const CHIP_SPACING: f32 = 12.0;
+// Shortest time between repaints in power saving mode (~10 fps)
+const LOW_POWER_FRAME: Duration = Duration::from_millis(100);
 
 // Window sizing (as fraction of screen width)
 const WINDOW_WIDTH_FRACTION: f32 = 0.66; // 2/3rds of screen
+// How long a dragged panel has to rest before it snaps into place
+const SNAP_SETTLE: Duration = Duration::from_millis(300);
 const SCREEN_WIDTH: f32 = 1920.0;
 const SCREEN_HEIGHT: f32 = 1080.0;
 
@@ -19,19 +97,128 @@ const SCREEN_HEIGHT: f32 = 1080.0;
 struct KeyPress {
     text: String,
     timestamp: Instant,
+    // Mouse chips draw an icon after the (modifier) text
+    mouse: Option<MouseIcon>,
+    source: Source,
+    // How long the chip shows before fading; depends on its kind, see DurationsConfig
+    display: Duration,
+    // Fill for chips from a device with its own color
+    color: Option<egui::Color32>,
+    // Image drawn before the text, from a matching rule
+    icon: Option<PathBuf>,
 }
 
+// Monitor bounds in physical pixels
 #[derive(Clone)]
 struct MonitorInfo {
     x: f32,
     y: f32,
     width: f32,
     height: f32,
+    // Physical pixels per point (the OS display scaling, 1.5 for 150%)
+    scale: f32,
+}
+
+impl MonitorInfo {
+    // Bounds in points, which is what window sizes and everything egui draws are measured in
+    fn rect(&self) -> egui::Rect {
+        egui::Rect::from_min_size(
+            egui::pos2(self.x / self.scale, self.y / self.scale),
+            egui::vec2(self.width / self.scale, self.height / self.scale),
+        )
+    }
+
+    // Bounds of the overlay window: a panel centered near the bottom of the monitor or where it was
+    // snapped to, or a bar across its full width
+    fn overlay_geometry(
+        &self,
+        config: &Config,
+        layout: &KeyboardLayout,
+        anchor: Option<Anchor>,
+    ) -> egui::Rect {
+        let rect = self.rect();
+        let placement = config.position.placement;
+        let width = match placement {
+            Placement::TopBar | Placement::BottomBar => rect.width(),
+            Placement::Cursor => config.position.cursor_width,
+            Placement::Panel | Placement::Window => rect.width() * WINDOW_WIDTH_FRACTION,
+        };
+        let height = window_height(config, layout, width);
+        let position = match placement {
+            // Until the pointer first moves or a window is focused; lower down the screen, but a tall
+            // panel like the keyboard is moved up to fit
+            Placement::Panel | Placement::Cursor | Placement::Window => {
+                let top = (rect.top() + rect.height() * 0.85)
+                    .min(rect.bottom() - height - config.position.edge_margin);
+                egui::pos2(rect.center().x - width / 2.0, top.max(rect.top()))
+            }
+            Placement::TopBar => rect.left_top(),
+            Placement::BottomBar => egui::pos2(rect.left(), rect.bottom() - height),
+        };
+        let mut overlay = egui::Rect::from_min_size(position, egui::vec2(width, height));
+        if placement == Placement::Panel
+            && let Some(anchor) = anchor
+        {
+            overlay = anchor
+                .align()
+                .align_size_within_rect(overlay.size(), rect.shrink(config.position.edge_margin));
+        }
+        placement::avoid(std::slice::from_ref(self), overlay, &config.position)
+    }
+}
+
+// Changes to the chip queue; input and control threads send them and the UI applies them each frame
+enum ChipUpdate {
+    Push(KeyPress),
+    // Scroll chip, and whether it updates the trailing scroll chip
+    Scroll(KeyPress, bool),
+    Clear,
+}
+
+type ChipSender = mpsc::Sender<ChipUpdate>;
+
+impl ChipUpdate {
+    fn apply(self, key_presses: &mut VecDeque<KeyPress>, lanes: bool, max_chips: usize) {
+        match self {
+            ChipUpdate::Push(key_press) => push_key_press(key_presses, key_press, lanes, max_chips),
+            ChipUpdate::Scroll(key_press, continues) => {
+                push_scroll(key_presses, key_press, continues, lanes, max_chips)
+            }
+            ChipUpdate::Clear => key_presses.clear(),
+        }
+    }
 }
 
 struct KeyDisplayApp {
-    key_presses: Arc<Mutex<VecDeque<KeyPress>>>,
+    // Owned by the UI thread; filled from `chip_updates`
+    key_presses: VecDeque<KeyPress>,
+    chip_updates: mpsc::Receiver<ChipUpdate>,
+    held_keys: Arc<Mutex<Vec<Key>>>,
+    cursor: Arc<Mutex<CursorState>>,
     monitors: Vec<MonitorInfo>,
+    config: SharedConfig,
+    layout: Arc<KeyboardLayout>,
+    // Where `layout` was loaded from, to notice when a profile or reload changes it
+    layout_path: Option<PathBuf>,
+    controls: Arc<Controls>,
+    history: Arc<Mutex<History>>,
+    backend_errors: BackendErrors,
+    // Whether the windows currently show, as opposed to what the hotkey requested
+    shown: bool,
+    battery: power::BatteryCheck,
+    // Whether monitors[0] holds the real monitor rather than the default size
+    monitor_measured: bool,
+    // Bounds last requested for the main overlay window
+    overlay: egui::Rect,
+    // Whether it is still gliding after the pointer
+    following: bool,
+    // Where the main window was last seen, and when it last moved
+    window_position: egui::Pos2,
+    moved_at: Option<Instant>,
+    confetti: confetti::Confetti,
+    // Screenshot requested of the main window, until eframe delivers the image
+    screenshot: Option<screenshot::Request>,
+    replay: replay::Replay,
 }
 
 #[derive(Default, Clone)]
@@ -39,37 +226,135 @@ struct Modifiers {
     ctrl: bool,
     shift: bool,
     alt: bool,
+    // Separate from Alt: on many layouts it types characters rather than starting shortcuts
+    altgr: bool,
     meta: bool,
     // Track if modifier was used in a combination
     ctrl_used: bool,
     shift_used: bool,
     alt_used: bool,
+    altgr_used: bool,
     meta_used: bool,
 }
 
 impl Modifiers {
-    fn format(&self) -> String {
-        let mut parts = Vec::new();
+    fn format(&self, config: &ModifiersConfig) -> String {
+        let parts: Vec<_> = config
+            .order()
+            .into_iter()
+            .filter(|modifier| match modifier {
+                Modifier::Ctrl => self.ctrl,
+                Modifier::Shift => self.shift,
+                Modifier::Alt => self.alt,
+                Modifier::AltGr => self.altgr,
+                Modifier::Meta => self.meta,
+            })
+            .map(Modifier::name)
+            .collect();
+        parts.join(&config.separator)
+    }
+
+    // AltGr held on its own, as when typing @ on a German keyboard
+    fn altgr_only(&self) -> bool {
+        self.altgr && !self.ctrl && !self.shift && !self.alt && !self.meta
+    }
+
+    // Only modifiers that change which character is typed, if any
+    fn typing_only(&self) -> bool {
+        !self.ctrl && !self.alt && !self.meta
+    }
+
+    // `key` pressed with the current modifiers, for the macro being recorded
+    fn combo(&self, key: Key) -> Combo {
+        Combo {
+            ctrl: self.ctrl,
+            shift: self.shift,
+            alt: self.alt,
+            meta: self.meta,
+            key,
+        }
+    }
+
+    // Whether `key` pressed with the current modifiers is exactly `combo`
+    fn matches(&self, combo: &Combo, key: Key) -> bool {
+        combo.key == key
+            && combo.ctrl == self.ctrl
+            && combo.shift == self.shift
+            && combo.alt == self.alt
+            && !self.altgr
+            && combo.meta == self.meta
+    }
+
+    // Track a modifier key going down or up; other keys are ignored
+    fn set(&mut self, key: Key, held: bool) {
+        match key {
+            Key::ControlLeft | Key::ControlRight => self.ctrl = held,
+            Key::ShiftLeft | Key::ShiftRight => self.shift = held,
+            Key::Alt => self.alt = held,
+            Key::AltGr => self.altgr = held,
+            Key::MetaLeft | Key::MetaRight => self.meta = held,
+            _ => {}
+        }
+    }
+
+    // Mark held modifiers as used in a combination
+    fn mark_used(&mut self) {
         if self.ctrl {
-            parts.push("Ctrl");
+            self.ctrl_used = true;
         }
         if self.shift {
-            parts.push("Shift");
+            self.shift_used = true;
         }
         if self.alt {
-            parts.push("Alt");
+            self.alt_used = true;
+        }
+        if self.altgr {
+            self.altgr_used = true;
         }
         if self.meta {
-            parts.push("Win");
+            self.meta_used = true;
         }
-        if parts.is_empty() {
-            String::new()
-        } else {
-            parts.join(" + ")
+    }
+}
+
+// With lanes, each source keeps its own `max_chips` so one can't evict the other
+fn push_key_press(
+    key_presses: &mut VecDeque<KeyPress>,
+    key_press: KeyPress,
+    lanes: bool,
+    max_chips: usize,
+) {
+    let source = key_press.source;
+    key_presses.push_back(key_press);
+
+    // Keep only the most recent keys
+    let in_lane = |key_press: &KeyPress| !lanes || key_press.source == source;
+    while key_presses
+        .iter()
+        .filter(|key_press| in_lane(key_press))
+        .count()
+        > max_chips
+    {
+        if let Some(oldest) = key_presses.iter().position(|key_press| in_lane(key_press)) {
+            key_presses.remove(oldest);
         }
     }
 }
 
+// Update the trailing scroll chip in place, or start a new one
+fn push_scroll(
+    key_presses: &mut VecDeque<KeyPress>,
+    key_press: KeyPress,
+    continues: bool,
+    lanes: bool,
+    max_chips: usize,
+) {
+    match key_presses.back_mut() {
+        Some(last) if continues && last.mouse == Some(MouseIcon::Wheel) => *last = key_press,
+        _ => push_key_press(key_presses, key_press, lanes, max_chips),
+    }
+}
+
 fn key_to_string(key: Key) -> String {
     match key {
         Key::Alt => "Alt".to_string(),
@@ -148,92 +433,705 @@ fn key_to_string(key: Key) -> String {
     }
 }
 
+// Symbol for keys that have a well-known one, used with `key_symbols`
+fn key_symbol(key: Key) -> Option<&'static str> {
+    match key {
+        Key::Return | Key::KpReturn => Some("↵"),
+        Key::Tab => Some("⇥"),
+        Key::Backspace => Some("⌫"),
+        Key::Delete => Some("⌦"),
+        Key::Space => Some("␣"),
+        Key::Escape => Some("⎋"),
+        Key::CapsLock => Some("⇪"),
+        Key::LeftArrow => Some("←"),
+        Key::RightArrow => Some("→"),
+        Key::UpArrow => Some("↑"),
+        Key::DownArrow => Some("↓"),
+        Key::Home => Some("⇱"),
+        Key::End => Some("⇲"),
+        Key::PageUp => Some("⇞"),
+        Key::PageDown => Some("⇟"),
+        _ => None,
+    }
+}
+
+// Diagnostic text for `show_scancodes`: rdev's name (what `labels` expects) and the raw code where known
+fn scancode_text(key: Key) -> String {
+    #[cfg(target_os = "linux")]
+    if let Some(code) = evdev_backend::scancode(key) {
+        return format!("{:?}, evdev {}", key, code);
+    }
+    match key {
+        Key::Unknown(code) => format!("code {} / {:#x}", code, code),
+        _ => format!("{:?}", key),
+    }
+}
+
+// Every named key rdev reports, used to resolve key names from user files
+const ALL_KEYS: &[Key] = &[
+    Key::Alt,
+    Key::AltGr,
+    Key::Backspace,
+    Key::CapsLock,
+    Key::ControlLeft,
+    Key::ControlRight,
+    Key::Delete,
+    Key::DownArrow,
+    Key::End,
+    Key::Escape,
+    Key::F1,
+    Key::F2,
+    Key::F3,
+    Key::F4,
+    Key::F5,
+    Key::F6,
+    Key::F7,
+    Key::F8,
+    Key::F9,
+    Key::F10,
+    Key::F11,
+    Key::F12,
+    Key::Home,
+    Key::LeftArrow,
+    Key::MetaLeft,
+    Key::MetaRight,
+    Key::PageDown,
+    Key::PageUp,
+    Key::Return,
+    Key::RightArrow,
+    Key::ShiftLeft,
+    Key::ShiftRight,
+    Key::Space,
+    Key::Tab,
+    Key::UpArrow,
+    Key::PrintScreen,
+    Key::ScrollLock,
+    Key::Pause,
+    Key::NumLock,
+    Key::BackQuote,
+    Key::Num1,
+    Key::Num2,
+    Key::Num3,
+    Key::Num4,
+    Key::Num5,
+    Key::Num6,
+    Key::Num7,
+    Key::Num8,
+    Key::Num9,
+    Key::Num0,
+    Key::Minus,
+    Key::Equal,
+    Key::KeyQ,
+    Key::KeyW,
+    Key::KeyE,
+    Key::KeyR,
+    Key::KeyT,
+    Key::KeyY,
+    Key::KeyU,
+    Key::KeyI,
+    Key::KeyO,
+    Key::KeyP,
+    Key::LeftBracket,
+    Key::RightBracket,
+    Key::KeyA,
+    Key::KeyS,
+    Key::KeyD,
+    Key::KeyF,
+    Key::KeyG,
+    Key::KeyH,
+    Key::KeyJ,
+    Key::KeyK,
+    Key::KeyL,
+    Key::SemiColon,
+    Key::Quote,
+    Key::BackSlash,
+    Key::IntlBackslash,
+    Key::KeyZ,
+    Key::KeyX,
+    Key::KeyC,
+    Key::KeyV,
+    Key::KeyB,
+    Key::KeyN,
+    Key::KeyM,
+    Key::Comma,
+    Key::Dot,
+    Key::Slash,
+    Key::Insert,
+    Key::KpReturn,
+    Key::KpMinus,
+    Key::KpPlus,
+    Key::KpMultiply,
+    Key::KpDivide,
+    Key::Kp0,
+    Key::Kp1,
+    Key::Kp2,
+    Key::Kp3,
+    Key::Kp4,
+    Key::Kp5,
+    Key::Kp6,
+    Key::Kp7,
+    Key::Kp8,
+    Key::Kp9,
+    Key::KpDelete,
+    Key::Function,
+];
+
+// Look up a key by its rdev name ("KeyA", "CapsLock") or display name ("A", "PgUp")
+fn key_from_name(name: &str) -> Option<Key> {
+    ALL_KEYS.iter().copied().find(|&key| {
+        format!("{:?}", key).eq_ignore_ascii_case(name)
+            || key_to_string(key).eq_ignore_ascii_case(name)
+    })
+}
+
 impl KeyDisplayApp {
-    fn new(key_presses: Arc<Mutex<VecDeque<KeyPress>>>, monitors: Vec<MonitorInfo>) -> Self {
-        Self { 
-            key_presses,
+    fn new(
+        chip_updates: mpsc::Receiver<ChipUpdate>,
+        held_keys: Arc<Mutex<Vec<Key>>>,
+        cursor: Arc<Mutex<CursorState>>,
+        monitors: Vec<MonitorInfo>,
+        config: SharedConfig,
+        layout: Arc<KeyboardLayout>,
+        controls: Arc<Controls>,
+        history: Arc<Mutex<History>>,
+        backend_errors: BackendErrors,
+    ) -> Self {
+        let layout_path = config.read().keyboard_layout.clone();
+        Self {
+            key_presses: VecDeque::new(),
+            chip_updates,
+            held_keys,
+            cursor,
             monitors,
+            config,
+            layout,
+            layout_path,
+            controls,
+            history,
+            backend_errors,
+            shown: true,
+            battery: power::BatteryCheck::default(),
+            monitor_measured: cfg!(target_os = "windows"),
+            overlay: egui::Rect::NOTHING,
+            following: false,
+            window_position: egui::Pos2::ZERO,
+            moved_at: None,
+            confetti: confetti::Confetti::default(),
+            screenshot: None,
+            replay: replay::Replay::default(),
         }
     }
 }
 
 impl eframe::App for KeyDisplayApp {
+    // Fully transparent so only what we paint shows up (the cursor overlay covers whole monitors)
+    fn clear_color(&self, _visuals: &egui::Visuals) -> [f32; 4] {
+        egui::Rgba::TRANSPARENT.to_array()
+    }
+
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         let now = Instant::now();
 
-        // Create secondary viewports for additional monitors (every frame)
-        if self.monitors.len() > 1 {
+        let (lanes, max_chips, power_saving, mut animations, confetti) = {
+            let config = self.config.read();
+            // Picks up a reloaded config or a profile with another language
+            i18n::set(config.language);
+            (
+                config.lanes,
+                config.max_chips(),
+                config.power_saving,
+                config.animations.clone(),
+                config.confetti.clone(),
+            )
+        };
+        let low_power = match power_saving {
+            PowerSaving::On => true,
+            PowerSaving::Off => false,
+            PowerSaving::Auto => self.battery.on_battery(),
+        };
+        animations.enabled &= !low_power;
+
+        // Apply chips queued since the last frame, even while hidden, so the channel never backs up
+        for update in self.chip_updates.try_iter() {
+            update.apply(&mut self.key_presses, lanes, max_chips);
+        }
+        // Without fading, chips disappear as soon as their display time is up
+        let fade = animations.fade();
+        self.key_presses
+            .retain(|key_press| now.duration_since(key_press.timestamp) < key_press.display + fade);
+
+        if let Some(count) = self.controls.take_copy_keys() {
+            let text = self.history.lock().recent_text(count);
+            if text.is_empty() {
+                self.controls.notify(tr(Text::NothingToCopy).to_string());
+            } else {
+                ctx.copy_text(text);
+                self.controls.notify(format!("📋 {}", tr(Text::Copied)));
+            }
+        }
+
+        // Of the frame drawn now, which leaves out the focus outline and drag handle for it
+        let (screenshot_settings, replay_settings) = {
+            let config = self.config.read();
+            (config.screenshot.clone(), config.replay.clone())
+        };
+        let requested = self.controls.take_screenshot();
+        let replay_frame =
+            !screenshot::only_received(ctx) && self.replay.wants_frame(&replay_settings, now);
+        if requested.is_some() || replay_frame {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Screenshot(egui::UserData::default()));
+        }
+        if requested.is_some() {
+            self.screenshot = requested;
+            ctx.request_repaint();
+        }
+        let received = screenshot::received(ctx);
+        if let Some(image) = &received
+            && replay_settings.enabled
+        {
+            self.replay.push(Arc::clone(image), &replay_settings, now);
+        }
+        if let Some(image) = received
+            && let Some(request) = self.screenshot.take()
+        {
+            // An explicit path means a file rather than the clipboard
+            let copy = screenshot_settings.copy && request.path.is_none();
+            if copy {
+                ctx.copy_image((*image).clone());
+            }
+            let path = request.path.or_else(|| {
+                screenshot_settings
+                    .save
+                    .then(|| screenshot::default_path(&screenshot_settings.dir))
+            });
+            let saved = path.and_then(|path| match screenshot::save(&image, &path) {
+                Ok(()) => {
+                    println!("Saved screenshot to {}", path.display());
+                    Some(path)
+                }
+                Err(error) => {
+                    eprintln!("Error saving screenshot to {}: {}", path.display(), error);
+                    None
+                }
+            });
+            match (copy, saved) {
+                (true, Some(_)) => self
+                    .controls
+                    .notify(format!("📷 {}", tr(Text::ScreenshotCopiedAndSaved))),
+                (true, None) => self
+                    .controls
+                    .notify(format!("📷 {}", tr(Text::ScreenshotCopied))),
+                (false, Some(path)) => self
+                    .controls
+                    .notify(format!("📷 {}", tr_args(Text::Saved, &[&path.display()]))),
+                (false, None) => {}
+            }
+        }
+        if let Some(request) = self.controls.take_export_replay() {
+            let path = request.path.unwrap_or_else(|| {
+                replay::default_path(&screenshot_settings.dir, replay_settings.format)
+            });
+            match self.replay.export(path, replay_settings.format) {
+                Ok(()) => self
+                    .controls
+                    .notify(format!("🎞 {}", tr(Text::SavingReplay))),
+                Err(error) => {
+                    eprintln!("Error exporting replay: {}", error);
+                    self.controls.notify(tr(Text::NothingToExport).to_string());
+                }
+            }
+        }
+
+        if self.controls.take_cheat_sheet_toggle() {
+            self.controls.toggle_cheat_sheet();
+        }
+
+        if self.controls.take_confetti() && animations.enabled {
+            self.confetti.burst(confetti.particles, now);
+        }
+        let celebrating = self.confetti.update(now, confetti.duration());
+
+        self.measure_monitor(ctx);
+
+        let visible = self.controls.is_visible();
+        if visible != self.shown {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Visible(visible));
+            self.shown = visible;
+        }
+
+        // Secondary viewports close when they are no longer shown each frame;
+        // showing the overlay again wakes the UI through Controls
+        if !visible {
+            return;
+        }
+
+        // Snapshot for this frame; the file watcher may swap in a new config at any time
+        let mut config = (**self.config.read()).clone();
+        let system_theme = ctx
+            .input(|i| i.raw.system_theme)
+            .unwrap_or(egui::Theme::Dark);
+        if let Some(mode) = *self.controls.theme_mode.lock() {
+            config.theme.mode = mode;
+        }
+        config.theme = config
+            .theme
+            .resolved(self.controls.high_contrast_toggled(), system_theme);
+        config.theme.zoom(self.controls.zoom());
+
+        if config.keyboard_layout != self.layout_path {
+            self.layout = Arc::new(load_layout(config.keyboard_layout.as_deref()));
+            self.layout_path = config.keyboard_layout.clone();
+        }
+
+        self.resize_overlay(ctx, &config);
+
+        permissions::show_onboarding(ctx);
+        errors::show_error_dialog(ctx, &self.backend_errors);
+        validation::show_dialog(ctx, &paths::config_file());
+        history::show_window(ctx, &self.controls, &self.history);
+
+        if config.cursor.enabled() {
+            cursor::show_cursor_overlays(
+                ctx,
+                &self.monitors,
+                &self.cursor,
+                &config.cursor,
+                config.theme.palette,
+            );
+        }
+
+        if let Some(sheet) = self.controls.cheat_sheet.lock().as_ref() {
+            cheat_sheet::show(ctx, &self.monitors[0], sheet, &config.theme);
+        }
+        which_key::show_hints(
+            ctx,
+            &self.monitors[0],
+            self.overlay,
+            &self.controls,
+            &config.theme,
+            Duration::from_millis(config.which_key.timeout_ms),
+        );
+
+        if config.big_key.enabled {
+            big_key::show_big_key(
+                ctx,
+                &self.monitors[0],
+                &self.key_presses,
+                &config.big_key,
+                &animations,
+                now,
+            );
+        }
+
+        // Nothing to frost behind a fully transparent panel
+        let blur = config.theme.blur && config.theme.panel_opacity > 0;
+
+        // Create secondary viewports for additional monitors (every frame); a panel following
+        // the cursor or focus moves between them instead
+        if self.monitors.len() > 1 && !config.position.placement.roams() {
             for (i, monitor) in self.monitors.iter().enumerate().skip(1) {
-                let window_width = monitor.width * WINDOW_WIDTH_FRACTION;
-                let window_x = monitor.x + (monitor.width - window_width) / 2.0;
-                let window_y = monitor.y + monitor.height * 0.85;
-                
+                let overlay =
+                    monitor.overlay_geometry(&config, &self.layout, self.controls.anchor());
+
                 let viewport_id = egui::ViewportId::from_hash_of(format!("monitor_{}", i));
-                let key_presses_clone = Arc::clone(&self.key_presses);
-                
+
                 ctx.show_viewport_immediate(
                     viewport_id,
                     egui::ViewportBuilder::default()
                         .with_title(format!("Key Display Overlay - Monitor {}", i + 1))
-                        .with_inner_size([window_width, 100.0])
-                        .with_position([window_x, window_y])
+                        .with_inner_size(overlay.size())
+                        .with_position(overlay.min)
                         .with_decorations(false)
                         .with_transparent(true)
                         .with_always_on_top()
                         .with_resizable(false)
                         .with_mouse_passthrough(false),
-                    move |ctx, _class| {
-                        render_overlay(ctx, &key_presses_clone, now);
+                    |ctx, _class| {
+                        self.render_overlay(ctx, &config, now, low_power, &animations);
                     },
                 );
+                window_level::raise(&format!("Key Display Overlay - Monitor {}", i + 1));
+                window_level::blur_behind(
+                    &format!("Key Display Overlay - Monitor {}", i + 1),
+                    blur,
+                    panel_radius(&config),
+                );
             }
         }
 
         // Render the main window (first monitor)
-        render_overlay(ctx, &self.key_presses, now);
+        self.render_overlay(ctx, &config, now, low_power, &animations);
+        window_level::raise("Key Display Overlay - Monitor 1");
+        window_level::blur_behind(
+            "Key Display Overlay - Monitor 1",
+            blur,
+            panel_radius(&config),
+        );
+
+        // Render at full rate only while something animates, otherwise sleep until the next
+        // fade starts; new input wakes the UI
+        let cursor_animating = config.cursor.enabled() && {
+            let cursor = self.cursor.lock();
+            !cursor.ripples.is_empty() || !cursor.trail.is_empty()
+        };
+        let deadline = if cursor_animating || celebrating {
+            Some(Duration::ZERO)
+        } else {
+            next_chip_deadline(&self.key_presses, now, &animations)
+        };
+        // The timer ticks every second, then fades like a chip
+        let timer_deadline = {
+            let display = Duration::from_millis(config.durations.other_ms);
+            let mut timer = self.controls.timer.lock();
+            if timer
+                .as_ref()
+                .is_some_and(|timer| timer.is_gone(now, display, &animations))
+            {
+                *timer = None;
+            }
+            timer.as_ref().map(|timer| timer.next_change(now, display))
+        };
+        let clock_deadline = (config.clock.show != ClockMode::Off)
+            .then(|| clock::next_tick(&config.clock, *self.controls.recording_started.lock()));
+        // The focused window is only looked up this often
+        let focus_deadline = (config.window_title.show
+            || config.position.placement == Placement::Window)
+            .then_some(focus::POLL_INTERVAL);
+        let follow_deadline = self.following.then_some(Duration::ZERO);
+        let snap_deadline = self
+            .moved_at
+            .map(|moved_at| SNAP_SETTLE.saturating_sub(moved_at.elapsed()));
+        let deadline = [
+            deadline,
+            timer_deadline,
+            clock_deadline,
+            focus_deadline,
+            follow_deadline,
+            snap_deadline,
+        ]
+        .into_iter()
+        .flatten()
+        .min();
+        match deadline {
+            Some(deadline) if low_power => ctx.request_repaint_after(deadline.max(LOW_POWER_FRAME)),
+            Some(Duration::ZERO) => ctx.request_repaint(),
+            Some(deadline) => ctx.request_repaint_after(deadline),
+            None => {}
+        }
+    }
+}
+
+// Time until a chip next changes appearance: zero while one is scaling or fading, None without chips.
+// Without animations the only change is a chip disappearing.
+fn next_chip_deadline(
+    key_presses: &VecDeque<KeyPress>,
+    now: Instant,
+    animations: &AnimationConfig,
+) -> Option<Duration> {
+    key_presses
+        .iter()
+        .map(|key_press| {
+            let age = now.duration_since(key_press.timestamp);
+            if !animations.enabled {
+                key_press.display.saturating_sub(age)
+            } else if age < animations.pop().max(animations.slide()) || age >= key_press.display {
+                Duration::ZERO
+            } else {
+                key_press.display - age
+            }
+        })
+        .min()
+}
+
+// Opacity of a chip of the given age, fading out after its display duration
+fn chip_alpha(age: Duration, display: Duration, animations: &AnimationConfig) -> u8 {
+    let fade = animations.fade();
+    if age > display && !fade.is_zero() {
+        let fade_progress = ((age - display).as_secs_f32() / fade.as_secs_f32()).min(1.0);
+        ((1.0 - animations.fade_easing.apply(fade_progress)).clamp(0.0, 1.0) * 255.0) as u8
+    } else {
+        255
     }
 }
 
-fn render_overlay(ctx: &egui::Context, key_presses: &Arc<Mutex<VecDeque<KeyPress>>>, now: Instant) {
-    // Check if window is focused and Escape is pressed
-    if ctx.input(|i| i.focused && i.key_pressed(egui::Key::Escape)) {
-        ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+// Size of a chip of the given age: enlarged when new, settling back to 1
+fn chip_scale(age: Duration, animations: &AnimationConfig) -> f32 {
+    let pop = animations.pop();
+    if age >= pop {
+        return 1.0;
     }
+    let pop_progress = age.as_secs_f32() / pop.as_secs_f32();
+    1.0 + (1.0 - animations.pop_easing.apply(pop_progress)) * animations.pop_scale
+}
 
-    // Clean up old key presses
-    {
-        let mut kp = key_presses.lock();
-        kp.retain(|kp| {
-            now.duration_since(kp.timestamp) < KEY_DISPLAY_DURATION + FADE_OUT_DURATION
-        });
+// Bars sit flush against the screen edge, so only the floating panel is rounded
+fn panel_radius(config: &Config) -> u8 {
+    if config.position.placement.is_bar() {
+        0
+    } else {
+        config.theme.panel_radius
+    }
+}
+
+// Keyboard mode needs room for the whole layout at the given width
+fn window_height(config: &Config, layout: &KeyboardLayout, window_width: f32) -> f32 {
+    // Text line plus padding; chip rows get a little headroom for the pop-in
+    let row = config.theme.font_size * 1.3 + 2.0 * config.theme.chip_padding_y as f32;
+    match config.mode {
+        DisplayMode::Chips if config.lanes => 66.0 + 2.0 * row,
+        DisplayMode::Chips => 48.0 + row,
+        DisplayMode::Keyboard if layout.width > 0.0 => {
+            40.0 + (window_width - 40.0) * layout.height / layout.width
+        }
+        // A layout without keys; the panel keeps the size of a row of chips
+        DisplayMode::Keyboard => 48.0 + row,
     }
+}
 
-    let key_presses_clone = key_presses.lock().clone();
-
-    // Request repaint for smooth animations
-    ctx.request_repaint();
-
-    // Check if window is focused for visual indication
-    let is_focused = ctx.input(|i| i.focused);
-
-    egui::CentralPanel::default()
-            .frame(
-                egui::Frame::new()
-                    .fill(egui::Color32::from_rgba_unmultiplied(
-                        35,
-                        35,
-                        35,
-                        127, // Constant 50% opacity (255 * 0.5)
-                    ))
-                    .inner_margin(egui::Margin::same(20))
-                    .corner_radius(egui::CornerRadius::same(12))
-                    .shadow(egui::epaint::Shadow {
-                        offset: [0, 4],
-                        blur: 16,
-                        spread: 0,
-                        color: egui::Color32::from_rgba_unmultiplied(0, 0, 0, 115), // Constant shadow opacity
-                    }),
+impl KeyDisplayApp {
+    // Only Windows lists monitors before a window exists; elsewhere take the real size and
+    // scale from the first frame that reports them and move the overlay into place
+    fn measure_monitor(&mut self, ctx: &egui::Context) {
+        if self.monitor_measured {
+            return;
+        }
+        let (size, scale) = ctx.input(|i| {
+            (
+                i.viewport().monitor_size,
+                i.viewport().native_pixels_per_point,
             )
+        });
+        let (Some(size), Some(scale)) = (size, scale) else {
+            return;
+        };
+
+        self.monitors[0] = MonitorInfo {
+            x: 0.0,
+            y: 0.0,
+            width: size.x * scale,
+            height: size.y * scale,
+            scale,
+        };
+        // The window moves into place in `resize_overlay`
+        self.monitor_measured = true;
+    }
+
+    // Snap the panel to the nearest anchor once the user stops dragging it. Dragging is left to the
+    // OS, so a drop shows as the window resting somewhere other than where it was put.
+    fn snap_dragged(&mut self, ctx: &egui::Context, config: &Config) {
+        if config.position.placement != Placement::Panel || config.position.snap_distance <= 0.0 {
+            return;
+        }
+        let Some(window) = ctx.input(|i| i.viewport().outer_rect) else {
+            return;
+        };
+        if window.min.distance(self.window_position) > 0.5 {
+            self.window_position = window.min;
+            self.moved_at = Some(Instant::now());
+            return;
+        }
+        if self
+            .moved_at
+            .is_none_or(|moved_at| moved_at.elapsed() < SNAP_SETTLE)
+        {
+            return;
+        }
+        self.moved_at = None;
+        if window.min.distance(self.overlay.min) <= 0.5 {
+            return;
+        }
+        if let Some(anchor) = placement::snap(self.monitors[0].rect(), window, &config.position) {
+            self.controls.set_anchor(anchor);
+            // Moved there even when it is the anchor the panel already had
+            self.overlay = egui::Rect::NOTHING;
+        }
+    }
+
+    // Font size, mode, placement and monitor changes all change the main window's bounds. A window
+    // dragged elsewhere stays there until they do.
+    fn resize_overlay(&mut self, ctx: &egui::Context, config: &Config) {
+        self.snap_dragged(ctx, config);
+        let mut overlay =
+            self.monitors[0].overlay_geometry(config, &self.layout, self.controls.anchor());
+        let target = match config.position.placement {
+            Placement::Cursor => self.cursor.lock().position.map(|pointer| {
+                let position = placement::follow_cursor(
+                    &self.monitors,
+                    pointer,
+                    overlay.size(),
+                    &config.position,
+                );
+                egui::Rect::from_min_size(position, overlay.size())
+            }),
+            Placement::Window => focus::focused_bounds().map(|window| {
+                placement::on_window(&self.monitors, window, &config.position, |width| {
+                    window_height(config, &self.layout, width)
+                })
+            }),
+            _ => None,
+        }
+        .map(|target| placement::avoid(&self.monitors, target, &config.position));
+        self.following = false;
+        if let Some(target) = target {
+            // Glide there rather than jump
+            let dt = ctx.input(|i| i.stable_dt);
+            let position =
+                placement::smooth(self.overlay.min, target.min, config.position.smoothing, dt);
+            self.following = position != target.min;
+            overlay = egui::Rect::from_min_size(position, target.size());
+        }
+        if overlay.size() != self.overlay.size() {
+            ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(overlay.size()));
+        }
+        if overlay.min != self.overlay.min {
+            ctx.send_viewport_cmd(egui::ViewportCommand::OuterPosition(overlay.min));
+        }
+        self.overlay = overlay;
+
+        let reserved =
+            (config.position.exclusive_zone && config.position.placement.is_bar()).then(|| {
+                let monitor = &self.monitors[0];
+                let top = config.position.placement == Placement::TopBar;
+                let bar = overlay.height() * monitor.scale;
+                // Struts count from the edges of the whole screen, not the monitor's
+                let thickness = if top {
+                    monitor.y + bar
+                } else {
+                    let screen_bottom = self
+                        .monitors
+                        .iter()
+                        .map(|monitor| monitor.y + monitor.height)
+                        .fold(0.0, f32::max);
+                    screen_bottom - (monitor.y + monitor.height) + bar
+                };
+                window_level::Strut {
+                    top,
+                    thickness: thickness.round().max(0.0) as u32,
+                    start: monitor.x as u32,
+                    end: ((monitor.x + monitor.width) as u32).saturating_sub(1),
+                }
+            });
+        window_level::reserve_space("Key Display Overlay - Monitor 1", reserved);
+    }
+
+    fn render_overlay(
+        &self,
+        ctx: &egui::Context,
+        config: &Config,
+        now: Instant,
+        low_power: bool,
+        animations: &AnimationConfig,
+    ) {
+        // Check if window is focused and Escape is pressed
+        if ctx.input(|i| i.focused && i.key_pressed(egui::Key::Escape)) {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+        }
+
+        // Check if window is focused for visual indication
+        let is_focused = ctx.input(|i| i.focused) && self.screenshot.is_none();
+
+        egui::CentralPanel::default()
+            .frame(panel_frame(config, low_power))
             .show(ctx, |ui| {
                 // Draw focus indicator outline on top of everything
                 if is_focused {
@@ -245,248 +1143,1657 @@ fn render_overlay(ctx: &egui::Context, key_presses: &Arc<Mutex<VecDeque<KeyPress
                         egui::StrokeKind::Outside,
                     );
                 }
-                
-                // Make the window draggable by detecting drag on the background
-                let response = ui.interact(ui.max_rect(), ui.id().with("drag_overlay"), egui::Sense::drag());
+
+                // Mark the overlay while capture is paused
+                if self.controls.is_paused() {
+                    ui.painter().text(
+                        ui.max_rect().right_top(),
+                        egui::Align2::RIGHT_TOP,
+                        format!("⏸ {}", tr(Text::Paused)),
+                        egui::FontId::proportional(12.0),
+                        egui::Color32::from_rgb(255, 200, 100),
+                    );
+                }
+
+                // Never write keystrokes to disk without saying so
+                if self.history.lock().is_persistent() || self.controls.event_log.is_some() {
+                    ui.painter().text(
+                        ui.max_rect().right_bottom(),
+                        egui::Align2::RIGHT_BOTTOM,
+                        format!("● {}", tr(Text::RecordingToDisk)),
+                        egui::FontId::proportional(12.0),
+                        egui::Color32::from_rgb(255, 110, 110),
+                    );
+                }
+
+                let recording_started = *self.controls.recording_started.lock();
+                if let Some(text) = clock::text(&config.clock, recording_started) {
+                    clock::paint(ui, &config.clock, &config.theme, &text);
+                }
+
+                if let Some(text) = window_title::text(&config.window_title) {
+                    window_title::paint(ui, &config.window_title, &config.theme, &text);
+                }
+
+                if let Some(timer) = self.controls.timer.lock().as_ref() {
+                    let alpha = timer.alpha(
+                        now,
+                        Duration::from_millis(config.durations.other_ms),
+                        animations,
+                    );
+                    timer::paint(ui, timer, &config.theme, alpha, now);
+                }
+
+                if let Some(name) = self.controls.capturing() {
+                    ui.painter().text(
+                        ui.max_rect().left_bottom(),
+                        egui::Align2::LEFT_BOTTOM,
+                        format!("⏺ {}", tr_args(Text::RecordingMacro, &[&name])),
+                        egui::FontId::proportional(12.0),
+                        egui::Color32::from_rgb(255, 110, 110),
+                    );
+                }
+
+                // Keep showing that some input isn't captured, even after the error dialog is dismissed
+                let stopped = errors::stopped_backends(&self.backend_errors);
+                if !stopped.is_empty() {
+                    ui.painter().text(
+                        ui.max_rect().left_top(),
+                        egui::Align2::LEFT_TOP,
+                        format!("⚠ {}", tr_args(Text::InputStopped, &[&stopped.join(", ")])),
+                        egui::FontId::proportional(12.0),
+                        egui::Color32::from_rgb(255, 110, 110),
+                    );
+                }
+
+                // Make the window draggable by detecting drag on the background, unless its
+                // position is locked
+                let locked = config.position.locked != self.controls.position_lock_toggled();
+                let sense = if locked {
+                    egui::Sense::click()
+                } else {
+                    egui::Sense::click_and_drag()
+                };
+                let response = ui.interact(ui.max_rect(), ui.id().with("drag_overlay"), sense);
                 if response.dragged() {
                     ctx.send_viewport_cmd(egui::ViewportCommand::StartDrag);
                 }
-                
+                response.context_menu(|ui| {
+                    if ui.button(tr(Text::CopyRecentKeys)).clicked() {
+                        self.controls.apply(&Action::CopyKeys(None));
+                        ui.close();
+                    }
+                    if ui.button(tr(Text::Screenshot)).clicked() {
+                        self.controls.apply(&Action::Screenshot(None));
+                        ui.close();
+                    }
+                    if ui
+                        .button(tr(if locked {
+                            Text::UnlockPosition
+                        } else {
+                            Text::LockPosition
+                        }))
+                        .clicked()
+                    {
+                        self.controls.apply(&Action::LockPosition);
+                        ui.close();
+                    }
+                });
+
+                // The handle stays out of recordings until the pointer is over the panel
+                if ui.rect_contains_pointer(ui.max_rect()) && self.screenshot.is_none() {
+                    let (_, _, color) = config.theme.idle_colors();
+                    let (grip, lock) = ui::drag_handle(ui, locked, color);
+                    if grip.dragged() {
+                        ctx.send_viewport_cmd(egui::ViewportCommand::StartDrag);
+                    }
+                    if lock.clicked() {
+                        self.controls.apply(&Action::LockPosition);
+                    }
+                }
+
                 // Always allocate minimum height to prevent layout shift when empty
                 ui.set_min_height(60.0);
-                
+
                 // Show focus message when window is focused (centered)
                 if is_focused {
                     ui.vertical_centered(|ui| {
                         ui.add_space(8.0);
                         ui.label(
-                            egui::RichText::new("Window in focus; press Esc to exit")
+                            egui::RichText::new(tr(Text::FocusHint))
                                 .size(14.0)
-                                .color(egui::Color32::from_rgb(180, 200, 255))
+                                .color(egui::Color32::from_rgb(180, 200, 255)),
                         );
                         ui.add_space(8.0);
                     });
                 }
-                
-                // Use a scroll area that auto-scrolls to the right (most recent keys)
-                egui::ScrollArea::horizontal()
-                    .auto_shrink(false)
-                    .stick_to_right(true)
-                    .show(ui, |ui| {
-                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                            ui.spacing_mut().item_spacing.x = 12.0;
-
-                            for (index, key_press) in key_presses_clone.iter().rev().enumerate() {
-                                let age = now.duration_since(key_press.timestamp);
-                                let is_most_recent = index == 0; // First item in reversed iteration is most recent
-                        
-                        // Calculate fade for individual keys
-                        let alpha = if age > KEY_DISPLAY_DURATION {
-                            let fade_progress = (age.as_millis() - KEY_DISPLAY_DURATION.as_millis())
-                                as f32
-                                / FADE_OUT_DURATION.as_millis() as f32;
-                            ((1.0 - fade_progress.min(1.0)) * 255.0) as u8
-                        } else {
-                            255
-                        };
 
-                        // Scale effect: slightly larger when first pressed
-                        let scale = if age.as_millis() < 100 {
-                            1.0 + (1.0 - age.as_millis() as f32 / 100.0) * 0.2
-                        } else {
-                            1.0
-                        };
-
-                        let font_size = 28.0 * scale;
-
-                        // Different colors for most recent key vs older keys
-                        let (bg_color, border_color, text_color) = if is_most_recent {
-                            // Most recent key: darker blue accent color
-                            (
-                                egui::Color32::from_rgba_unmultiplied(70, 110, 200, alpha), // Darker blue background
-                                egui::Color32::from_rgba_unmultiplied(100, 140, 220, alpha), // Medium blue border
-                                egui::Color32::from_rgba_unmultiplied(255, 255, 255, alpha), // White text
-                            )
-                        } else {
-                            // Older keys: normal gray color
-                            (
-                                egui::Color32::from_rgba_unmultiplied(70, 75, 85, alpha),
-                                egui::Color32::from_rgba_unmultiplied(140, 150, 170, alpha),
-                                egui::Color32::from_rgba_unmultiplied(255, 255, 255, alpha),
-                            )
-                        };
-
-                        // Use a Frame to draw background behind the text
-                        egui::Frame::new()
-                            .fill(bg_color)
-                            .corner_radius(egui::CornerRadius::same(6))
-                            .stroke(egui::Stroke::new(1.5, border_color))
-                            .inner_margin(egui::Margin::symmetric(12, 8))
-                            .show(ui, |ui| {
-                                // Draw key text on top of the frame
-                                let text = egui::RichText::new(&key_press.text)
-                                    .size(font_size)
-                                    .strong()
-                                    .color(text_color);
-
-                                ui.add(egui::Label::new(text).wrap_mode(egui::TextWrapMode::Extend));
-                            });
-                            }
-                        });
-                    });
+                if config.mode == DisplayMode::Keyboard {
+                    let held_keys = self.held_keys.lock().clone();
+                    keyboard::render_keyboard(ui, &self.layout, &held_keys, &config.theme);
+                    return;
+                }
+
+                render_chips(ui, &self.key_presses, now, animations, config);
             });
+
+        self.confetti.paint(ctx, now, config.confetti.duration());
+    }
 }
 
-fn main() -> Result<(), eframe::Error> {
-    let key_presses = Arc::new(Mutex::new(VecDeque::new()));
-    let modifiers = Arc::new(Mutex::new(Modifiers::default()));
-
-    let key_presses_clone = Arc::clone(&key_presses);
-    let modifiers_clone = Arc::clone(&modifiers);
-
-    // Spawn keyboard listener thread
-    thread::spawn(move || {
-        let callback = move |event: Event| {
-            match event.event_type {
-                EventType::KeyPress(key) => {
-                    let mut mods = modifiers_clone.lock();
-
-                    // Update modifier state
-                    match key {
-                        Key::ControlLeft | Key::ControlRight => mods.ctrl = true,
-                        Key::ShiftLeft | Key::ShiftRight => mods.shift = true,
-                        Key::Alt | Key::AltGr => mods.alt = true,
-                        Key::MetaLeft | Key::MetaRight => mods.meta = true,
-                        _ => {}
-                    }
+// Background of the overlay, which the chips and everything else are drawn on
+fn panel_frame(config: &Config, low_power: bool) -> egui::Frame {
+    egui::Frame::new()
+        .fill(config.theme.panel_fill())
+        .inner_margin(egui::Margin::same(20))
+        .corner_radius(egui::CornerRadius::same(panel_radius(config)))
+        // A fully transparent panel leaves only the chips, without a shadow around nothing
+        .shadow(if low_power || config.theme.panel_opacity == 0 {
+            egui::epaint::Shadow::NONE
+        } else {
+            config.theme.panel_shadow.shadow()
+        })
+}
 
-                    // Build the key combination string
-                    let mut key_text = String::new();
-                    
-                    // Add modifiers if present and this isn't a modifier key itself
-                    let is_modifier = matches!(
-                        key,
-                        Key::ControlLeft
-                            | Key::ControlRight
-                            | Key::ShiftLeft
-                            | Key::ShiftRight
-                            | Key::Alt
-                            | Key::AltGr
-                            | Key::MetaLeft
-                            | Key::MetaRight
-                    );
+// The chip rows, one or two with lanes, filling the rest of the panel
+fn render_chips(
+    ui: &mut egui::Ui,
+    key_presses: &VecDeque<KeyPress>,
+    now: Instant,
+    animations: &AnimationConfig,
+    config: &Config,
+) {
+    if config.lanes {
+        // Mouse activity gets its own row so it can't push shortcuts off-screen
+        let (keys, pointer): (Vec<_>, Vec<_>) = key_presses
+            .iter()
+            .partition(|key_press| key_press.source == Source::Keyboard);
+        let lane_height = ui.available_height() / 2.0;
+        render_chip_row(
+            ui,
+            "keyboard_lane",
+            &keys,
+            lane_height,
+            now,
+            animations,
+            config,
+        );
+        render_chip_row(
+            ui,
+            "pointer_lane",
+            &pointer,
+            lane_height,
+            now,
+            animations,
+            config,
+        );
+    } else {
+        let chips: Vec<_> = key_presses.iter().collect();
+        render_chip_row(
+            ui,
+            "chips",
+            &chips,
+            ui.available_height(),
+            now,
+            animations,
+            config,
+        );
+    }
+}
 
-                    if !is_modifier {
-                        let mod_str = mods.format();
-                        if !mod_str.is_empty() {
-                            key_text.push_str(&mod_str);
-                            key_text.push_str(" + ");
-                            
-                            // Mark modifiers as used in combination
-                            if mods.ctrl { mods.ctrl_used = true; }
-                            if mods.shift { mods.shift_used = true; }
-                            if mods.alt { mods.alt_used = true; }
-                            if mods.meta { mods.meta_used = true; }
-                        }
+// One horizontal row of chips, newest on the right
+fn render_chip_row(
+    ui: &mut egui::Ui,
+    id_salt: &str,
+    chips: &[&KeyPress],
+    height: f32,
+    now: Instant,
+    animations: &AnimationConfig,
+    config: &Config,
+) {
+    let theme = &config.theme;
+    // Mirrored for right-to-left locales: newest on the left
+    let rtl = theme.direction == Direction::Rtl;
+    ui.allocate_ui(egui::vec2(ui.available_width(), height), |ui| {
+        // Use a scroll area that auto-scrolls to the right (most recent keys)
+        egui::ScrollArea::horizontal()
+            .id_salt(id_salt)
+            .auto_shrink(false)
+            .stick_to_right(!rtl)
+            .show(ui, |ui| {
+                // Slide-in: the whole row starts shifted right by the newest chip and glides into place.
+                // Only painting moves, so the scroll area doesn't follow the chip off the edge.
+                let newest_width_id = egui::Id::new(id_salt).with("newest_width");
+                let slide_offset = chips.last().map_or(0.0, |newest| {
+                    let slide = animations.slide();
+                    let age = now.duration_since(newest.timestamp);
+                    if age >= slide {
+                        return 0.0;
                     }
+                    // Measured last frame, so a brand-new chip briefly uses its predecessor's width
+                    let width = ui
+                        .data(|data| data.get_temp::<f32>(newest_width_id))
+                        .unwrap_or(0.0);
+                    let progress = animations
+                        .slide_easing
+                        .apply(age.as_secs_f32() / slide.as_secs_f32());
+                    let offset = (1.0 - progress) * (width + CHIP_SPACING);
+                    if rtl { -offset } else { offset }
+                });
+                let layout = if rtl {
+                    egui::Layout::left_to_right(egui::Align::Center)
+                } else {
+                    egui::Layout::right_to_left(egui::Align::Center)
+                };
+                let transform =
+                    egui::emath::TSTransform::from_translation(egui::vec2(slide_offset, 0.0));
 
-                    key_text.push_str(&key_to_string(key));
+                ui.with_visual_transform(transform, |ui| {
+                    ui.with_layout(layout, |ui| {
+                        ui.spacing_mut().item_spacing.x = CHIP_SPACING;
 
-                    let mut key_presses = key_presses_clone.lock();
-                    
-                    // Only add non-modifier keys
-                    if !is_modifier {
-                        key_presses.push_back(KeyPress {
-                            text: key_text,
-                            timestamp: Instant::now(),
-                        });
+                        for (index, key_press) in chips.iter().rev().enumerate() {
+                            let age = now.duration_since(key_press.timestamp);
+                            // First item in reversed iteration is most recent
+                            let is_most_recent = index == 0;
 
-                        // Keep only the most recent keys
-                        while key_presses.len() > MAX_KEYS {
-                            key_presses.pop_front();
-                        }
-                    }
-                }
-                EventType::KeyRelease(key) => {
-                    let mut mods = modifiers_clone.lock();
-                    
-                    // Check if this modifier was used in a combination
-                    let is_modifier = matches!(
-                        key,
-                        Key::ControlLeft
-                            | Key::ControlRight
-                            | Key::ShiftLeft
-                            | Key::ShiftRight
-                            | Key::Alt
-                            | Key::AltGr
-                            | Key::MetaLeft
-                            | Key::MetaRight
-                    );
-                    
-                    // Show standalone modifier only if it wasn't used in combination
-                    if is_modifier {
-                        let was_used = match key {
-                            Key::ControlLeft | Key::ControlRight => mods.ctrl_used,
-                            Key::ShiftLeft | Key::ShiftRight => mods.shift_used,
-                            Key::Alt | Key::AltGr => mods.alt_used,
-                            Key::MetaLeft | Key::MetaRight => mods.meta_used,
-                            _ => false,
-                        };
-                        
-                        if !was_used {
-                            // Show standalone modifier key
-                            let mut key_presses = key_presses_clone.lock();
-                            key_presses.push_back(KeyPress {
-                                text: key_to_string(key),
-                                timestamp: Instant::now(),
-                            });
-                            
-                            while key_presses.len() > MAX_KEYS {
-                                key_presses.pop_front();
+                            // Calculate fade for individual keys
+                            let alpha = chip_alpha(age, key_press.display, animations);
+
+                            // Scale effect: slightly larger when first pressed
+                            let scale = chip_scale(age, animations);
+
+                            let font_size = theme.font_size * scale;
+
+                            // Different colors for most recent key vs older keys
+                            let (bg_color, border_color, text_color) = if theme.high_contrast {
+                                // Inverted newest chip, black and white otherwise
+                                let (fill, text) = if is_most_recent {
+                                    (egui::Color32::WHITE, egui::Color32::BLACK)
+                                } else {
+                                    (egui::Color32::BLACK, egui::Color32::WHITE)
+                                };
+                                let fade = alpha as f32 / 255.0;
+                                (
+                                    fill.gamma_multiply(fade),
+                                    egui::Color32::WHITE.gamma_multiply(fade),
+                                    text.gamma_multiply(fade),
+                                )
+                            } else if is_most_recent {
+                                // Most recent key: the palette's accent color (blue by default)
+                                let (fill, stroke) = theme.palette.accent();
+                                (
+                                    fill.gamma_multiply(alpha as f32 / 255.0),
+                                    stroke.gamma_multiply(alpha as f32 / 255.0),
+                                    egui::Color32::from_rgba_unmultiplied(255, 255, 255, alpha), // White text
+                                )
+                            } else {
+                                // Older keys: gray, light or dark with the theme
+                                let (fill, stroke, text) = theme.idle_colors();
+                                let fade = alpha as f32 / 255.0;
+                                (
+                                    fill.gamma_multiply(fade),
+                                    stroke.gamma_multiply(fade),
+                                    text.gamma_multiply(fade),
+                                )
+                            };
+
+                            // Chips from a device with its own color keep it, older ones a little dimmer
+                            let bg_color = match key_press.color {
+                                Some(color) if !theme.high_contrast => {
+                                    let fade = alpha as f32 / 255.0;
+                                    color.gamma_multiply(if is_most_recent {
+                                        fade
+                                    } else {
+                                        fade * 0.7
+                                    })
+                                }
+                                _ => bg_color,
+                            };
+
+                            let border_color =
+                                theme.chip_stroke_color.map_or(border_color, |color| {
+                                    color.0.gamma_multiply(alpha as f32 / 255.0)
+                                });
+                            let mut shadow = theme.chip_shadow.shadow();
+                            shadow.color = shadow.color.gamma_multiply(alpha as f32 / 255.0);
+
+                            // Use a Frame to draw background behind the text
+                            let chip = egui::Frame::new()
+                                .fill(bg_color)
+                                .corner_radius(egui::CornerRadius::same(theme.chip_radius))
+                                .stroke(egui::Stroke::new(theme.chip_stroke_width, border_color))
+                                .inner_margin(egui::Margin::symmetric(
+                                    theme.chip_padding_x,
+                                    theme.chip_padding_y,
+                                ))
+                                .shadow(shadow)
+                                .show(ui, |ui| {
+                                    // The mouse icon always goes after the text, so it comes
+                                    // first when laid out right to left
+                                    ui.spacing_mut().item_spacing.x = 6.0;
+                                    if !rtl && let Some(icon) = key_press.mouse {
+                                        ui::mouse_icon(ui, icon, font_size, text_color);
+                                    }
+
+                                    if let Some(path) = &key_press.icon {
+                                        let image =
+                                            egui::Image::new(format!("file://{}", path.display()))
+                                                .fit_to_exact_size(egui::vec2(
+                                                    font_size, font_size,
+                                                ));
+                                        ui.add(image);
+                                    }
+
+                                    // Draw key text on top of the frame
+                                    if !key_press.text.is_empty() {
+                                        chip_text(
+                                            ui,
+                                            &key_press.text,
+                                            config,
+                                            font_size,
+                                            text_color,
+                                        );
+                                    }
+
+                                    if rtl && let Some(icon) = key_press.mouse {
+                                        ui::mouse_icon(ui, icon, font_size, text_color);
+                                    }
+                                });
+                            if is_most_recent {
+                                ui.data_mut(|data| {
+                                    data.insert_temp(newest_width_id, chip.response.rect.width())
+                                });
                             }
                         }
-                    }
-                    
-                    // Update modifier state on release
-                    match key {
-                        Key::ControlLeft | Key::ControlRight => {
-                            mods.ctrl = false;
-                            mods.ctrl_used = false;
-                        }
-                        Key::ShiftLeft | Key::ShiftRight => {
-                            mods.shift = false;
-                            mods.shift_used = false;
-                        }
-                        Key::Alt | Key::AltGr => {
-                            mods.alt = false;
-                            mods.alt_used = false;
-                        }
-                        Key::MetaLeft | Key::MetaRight => {
-                            mods.meta = false;
-                            mods.meta_used = false;
-                        }
-                        _ => {}
-                    }
+                    })
+                });
+            });
+    });
+}
+
+enum ChipPiece<'a> {
+    Text(String),
+    Icon(&'a Path),
+}
+
+// Chip text, with the names of keys that have an icon drawn as the icon
+fn chip_text(ui: &mut egui::Ui, text: &str, config: &Config, font_size: f32, color: egui::Color32) {
+    let label = |ui: &mut egui::Ui, text: &str| {
+        // egui lays text out left to right, so Hebrew or Arabic labels are reordered first
+        let text = egui::RichText::new(ui::visual_order(text))
+            .size(font_size)
+            .strong()
+            .color(color);
+        ui.add(egui::Label::new(text).wrap_mode(egui::TextWrapMode::Extend));
+    };
+    if config.icons.is_empty() {
+        label(ui, text);
+        return;
+    }
+
+    // Runs of text between icons, separators included
+    let separator = config.modifiers.separator.as_str();
+    let parts: Vec<&str> = if separator.is_empty() {
+        vec![text]
+    } else {
+        text.split(separator).collect()
+    };
+    let mut pieces = Vec::new();
+    let mut pending = String::new();
+    for (index, part) in parts.into_iter().enumerate() {
+        if index > 0 {
+            pending.push_str(separator);
+        }
+        match config.icon(part) {
+            Some(path) => {
+                if !pending.is_empty() {
+                    pieces.push(ChipPiece::Text(std::mem::take(&mut pending)));
                 }
-                _ => {}
+                pieces.push(ChipPiece::Icon(path));
             }
-        };
+            None => pending.push_str(part),
+        }
+    }
+    if !pending.is_empty() {
+        pieces.push(ChipPiece::Text(pending));
+    }
 
-        if let Err(error) = listen(callback) {
-            eprintln!("Error listening to keyboard events: {:?}", error);
+    ui.scope(|ui| {
+        ui.spacing_mut().item_spacing.x = 0.0;
+        // Pieces are added in reading order, whichever way the row is laid out
+        let ordered: Vec<_> = if ui.layout().prefer_right_to_left() {
+            pieces.iter().rev().collect()
+        } else {
+            pieces.iter().collect()
+        };
+        for piece in ordered {
+            match piece {
+                ChipPiece::Text(text) => label(ui, text),
+                // egui's loaders read and rasterize each file once and cache the texture
+                ChipPiece::Icon(path) => {
+                    let image = egui::Image::new(format!("file://{}", path.display()))
+                        .fit_to_exact_size(egui::vec2(font_size, font_size))
+                        .tint(color);
+                    ui.add(image);
+                }
+            }
         }
     });
+}
+
+// Turns raw input events into chips, shared by all input backends
+struct EventHandler {
+    chips: ChipSender,
+    held_keys: Arc<Mutex<Vec<Key>>>,
+    cursor: Arc<Mutex<CursorState>>,
+    config: SharedConfig,
+    controls: Arc<Controls>,
+    history: Arc<Mutex<History>>,
+    osc: Option<osc::OscSender>,
+    remote: Option<RemoteSender>,
+    websocket: Option<websocket::WebSocketServer>,
+    webhooks: webhook::WebhookSender,
+    // Print chips as JSON lines (headless mode)
+    stdout: bool,
+    // `--chips-file`: the same lines appended to a file
+    chip_file: Option<Mutex<std::io::LineWriter<std::fs::File>>>,
+    modifiers: Modifiers,
+    // Remapped tap-hold key currently down
+    tap_hold: Option<TapHold>,
+    macro_matcher: macros::Matcher,
+    // The config's `script`
+    script: Option<ChipScript>,
+    plugins: Option<PluginHost>,
+    // Continuations shown after prefix combos
+    which_key: which_key::Bindings,
+    drag_tracker: DragTracker,
+    scroll_accumulator: ScrollAccumulator,
+}
+
+struct TapHold {
+    key: Key,
+    pressed_at: Instant,
+    // Another key was pressed meanwhile, so this is a hold rather than a tap
+    interrupted: bool,
+}
+
+impl EventHandler {
+    // Add a chip unless capture is paused or the script hides it
+    fn push(&self, mut key_press: KeyPress) {
+        if self.controls.is_paused() {
+            return;
+        }
+        if let Some(script) = &self.script
+            && !script(&mut key_press, &self.modifiers)
+        {
+            return;
+        }
+        self.publish(&key_press.text, key_press.mouse, key_press.source, false);
+        let _ = self.chips.send(ChipUpdate::Push(key_press));
+    }
+
+    // Add or update the trailing scroll chip unless capture is paused
+    fn scroll(&self, text: String, continues: bool) {
+        if !self.controls.is_paused() {
+            self.publish(&text, Some(MouseIcon::Wheel), Source::Pointer, continues);
+            let key_press = KeyPress {
+                text,
+                timestamp: Instant::now(),
+                mouse: Some(MouseIcon::Wheel),
+                source: Source::Pointer,
+                display: Duration::from_millis(self.config.read().durations.mouse_ms),
+                color: None,
+                icon: None,
+            };
+            let _ = self.chips.send(ChipUpdate::Scroll(key_press, continues));
+        }
+    }
+
+    // Mirror a chip to the history and the configured outputs
+    fn publish(&self, text: &str, mouse: Option<MouseIcon>, source: Source, continues: bool) {
+        if !continues {
+            self.history.lock().record(text);
+            if let Some(osc) = &self.osc {
+                osc.send(text);
+            }
+        }
+        let chip = RemoteChip {
+            text: text.to_string(),
+            mouse,
+            source,
+            continues,
+        };
+        if self.stdout || self.chip_file.is_some() || self.websocket.is_some() {
+            let payload = serde_json::to_string(&chip).unwrap_or_default();
+            if self.stdout {
+                println!("{}", payload);
+            }
+            if let Some(file) = &self.chip_file {
+                use std::io::Write;
+                if let Err(error) = writeln!(file.lock(), "{}", payload) {
+                    eprintln!("Error writing chips file: {}", error);
+                }
+            }
+            if let Some(websocket) = &self.websocket {
+                websocket.send(&payload);
+            }
+        }
+        if let Some(remote) = &self.remote {
+            remote.send(chip);
+        }
+    }
+
+    // Returns true when the event was a hotkey
+    fn handle(&mut self, event: InputEvent) -> bool {
+        let config = self.config.read().clone();
+
+        // Nothing goes to the event log or plugins while paused or from ignored devices
+        let withheld = self.controls.is_paused()
+            || matches!(&event, InputEvent::Tagged(_, device) if config.ignores_device(device));
+
+        // Only what could be shown is logged, and no pointer motion, which would drown out
+        // everything else
+        if let Some(event_log) = &self.controls.event_log
+            && !withheld
+        {
+            let motion = match &event {
+                InputEvent::Device(event) | InputEvent::Tagged(event, _) => {
+                    matches!(event.event_type, EventType::MouseMove { .. })
+                }
+                InputEvent::Chip(..) | InputEvent::Remote(_) => false,
+            };
+            if !motion {
+                event_log.lock().write(event.clone().into());
+            }
+        }
+
+        let outputs = match self.plugins.as_mut() {
+            Some(plugins) if !withheld => plugins(&event),
+            _ => Vec::new(),
+        };
+        for output in outputs {
+            match output {
+                PluginOutput::Chip(text) => self.push(KeyPress {
+                    text,
+                    timestamp: Instant::now(),
+                    mouse: None,
+                    source: Source::Keyboard,
+                    display: Duration::from_millis(config.durations.other_ms),
+                    color: None,
+                    icon: None,
+                }),
+                PluginOutput::Action(action) => match Action::parse(&action)
+                    .and_then(|action| action.confined(&config.screenshot.dir))
+                {
+                    Ok(action) => self.controls.apply(&action),
+                    Err(error) => eprintln!("Error in plugin action: {}", error),
+                },
+            }
+        }
+
+        let (mut event, device) = match event {
+            InputEvent::Device(event) => (event, None),
+            // Ignored devices don't even count toward held modifiers
+            InputEvent::Tagged(_, device) if config.ignores_device(&device) => return false,
+            InputEvent::Tagged(event, device) => (event, Some(device)),
+            InputEvent::Chip(text, source) => {
+                self.push(KeyPress {
+                    text,
+                    timestamp: Instant::now(),
+                    mouse: None,
+                    source,
+                    display: Duration::from_millis(config.durations.other_ms),
+                    color: None,
+                    icon: None,
+                });
+                return false;
+            }
+            InputEvent::Remote(chip) => {
+                if chip.mouse == Some(MouseIcon::Wheel) {
+                    self.scroll(chip.text, chip.continues);
+                } else {
+                    // Only the text tells what kind of chip the sender saw
+                    let display = if chip.mouse.is_some() {
+                        config.durations.mouse_ms
+                    } else if chip.text.contains(" + ")
+                        || (!config.modifiers.separator.trim().is_empty()
+                            && chip.text.contains(&config.modifiers.separator))
+                    {
+                        config.durations.shortcut_ms
+                    } else {
+                        config.durations.key_ms
+                    };
+                    self.push(KeyPress {
+                        text: chip.text,
+                        timestamp: Instant::now(),
+                        mouse: chip.mouse,
+                        source: chip.source,
+                        display: Duration::from_millis(display),
+                        color: None,
+                        icon: None,
+                    });
+                }
+                return false;
+            }
+        };
+
+        let (event_type, tap) = self.remap(&config, event.event_type);
+        event.event_type = event_type;
+        let suppress = self.handle_device(&config, event, device.as_ref());
+
+        // A tap-hold key released before it was used shows as its tap key
+        if let Some(tap) = tap {
+            for event_type in [EventType::KeyPress(tap), EventType::KeyRelease(tap)] {
+                let event = rdev::Event {
+                    time: SystemTime::now(),
+                    name: None,
+                    event_type,
+                };
+                self.handle_device(&config, event, device.as_ref());
+            }
+        }
+        suppress
+    }
+
+    // Apply `[remap]`, so keys changed by kanata, kmonad or Karabiner show as what they do. Also
+    // returns the tap key to show when a tap-hold key was tapped.
+    fn remap(&mut self, config: &Config, event_type: EventType) -> (EventType, Option<Key>) {
+        match event_type {
+            EventType::KeyPress(key) => {
+                match self.tap_hold.as_mut() {
+                    // Autorepeat of the tap-hold key itself
+                    Some(state) if state.key == key => {}
+                    // Anything typed while it's down makes it a hold
+                    Some(state) => state.interrupted = true,
+                    None => {}
+                }
+                match config.remapped(key) {
+                    Some(Remap::Key(target)) => (EventType::KeyPress(target.0), None),
+                    Some(Remap::TapHold { hold, .. }) => {
+                        if self.tap_hold.as_ref().is_none_or(|state| state.key != key) {
+                            self.tap_hold = Some(TapHold {
+                                key,
+                                pressed_at: Instant::now(),
+                                interrupted: false,
+                            });
+                        }
+                        (EventType::KeyPress(hold.0), None)
+                    }
+                    None => (event_type, None),
+                }
+            }
+            EventType::KeyRelease(key) => match config.remapped(key) {
+                Some(Remap::Key(target)) => (EventType::KeyRelease(target.0), None),
+                Some(remap @ Remap::TapHold { tap, hold, .. }) => {
+                    let tapped =
+                        self.tap_hold
+                            .take_if(|state| state.key == key)
+                            .is_some_and(|state| {
+                                !state.interrupted
+                                    && state.pressed_at.elapsed() < remap.tap_timeout()
+                            });
+                    if tapped {
+                        // The hold key wasn't really pressed, so its release isn't a chip of its own
+                        self.modifiers.mark_used();
+                    }
+                    (EventType::KeyRelease(hold.0), tapped.then_some(tap.0))
+                }
+                None => (event_type, None),
+            },
+            _ => (event_type, None),
+        }
+    }
+
+    fn handle_device(
+        &mut self,
+        config: &Config,
+        event: rdev::Event,
+        device: Option<&DeviceInfo>,
+    ) -> bool {
+        // Which keyboard or mouse this came from, when the backend knows and the config cares
+        let (device_label, device_color) = config.device_style(device);
+        let tag = |text: String| match &device_label {
+            Some(label) => format!("{}: {}", label, text),
+            None => text,
+        };
+
+        match event.event_type {
+            EventType::KeyPress(key) => {
+                // Held keys autorepeat
+                let repeat = {
+                    let mut held_keys = self.held_keys.lock();
+                    let repeat = held_keys.contains(&key);
+                    if !repeat {
+                        held_keys.push(key);
+                    }
+                    repeat
+                };
+
+                let mods = &mut self.modifiers;
+
+                // Update modifier state
+                mods.set(key, true);
+
+                // Webhooks and commands fire for hotkeys too, e.g. a "clip that" key bound in OBS.
+                // Webhooks send the combo off the machine, so not while capture is paused
+                if !repeat {
+                    if !self.controls.is_paused() {
+                        for webhook in config
+                            .webhooks
+                            .iter()
+                            .filter(|webhook| mods.matches(&webhook.combo, key))
+                        {
+                            self.webhooks.trigger(webhook);
+                        }
+                    }
+                    for command in config
+                        .commands
+                        .iter()
+                        .filter(|command| mods.matches(&command.combo, key))
+                    {
+                        command::run(command);
+                    }
+                }
+
+                // Hotkeys act immediately and are not shown
+                if let Some(action) = config.hotkeys.action(|combo| mods.matches(combo, key)) {
+                    mods.mark_used();
+                    self.controls.apply(&action);
+                    return true;
+                }
+
+                // Celebrated combos are still shown, with a burst of confetti on top
+                if config
+                    .confetti
+                    .combos
+                    .iter()
+                    .any(|combo| mods.matches(combo, key))
+                {
+                    self.controls.apply(&Action::Confetti);
+                }
+
+                // Build the key combination string
+                let mut key_text = String::new();
+
+                // Add modifiers if present and this isn't a modifier key itself
+                let is_modifier = matches!(
+                    key,
+                    Key::ControlLeft
+                        | Key::ControlRight
+                        | Key::ShiftLeft
+                        | Key::ShiftRight
+                        | Key::Alt
+                        | Key::AltGr
+                        | Key::MetaLeft
+                        | Key::MetaRight
+                );
+
+                let combo = mods.combo(key);
+                let mut performed_macro = None;
+                if !is_modifier {
+                    self.controls.capture_combo(combo);
+                    which_key::pressed(&self.controls, &self.which_key, &combo);
+                    if config.macros.show_names {
+                        let max_gap = Duration::from_millis(config.macros.max_gap_ms);
+                        performed_macro =
+                            self.macro_matcher
+                                .feed(combo, &self.controls.macros.lock(), max_gap);
+                    }
+                }
+
+                // With `altgr_characters`, AltGr+Q reads as the "@" it typed; with
+                // `key_display = "character"` so does any key typed without Ctrl, Alt or Meta,
+                // e.g. the "j" Dvorak types on the C key
+                let typed_character = !is_modifier
+                    && config.key_display == KeyDisplay::Character
+                    && mods.typing_only();
+                let produced_character = event.name.as_deref().filter(|name| {
+                    ((config.modifiers.altgr_characters && mods.altgr_only()) || typed_character)
+                        && !name.trim().is_empty()
+                        && !name.chars().any(char::is_control)
+                });
+
+                let mut display = config.durations.key_ms;
+                let mut is_shortcut = false;
+                if let Some(character) = produced_character {
+                    mods.mark_used();
+                    key_text.push_str(character);
+                } else if !is_modifier {
+                    let mod_str = mods.format(&config.modifiers);
+                    if !mod_str.is_empty() {
+                        display = config.durations.shortcut_ms;
+                        is_shortcut = true;
+                        key_text.push_str(&mod_str);
+                        key_text.push_str(&config.modifiers.separator);
+
+                        // Mark modifiers as used in combination
+                        mods.mark_used();
+                    }
+                }
+
+                if produced_character.is_none() {
+                    key_text.push_str(&config.label(key));
+                }
+                if config.show_scancodes {
+                    key_text = format!("{} [{}]", key_text, scancode_text(key));
+                }
+
+                // Only add non-modifier keys
+                if !is_modifier && (is_shortcut || !config.shortcuts_only) {
+                    // `[[rules]]` can relabel, color or add an icon to the chip; the focused
+                    // application is only looked up once there are rules
+                    let app = if config.rules.is_empty() {
+                        None
+                    } else {
+                        focus::focused_app()
+                    };
+                    let rule = config.rule(&combo, app.as_deref());
+                    if let Some(label) = rule.and_then(|rule| rule.label.as_ref()) {
+                        key_text = label.replace("{keys}", &key_text);
+                    }
+                    self.push(KeyPress {
+                        text: tag(key_text),
+                        timestamp: Instant::now(),
+                        mouse: None,
+                        source: Source::Keyboard,
+                        display: Duration::from_millis(display),
+                        color: rule
+                            .and_then(|rule| rule.color)
+                            .map(|color| color.0)
+                            .or(device_color),
+                        icon: rule.and_then(|rule| rule.icon.clone()),
+                    });
+                }
+                // Named after the combo that completed it
+                if let Some(name) = performed_macro {
+                    self.push(KeyPress {
+                        text: format!("⟶ {}", name),
+                        timestamp: Instant::now(),
+                        mouse: None,
+                        source: Source::Keyboard,
+                        display: Duration::from_millis(config.durations.other_ms),
+                        color: None,
+                        icon: None,
+                    });
+                }
+            }
+            EventType::KeyRelease(key) => {
+                self.held_keys.lock().retain(|&held| held != key);
+
+                // Check if this modifier was used in a combination
+                let is_modifier = matches!(
+                    key,
+                    Key::ControlLeft
+                        | Key::ControlRight
+                        | Key::ShiftLeft
+                        | Key::ShiftRight
+                        | Key::Alt
+                        | Key::AltGr
+                        | Key::MetaLeft
+                        | Key::MetaRight
+                );
+
+                // Show standalone modifier only if it wasn't used in combination
+                if is_modifier {
+                    let mods = &self.modifiers;
+                    let was_used = match key {
+                        Key::ControlLeft | Key::ControlRight => mods.ctrl_used,
+                        Key::ShiftLeft | Key::ShiftRight => mods.shift_used,
+                        Key::Alt => mods.alt_used,
+                        Key::AltGr => mods.altgr_used,
+                        Key::MetaLeft | Key::MetaRight => mods.meta_used,
+                        _ => false,
+                    };
+
+                    if !was_used && !config.shortcuts_only {
+                        // Show standalone modifier key
+                        self.push(KeyPress {
+                            text: tag(if config.show_scancodes {
+                                format!("{} [{}]", config.label(key), scancode_text(key))
+                            } else {
+                                config.label(key)
+                            }),
+                            timestamp: Instant::now(),
+                            mouse: None,
+                            source: Source::Keyboard,
+                            display: Duration::from_millis(config.durations.modifier_ms),
+                            color: device_color,
+                            icon: None,
+                        });
+                    }
+                }
+
+                // Update modifier state on release
+                let mods = &mut self.modifiers;
+                match key {
+                    Key::ControlLeft | Key::ControlRight => {
+                        mods.ctrl = false;
+                        mods.ctrl_used = false;
+                    }
+                    Key::ShiftLeft | Key::ShiftRight => {
+                        mods.shift = false;
+                        mods.shift_used = false;
+                    }
+                    Key::Alt => {
+                        mods.alt = false;
+                        mods.alt_used = false;
+                    }
+                    Key::AltGr => {
+                        mods.altgr = false;
+                        mods.altgr_used = false;
+                    }
+                    Key::MetaLeft | Key::MetaRight => {
+                        mods.meta = false;
+                        mods.meta_used = false;
+                    }
+                    _ => {}
+                }
+            }
+            EventType::MouseMove { x, y } => {
+                let cursor_config = &config.cursor;
+                let trail_length = if cursor_config.trail {
+                    cursor_config.trail_length
+                } else {
+                    0
+                };
+                self.cursor
+                    .lock()
+                    .move_to(egui::pos2(x as f32, y as f32), trail_length);
+            }
+            EventType::ButtonPress(button) => {
+                self.cursor.lock().click(button, &config.cursor);
+
+                if config.show_mouse {
+                    let mods = &mut self.modifiers;
+
+                    // Modifier-clicks read as "Ctrl +" followed by the mouse icon
+                    let mut prefix = mods.format(&config.modifiers);
+                    if !prefix.is_empty() {
+                        prefix.push_str(config.modifiers.separator.trim_end());
+                        mods.mark_used();
+                    }
+
+                    // The chip is added on release, once we know whether this was a drag
+                    let position = self.cursor.lock().position;
+                    self.drag_tracker.press(button, position, prefix);
+                }
+            }
+            EventType::ButtonRelease(button) if config.show_mouse => {
+                let position = self.cursor.lock().position;
+                if let Some(text) = self.drag_tracker.release(button, position) {
+                    self.push(KeyPress {
+                        text: tag(text),
+                        timestamp: Instant::now(),
+                        mouse: Some(MouseIcon::from_button(button)),
+                        source: Source::Pointer,
+                        display: Duration::from_millis(config.durations.mouse_ms),
+                        color: device_color,
+                        icon: None,
+                    });
+                }
+            }
+            EventType::Wheel { delta_x, delta_y }
+                if config.show_mouse && (delta_x != 0 || delta_y != 0) =>
+            {
+                let mods = &mut self.modifiers;
+                let mut prefix = mods.format(&config.modifiers);
+                if !prefix.is_empty() {
+                    prefix.push_str(config.modifiers.separator.trim_end());
+                    mods.mark_used();
+                }
+
+                let (text, continues) = self.scroll_accumulator.scroll(&prefix, delta_x, delta_y);
+                self.scroll(text, continues);
+            }
+            EventType::ButtonRelease(_) | EventType::Wheel { .. } => {}
+        }
+        false
+    }
+}
+
+// Controller backend, when enabled in the config
+#[cfg(feature = "gamepad")]
+fn gamepad_backend(config: &Config) -> Option<Box<dyn InputBackend>> {
+    if !config.gamepad.enabled {
+        return None;
+    }
+    Some(Box::new(gamepad::GamepadBackend::new(
+        config.gamepad.clone(),
+    )))
+}
+
+#[cfg(not(feature = "gamepad"))]
+fn gamepad_backend(config: &Config) -> Option<Box<dyn InputBackend>> {
+    if config.gamepad.enabled {
+        eprintln!("Gamepad support was not compiled in (enable the `gamepad` feature)");
+    }
+    None
+}
+
+// Restyles a chip in place; false hides it
+type ChipScript = Box<dyn Fn(&mut KeyPress, &Modifiers) -> bool + Send>;
+
+#[cfg(feature = "scripting")]
+fn chip_script(config: &Config) -> Option<ChipScript> {
+    let path = config.script.as_ref()?;
+    match scripting::Script::load(path) {
+        Ok(script) => Some(Box::new(move |key_press, modifiers| {
+            script.apply(key_press, modifiers)
+        })),
+        Err(error) => {
+            eprintln!("Error loading chip script: {}", error);
+            None
+        }
+    }
+}
+
+#[cfg(not(feature = "scripting"))]
+fn chip_script(config: &Config) -> Option<ChipScript> {
+    if config.script.is_some() {
+        eprintln!("Chip scripts were not compiled in (enable the `scripting` feature)");
+    }
+    None
+}
+
+// What plugins ask for in response to an input event
+enum PluginOutput {
+    Chip(String),
+    Action(String),
+}
+
+type PluginHost = Box<dyn FnMut(&InputEvent) -> Vec<PluginOutput> + Send>;
+
+#[cfg(feature = "plugins")]
+fn plugin_host(config: &Config) -> Option<PluginHost> {
+    if config.plugins.is_empty() {
+        return None;
+    }
+    let mut plugins = plugins::Plugins::load(&config.plugins);
+    Some(Box::new(move |event| plugins.send(event)))
+}
+
+#[cfg(not(feature = "plugins"))]
+fn plugin_host(config: &Config) -> Option<PluginHost> {
+    if !config.plugins.is_empty() {
+        eprintln!("Plugins were not compiled in (enable the `plugins` feature)");
+    }
+    None
+}
+
+// Stylus backend, where the platform exposes tablet events
+#[cfg(target_os = "linux")]
+fn pen_backend(config: &Config) -> Option<Box<dyn InputBackend>> {
+    if !config.pen {
+        return None;
+    }
+    Some(Box::new(pen::PenBackend))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn pen_backend(config: &Config) -> Option<Box<dyn InputBackend>> {
+    if config.pen {
+        eprintln!("Pen/tablet input is only supported on Linux");
+    }
+    None
+}
+
+// Touch gesture backend, where the platform exposes multitouch events
+#[cfg(target_os = "linux")]
+fn touch_backend(config: &Config) -> Option<Box<dyn InputBackend>> {
+    if !config.touch {
+        return None;
+    }
+    Some(Box::new(touch::TouchBackend))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn touch_backend(config: &Config) -> Option<Box<dyn InputBackend>> {
+    if config.touch {
+        eprintln!("Touch input is only supported on Linux");
+    }
+    None
+}
+
+// Keyboard and mouse read straight from /dev/input, for when the rdev hook sees nothing (Wayland)
+#[cfg(target_os = "linux")]
+fn evdev_backend() -> Result<Box<dyn InputBackend>, String> {
+    Ok(Box::new(evdev_backend::EvdevBackend))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn evdev_backend() -> Result<Box<dyn InputBackend>, String> {
+    Err("The evdev backend is only supported on Linux".to_string())
+}
+
+// Keyboard and mouse backend chosen with `--backend`
+fn device_backend(config: &Config, backend: Option<&str>) -> Result<Box<dyn InputBackend>, String> {
+    match backend.unwrap_or("rdev") {
+        "rdev" => Ok(permissions::wrap(Box::new(input::RdevBackend {
+            suppress_hotkeys: config.hotkeys.suppress,
+        }))),
+        "evdev" => {
+            if config.hotkeys.suppress {
+                eprintln!(
+                    "hotkeys.suppress is not supported by the evdev backend; hotkeys reach other applications"
+                );
+            }
+            if config.cursor.enabled() {
+                eprintln!(
+                    "The evdev backend doesn't report the pointer position, so the [cursor] halo, ripples and trail stay hidden"
+                );
+            }
+            evdev_backend()
+        }
+        other => Err(format!(
+            "unknown input backend `{}`, expected rdev or evdev",
+            other
+        )),
+    }
+}
+
+// A remote display shows only the sending machine's chips
+fn input_backends(
+    config: &Config,
+    backend: Option<&str>,
+) -> Result<Vec<Box<dyn InputBackend>>, String> {
+    if config.remote.mode == RemoteMode::Receive {
+        return Ok(vec![Box::new(RemoteBackend {
+            address: config.remote.address.clone(),
+            secret: config.remote.secret.clone(),
+        })]);
+    }
+
+    let mut backends = vec![device_backend(config, backend)?];
+    backends.extend(gamepad_backend(config));
+    backends.extend(pen_backend(config));
+    backends.extend(touch_backend(config));
+    if config.show_layout_changes {
+        backends.push(Box::new(input_source::InputSourceBackend));
+    }
+    Ok(backends)
+}
+
+const USAGE: &str = "usage: rusteys [--config <path>] [--headless] [--chips-file <path>] [--backend rdev|evdev] [--renderer wgpu|glow] [--vsync on|off] [--software] [--log-file <path> [--log-max-mb <n>] [--log-rotate never|hourly|daily]] [--<action>[=<argument>]...] | [ctl <command> | config export <file> | config import <file> | record <file> | replay <file> [speed] | stats <file>... [--json <out>] | export csv|edl|fcpxml <out> <file>... [--offset <seconds>] [--fps <n>] | render --session <file>... --out <file.mov|file.webm|folder> [--size <width>x<height>] [--fps <n>] [--scale <n>] [--offset <seconds>] | autostart enable|disable]";
+
+// Subcommands that run instead of the overlay; None when there is none
+fn run_command(args: &[String], backend: Option<&str>) -> Option<Result<(), String>> {
+    let (command, rest) = args.split_first()?;
+    let result = match (command.as_str(), rest) {
+        // Talk to the running instance
+        ("ctl", command) if !command.is_empty() => match ipc::send(&command.join(" ")) {
+            Ok(reply) if reply == "ok" => Ok(()),
+            Ok(reply) => Err(reply),
+            Err(error) => Err(format!("Could not reach a running rusteys: {}", error)),
+        },
+        ("config", [action, path]) if action == "export" => Config::export(Path::new(path)),
+        ("config", [action, path]) if action == "import" => Config::import(Path::new(path)),
+        ("record", [path]) => input_backends(&Config::load(), backend)
+            .and_then(|backends| session::record(Path::new(path), backends)),
+        ("stats", [paths @ .., flag, out]) if flag == "--json" && !paths.is_empty() => {
+            analyze::run(paths, Some(Path::new(out)))
+        }
+        ("stats", paths) if !paths.is_empty() => analyze::run(paths, None),
+        ("export", [format, out, rest @ ..]) => export_args(rest)
+            .and_then(|(paths, options)| export::run(format, &paths, Path::new(out), &options)),
+        ("render", rest) => render_args(rest).and_then(|options| render::run(&options)),
+        ("autostart", [action]) if action == "enable" => autostart::enable(),
+        ("autostart", [action]) if action == "disable" => autostart::disable(),
+        // Shows the overlay, see replay_backend
+        ("replay", _) => return None,
+        // Overlay flags, see flag_actions
+        (flag, _) if flag.starts_with("--") => return None,
+        _ => Err(USAGE.to_string()),
+    };
+    Some(result)
+}
+
+// Session files, `--offset <seconds>` (the video started that long after the mark) and
+// `--fps <n>` for `export`
+fn export_args(args: &[String]) -> Result<(Vec<String>, ExportOptions), String> {
+    let mut paths = Vec::new();
+    let mut options = ExportOptions::default();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--offset" => {
+                let seconds = args.next().ok_or_else(|| USAGE.to_string())?;
+                let seconds: f64 = seconds
+                    .parse()
+                    .map_err(|_| format!("invalid offset `{}`", seconds))?;
+                options.offset_ms = (seconds * 1000.0).round() as i64;
+            }
+            "--fps" => {
+                let fps = args.next().ok_or_else(|| USAGE.to_string())?;
+                options.fps = fps
+                    .parse()
+                    .ok()
+                    .filter(|fps| *fps > 0)
+                    .ok_or_else(|| format!("invalid frame rate `{}`", fps))?;
+            }
+            _ => paths.push(arg.clone()),
+        }
+    }
+    if paths.is_empty() {
+        return Err(USAGE.to_string());
+    }
+    Ok((paths, options))
+}
+
+// `--session <file>` (repeatable), `--out <path>`, `--size 1920x1080`, `--fps <n>`,
+// `--scale <n>` and `--offset <seconds>` for `render`
+fn render_args(args: &[String]) -> Result<RenderOptions, String> {
+    let mut options = RenderOptions::default();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let value = args.next().ok_or_else(|| USAGE.to_string())?;
+        match arg.as_str() {
+            "--session" => options.sessions.push(value.clone()),
+            "--out" => options.out = PathBuf::from(value),
+            "--size" => {
+                let size: Option<(u32, u32)> = value
+                    .split_once('x')
+                    .and_then(|(width, height)| Some((width.parse().ok()?, height.parse().ok()?)));
+                (options.width, options.height) = size
+                    .filter(|&(width, height)| width > 0 && height > 0)
+                    .ok_or_else(|| format!("invalid size `{}`, expected e.g. 1920x1080", value))?;
+            }
+            "--fps" => {
+                options.fps = value
+                    .parse()
+                    .ok()
+                    .filter(|fps| *fps > 0)
+                    .ok_or_else(|| format!("invalid frame rate `{}`", value))?;
+            }
+            "--scale" => {
+                options.scale = value
+                    .parse()
+                    .ok()
+                    .filter(|scale: &f32| *scale > 0.0)
+                    .ok_or_else(|| format!("invalid scale `{}`", value))?;
+            }
+            "--offset" => {
+                let seconds: f64 = value
+                    .parse()
+                    .map_err(|_| format!("invalid offset `{}`", value))?;
+                options.offset_ms = (seconds * 1000.0).round() as i64;
+            }
+            _ => return Err(USAGE.to_string()),
+        }
+    }
+    if options.sessions.is_empty() || options.out.as_os_str().is_empty() {
+        return Err(USAGE.to_string());
+    }
+    Ok(options)
+}
+
+// `--pause`, `--profile=gaming`: actions to run at startup, or to send to a running instance
+fn flag_actions(args: &[String]) -> Result<Vec<Action>, String> {
+    args.iter()
+        .filter_map(|arg| arg.strip_prefix("--"))
+        .filter(|flag| *flag != "headless")
+        .map(|flag| Action::parse(&flag.replacen('=', " ", 1)))
+        .collect()
+}
+
+// Remove `--<name> <value>` or `--<name>=<value>` from the arguments and return the value
+fn take_option(args: &mut Vec<String>, name: &str) -> Result<Option<String>, String> {
+    if let Some(index) = args.iter().position(|arg| arg == name) {
+        if index + 1 >= args.len() {
+            return Err(USAGE.to_string());
+        }
+        let value = args.remove(index + 1);
+        args.remove(index);
+        return Ok(Some(value));
+    }
+    let prefix = format!("{}=", name);
+    let index = args.iter().position(|arg| arg.starts_with(&prefix));
+    Ok(index.map(|index| args.remove(index)[prefix.len()..].to_string()))
+}
+
+// Options that pick how rusteys captures and draws, rather than actions to run
+struct LaunchOptions {
+    // Keyboard and mouse backend, see device_backend
+    backend: Option<String>,
+    // Some Linux drivers draw a black background with one renderer but not the other
+    renderer: eframe::Renderer,
+    vsync: bool,
+    // CPU rendering through Mesa's llvmpipe, for VMs and broken GL drivers
+    software: bool,
+    // `--log-file`, with `--log-max-mb` and `--log-rotate`
+    log: Option<LogOptions>,
+}
+
+impl LaunchOptions {
+    fn take(args: &mut Vec<String>) -> Result<Self, String> {
+        let backend = take_option(args, "--backend")?;
+        let software = match args.iter().position(|arg| arg == "--software") {
+            Some(index) => {
+                args.remove(index);
+                true
+            }
+            None => false,
+        };
+        let renderer = match take_option(args, "--renderer")?.as_deref() {
+            // llvmpipe only implements OpenGL
+            _ if software => eframe::Renderer::Glow,
+            None => eframe::Renderer::default(),
+            Some("wgpu") => eframe::Renderer::Wgpu,
+            Some("glow") => eframe::Renderer::Glow,
+            Some(other) => {
+                return Err(format!(
+                    "unknown renderer `{}`, expected wgpu or glow",
+                    other
+                ));
+            }
+        };
+        let vsync = match take_option(args, "--vsync")?.as_deref() {
+            None | Some("on") => true,
+            Some("off") => false,
+            Some(other) => {
+                return Err(format!(
+                    "invalid vsync setting `{}`, expected on or off",
+                    other
+                ));
+            }
+        };
+        let max_size_mb = match take_option(args, "--log-max-mb")? {
+            None => 50,
+            Some(size) => size
+                .parse()
+                .ok()
+                .filter(|size| *size > 0)
+                .ok_or_else(|| format!("invalid log size `{}`", size))?,
+        };
+        let rotation = match take_option(args, "--log-rotate")? {
+            None => Rotation::Daily,
+            Some(rotation) => Rotation::parse(&rotation)?,
+        };
+        let log = take_option(args, "--log-file")?.map(|path| LogOptions {
+            path: PathBuf::from(path),
+            max_size_mb,
+            rotation,
+        });
+        Ok(Self {
+            backend,
+            renderer,
+            vsync,
+            software,
+            log,
+        })
+    }
+}
+
+// `replay <file> [speed]` shows a recorded session instead of live input
+fn replay_backend(args: &[String]) -> Option<Result<ReplayBackend, String>> {
+    let (command, rest) = args.split_first()?;
+    if command != "replay" {
+        return None;
+    }
+
+    let result = match rest {
+        [path] => Ok((path, 1.0)),
+        [path, speed] => match speed.parse::<f64>() {
+            Ok(speed) if speed > 0.0 => Ok((path, speed)),
+            _ => Err(format!("invalid replay speed `{}`", speed)),
+        },
+        _ => Err(USAGE.to_string()),
+    };
+    Some(result.map(|(path, speed)| ReplayBackend {
+        path: PathBuf::from(path),
+        speed,
+    }))
+}
+
+// Transparent style, fonts and image loaders, for the overlay window and `rusteys render`
+fn prepare_context(ctx: &egui::Context, config: &Config) {
+    let mut style = (*ctx.style()).clone();
+    style.visuals.window_fill = egui::Color32::TRANSPARENT;
+    style.visuals.panel_fill = egui::Color32::TRANSPARENT;
+    style.visuals.window_stroke = egui::Stroke::NONE;
+    style.visuals.extreme_bg_color = egui::Color32::TRANSPARENT;
+    style.visuals.faint_bg_color = egui::Color32::TRANSPARENT;
+    ctx.set_style(style);
+    ui::install_symbol_font(ctx, config.symbol_font.as_deref());
+    egui_extras::install_image_loaders(ctx);
+}
+
+fn load_layout(path: Option<&Path>) -> KeyboardLayout {
+    match path {
+        Some(path) => KeyboardLayout::load(path).unwrap_or_else(|error| {
+            eprintln!(
+                "Error loading keyboard layout {}: {}",
+                path.display(),
+                error
+            );
+            KeyboardLayout::ansi()
+        }),
+        None => KeyboardLayout::ansi(),
+    }
+}
+
+fn main() -> Result<(), eframe::Error> {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    match take_option(&mut args, "--config") {
+        Ok(Some(path)) => paths::set_config(PathBuf::from(path)),
+        Ok(None) => paths::migrate_legacy(),
+        Err(error) => {
+            eprintln!("{}", error);
+            std::process::exit(1);
+        }
+    }
+    let launch = LaunchOptions::take(&mut args).unwrap_or_else(|error| {
+        eprintln!("{}", error);
+        std::process::exit(1);
+    });
+    if launch.software {
+        // Nothing else is running yet, so changing the environment is safe
+        unsafe { std::env::set_var("LIBGL_ALWAYS_SOFTWARE", "1") };
+    }
+    if let Some(result) = run_command(&args, launch.backend.as_deref()) {
+        if let Err(error) = result {
+            eprintln!("{}", error);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+    // Capture and stream chips without any window
+    let headless = args.iter().any(|arg| arg == "--headless");
+    let chip_file = take_option(&mut args, "--chips-file")
+        .and_then(|path| {
+            path.map(|path| {
+                std::fs::OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&path)
+                    .map_err(|e| format!("{}: {}", path, e))
+            })
+            .transpose()
+        })
+        .unwrap_or_else(|error| {
+            eprintln!("{}", error);
+            std::process::exit(1);
+        });
+    let replay = replay_backend(&args).transpose().unwrap_or_else(|error| {
+        eprintln!("{}", error);
+        std::process::exit(1);
+    });
+    let actions = flag_actions(&args).unwrap_or_else(|error| {
+        eprintln!("{}\n{}", error, USAGE);
+        std::process::exit(1);
+    });
+
+    // Only one overlay at a time for each user: whichever instance binds that user's control
+    // socket runs, and later ones started by the same user hand their flags to it instead
+    let control_socket = match ipc::bind() {
+        Ok(listener) => Some(listener),
+        Err(_) if replay.is_none() && ipc::send("ping").is_ok() => {
+            if actions.is_empty() {
+                eprintln!("rusteys is already running");
+            }
+            for action in &actions {
+                match ipc::send(&action.to_string()) {
+                    Ok(reply) if reply == "ok" => {}
+                    Ok(reply) => eprintln!("{}", reply),
+                    Err(error) => eprintln!("Could not reach a running rusteys: {}", error),
+                }
+            }
+            return Ok(());
+        }
+        Err(error) => {
+            eprintln!("Error starting control socket: {}", error);
+            None
+        }
+    };
+
+    let config = Arc::new(Config::load());
+    i18n::set(config.language);
+    let shared_config: SharedConfig = Arc::new(RwLock::new(Arc::clone(&config)));
+    let repaint = Repaint::default();
+    config::watch(Arc::clone(&shared_config), repaint.clone());
+    let layout = Arc::new(load_layout(config.keyboard_layout.as_deref()));
+
+    let (chips, chip_updates) = mpsc::channel();
+    let held_keys = Arc::new(Mutex::new(Vec::new()));
+    let cursor = Arc::new(Mutex::new(CursorState::default()));
+    let event_log = launch.log.map(|options| {
+        let log = EventLog::open(options).unwrap_or_else(|error| {
+            eprintln!("Error opening log file {}", error);
+            std::process::exit(1);
+        });
+        Arc::new(Mutex::new(log))
+    });
+    let controls = Arc::new(Controls::new(
+        chips.clone(),
+        Arc::clone(&shared_config),
+        event_log,
+        repaint.clone(),
+    ));
+    let history = Arc::new(Mutex::new(History::new(&config.history)));
+    let which_key = match &config.which_key.bindings {
+        Some(path) => which_key::Bindings::load(path).unwrap_or_else(|error| {
+            eprintln!("Error loading which-key bindings: {}", error);
+            which_key::Bindings::default()
+        }),
+        None => which_key::Bindings::default(),
+    };
+    let backend_errors = BackendErrors::default();
+    let osc = if config.osc.enabled {
+        osc::OscSender::new(&config.osc)
+            .map_err(|error| {
+                eprintln!(
+                    "Error setting up OSC output to {}: {}",
+                    config.osc.target, error
+                )
+            })
+            .ok()
+    } else {
+        None
+    };
+    let websocket = if config.websocket.enabled {
+        websocket::WebSocketServer::new(
+            &config.websocket.address,
+            config.websocket.allowed_origins.clone(),
+        )
+        .map_err(|error| eprintln!("Error starting WebSocket server: {}", error))
+        .ok()
+    } else {
+        None
+    };
+    let handler = Arc::new(Mutex::new(EventHandler {
+        chips,
+        held_keys: Arc::clone(&held_keys),
+        cursor: Arc::clone(&cursor),
+        config: Arc::clone(&shared_config),
+        controls: Arc::clone(&controls),
+        history: Arc::clone(&history),
+        osc,
+        remote: (config.remote.mode == RemoteMode::Send).then(|| RemoteSender::new(&config.remote)),
+        websocket,
+        webhooks: webhook::WebhookSender::spawn(),
+        stdout: headless,
+        chip_file: chip_file.map(|file| Mutex::new(std::io::LineWriter::new(file))),
+        modifiers: Modifiers::default(),
+        tap_hold: None,
+        macro_matcher: macros::Matcher::default(),
+        script: chip_script(&config),
+        plugins: plugin_host(&config),
+        which_key,
+        drag_tracker: DragTracker::default(),
+        scroll_accumulator: ScrollAccumulator::default(),
+    }));
+
+    for action in &actions {
+        controls.apply(action);
+    }
+    if let Some(listener) = control_socket {
+        ipc::serve(listener, Arc::clone(&controls));
+    }
+    if config.http.enabled {
+        http::serve(&config.http, Arc::clone(&controls), Arc::clone(&history));
+    }
+
+    // Spawn a listener thread per input backend, all feeding the same handler
+    let backends = match replay {
+        Some(replay) => vec![Box::new(replay) as Box<dyn InputBackend>],
+        None => input_backends(&config, launch.backend.as_deref()).unwrap_or_else(|error| {
+            eprintln!("{}", error);
+            std::process::exit(1);
+        }),
+    };
+    for backend in backends {
+        let handler = Arc::clone(&handler);
+        let wake = repaint.clone();
+        input::spawn(
+            backend,
+            Box::new(move |event| {
+                // Bare pointer motion only matters to the cursor overlays
+                let moved = matches!(
+                    &event,
+                    InputEvent::Device(rdev::Event {
+                        event_type: EventType::MouseMove { .. },
+                        ..
+                    }) | InputEvent::Tagged(
+                        rdev::Event {
+                            event_type: EventType::MouseMove { .. },
+                            ..
+                        },
+                        _
+                    )
+                );
+                let mut handler = handler.lock();
+                let suppress = handler.handle(event);
+                if !moved || handler.config.read().tracks_pointer() {
+                    wake.request();
+                }
+                suppress
+            }),
+            Arc::clone(&backend_errors),
+            repaint.clone(),
+        );
+    }
+
+    if headless {
+        eprintln!("Running headless; press Ctrl+C to stop");
+        // Nothing displays chips, so let sends fail instead of queueing forever
+        drop(chip_updates);
+        loop {
+            std::thread::park();
+        }
+    }
 
     // Get monitor information from Windows API
     #[cfg(target_os = "windows")]
     let mut monitors = get_monitors_windows();
-    
+
+    // Replaced by the real size and scale once the window is open, see KeyDisplayApp::measure_monitor
     #[cfg(not(target_os = "windows"))]
     let mut monitors = vec![MonitorInfo {
         x: 0.0,
         y: 0.0,
         width: SCREEN_WIDTH,
         height: SCREEN_HEIGHT,
+        scale: 1.0,
     }];
 
     if monitors.is_empty() {
@@ -496,56 +2803,78 @@ fn main() -> Result<(), eframe::Error> {
             y: 0.0,
             width: SCREEN_WIDTH,
             height: SCREEN_HEIGHT,
+            scale: 1.0,
         });
     }
 
-    println!("Detected {} monitor(s), creating overlay on each...", monitors.len());
+    println!(
+        "Detected {} monitor(s), creating overlay on each...",
+        monitors.len()
+    );
 
     // Position the first window on the first monitor
-    let first_monitor = &monitors[0];
-    let window_width = first_monitor.width * WINDOW_WIDTH_FRACTION;
-    let window_x = first_monitor.x + (first_monitor.width - window_width) / 2.0;
-    let window_y = first_monitor.y + first_monitor.height * 0.85;
+    let overlay = monitors[0].overlay_geometry(&config, &layout, controls.anchor());
 
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
-            .with_inner_size([window_width, 100.0])
-            .with_position([window_x, window_y])
+            .with_inner_size(overlay.size())
+            .with_position(overlay.min)
             .with_decorations(false)
             .with_transparent(true)
             .with_always_on_top()
             .with_resizable(false)
             .with_mouse_passthrough(false),
+        renderer: launch.renderer,
+        vsync: launch.vsync,
         ..Default::default()
     };
 
-    eframe::run_native(
+    let result = eframe::run_native(
         "Key Display Overlay - Monitor 1",
         options,
         Box::new(move |cc| {
-            let mut style = (*cc.egui_ctx.style()).clone();
-            style.visuals.window_fill = egui::Color32::TRANSPARENT;
-            style.visuals.panel_fill = egui::Color32::TRANSPARENT;
-            style.visuals.window_stroke = egui::Stroke::NONE;
-            style.visuals.extreme_bg_color = egui::Color32::TRANSPARENT;
-            style.visuals.faint_bg_color = egui::Color32::TRANSPARENT;
-            cc.egui_ctx.set_style(style);
-
-            Ok(Box::new(KeyDisplayApp::new(key_presses, monitors)))
+            prepare_context(&cc.egui_ctx, &config);
+            repaint.set(&cc.egui_ctx);
+
+            Ok(Box::new(KeyDisplayApp::new(
+                chip_updates,
+                held_keys,
+                cursor,
+                monitors,
+                shared_config,
+                layout,
+                controls,
+                history,
+                backend_errors,
+            )))
         }),
-    )
+    );
+
+    // Most often a VM or a broken GPU driver
+    if let Err(error) = &result {
+        eprintln!("Could not create the overlay window: {}", error);
+        if !launch.software {
+            eprintln!(
+                "Try `rusteys --software` for CPU rendering, the other renderer with `--renderer glow` or `--renderer wgpu`, or `rusteys --headless`"
+            );
+        }
+    }
+    result
 }
 
 #[cfg(target_os = "windows")]
 fn get_monitors_windows() -> Vec<MonitorInfo> {
     use std::ptr;
-    use winapi::shared::windef::{HDC, HMONITOR, LPRECT};
-    use winapi::um::winuser::EnumDisplayMonitors;
-    
+    use winapi::shared::windef::{
+        DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2, HDC, HMONITOR, LPRECT,
+    };
+    use winapi::um::shellscalingapi::{GetDpiForMonitor, MDT_EFFECTIVE_DPI};
+    use winapi::um::winuser::{EnumDisplayMonitors, SetProcessDpiAwarenessContext};
+
     let mut monitors = Vec::new();
-    
+
     unsafe extern "system" fn monitor_enum_proc(
-        _hmonitor: HMONITOR,
+        hmonitor: HMONITOR,
         _hdc: HDC,
         lprect: LPRECT,
         lparam: isize,
@@ -553,19 +2882,27 @@ fn get_monitors_windows() -> Vec<MonitorInfo> {
         unsafe {
             let monitors = &mut *(lparam as *mut Vec<MonitorInfo>);
             let rect = &*lprect;
-            
+
+            // 96 DPI is 100% scaling
+            let (mut dpi_x, mut dpi_y) = (96, 96);
+            GetDpiForMonitor(hmonitor, MDT_EFFECTIVE_DPI, &mut dpi_x, &mut dpi_y);
+
             monitors.push(MonitorInfo {
                 x: rect.left as f32,
                 y: rect.top as f32,
                 width: (rect.right - rect.left) as f32,
                 height: (rect.bottom - rect.top) as f32,
+                scale: dpi_x as f32 / 96.0,
             });
-            
+
             1 // Continue enumeration
         }
     }
-    
+
     unsafe {
+        // Physical pixels, as winit will use once it opens the window; otherwise Windows reports
+        // bounds scaled to the primary monitor's DPI
+        SetProcessDpiAwarenessContext(DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2);
         EnumDisplayMonitors(
             ptr::null_mut(),
             ptr::null(),
@@ -573,6 +2910,6 @@ fn get_monitors_windows() -> Vec<MonitorInfo> {
             &mut monitors as *mut _ as isize,
         );
     }
-    
+
     monitors
 }
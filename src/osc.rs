@@ -0,0 +1,50 @@
+use crate::config::OscConfig;
+use rosc::{OscMessage, OscPacket, OscType, encoder};
+use std::io;
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+
+// Sends every chip as an OSC message with the chip text as its only argument
+pub struct OscSender {
+    socket: UdpSocket,
+    target: SocketAddr,
+    address: String,
+}
+
+impl OscSender {
+    // The target is resolved here, once, since chips are sent from the input hook
+    pub fn new(config: &OscConfig) -> io::Result<Self> {
+        let target = config.target.to_socket_addrs()?.next().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("`{}` has no address", config.target),
+            )
+        })?;
+        let local = if target.is_ipv6() {
+            "[::]:0"
+        } else {
+            "0.0.0.0:0"
+        };
+        Ok(Self {
+            socket: UdpSocket::bind(local)?,
+            target,
+            address: config.address.clone(),
+        })
+    }
+
+    pub fn send(&self, text: &str) {
+        let packet = OscPacket::Message(OscMessage {
+            addr: self.address.clone(),
+            args: vec![OscType::String(text.to_string())],
+        });
+        let result = encoder::encode(&packet)
+            .map_err(|error| error.to_string())
+            .and_then(|bytes| {
+                self.socket
+                    .send_to(&bytes, self.target)
+                    .map_err(|error| error.to_string())
+            });
+        if let Err(error) = result {
+            eprintln!("Error sending OSC message to {}: {}", self.target, error);
+        }
+    }
+}
@@ -0,0 +1,167 @@
+use directories::ProjectDirs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+const CONFIG_NAME: &str = "rusteys.toml";
+
+// Left in the data folder once legacy files have been looked for
+const MIGRATED_MARKER: &str = "migrated";
+
+// Set once by `--config` before anything reads the config
+static CONFIG_OVERRIDE: OnceLock<PathBuf> = OnceLock::new();
+
+// ~/.config/rusteys, ~/Library/Application Support/rusteys or %APPDATA%\rusteys\config;
+// `None` without a home directory, where everything stays in the working directory as before
+fn project() -> Option<&'static ProjectDirs> {
+    static DIRS: OnceLock<Option<ProjectDirs>> = OnceLock::new();
+    DIRS.get_or_init(|| ProjectDirs::from("", "", "rusteys"))
+        .as_ref()
+}
+
+pub fn set_config(path: PathBuf) {
+    let _ = CONFIG_OVERRIDE.set(path);
+}
+
+pub fn config_dir() -> PathBuf {
+    project().map_or_else(PathBuf::new, |dirs| dirs.config_dir().to_path_buf())
+}
+
+// History, macros, screenshots and runtime state
+pub fn data_dir() -> PathBuf {
+    project().map_or_else(PathBuf::new, |dirs| dirs.data_dir().to_path_buf())
+}
+
+// Files that can be recreated at any time, like the control socket where it is a file
+pub fn cache_dir() -> PathBuf {
+    project().map_or_else(PathBuf::new, |dirs| dirs.cache_dir().to_path_buf())
+}
+
+pub fn config_file() -> PathBuf {
+    match CONFIG_OVERRIDE.get() {
+        Some(path) => path.clone(),
+        None => config_dir().join(CONFIG_NAME),
+    }
+}
+
+// Previous config file kept by `rusteys config import`
+pub fn backup_file() -> PathBuf {
+    let mut name = config_file().into_os_string();
+    name.push(".bak");
+    PathBuf::from(name)
+}
+
+// Settings changed at runtime, like the hotkey zoom, saved apart so the config file is never rewritten
+pub fn state_file() -> PathBuf {
+    data_dir().join("rusteys-state.toml")
+}
+
+// Relative paths in the config file are relative to the file, not to where rusteys was started
+pub fn resolve(path: &mut PathBuf) {
+    if path.is_relative()
+        && let Some(dir) = config_file().parent()
+    {
+        *path = dir.join(&*path);
+    }
+}
+
+// Earlier versions kept everything in the working directory; on the first start with these
+// locations, copy what is there, leaving the originals and anything that already exists alone
+pub fn migrate_legacy() {
+    let marker = data_dir().join(MIGRATED_MARKER);
+    if project().is_none() || marker.exists() {
+        return;
+    }
+    let copies = [
+        (CONFIG_NAME, config_file()),
+        ("rusteys.toml.bak", backup_file()),
+        ("cheat-sheets", config_dir().join("cheat-sheets")),
+        ("rusteys-state.toml", state_file()),
+        (
+            "rusteys-history.jsonl",
+            data_dir().join("rusteys-history.jsonl"),
+        ),
+        (
+            "rusteys-macros.toml",
+            data_dir().join("rusteys-macros.toml"),
+        ),
+    ];
+    for (legacy, target) in copies {
+        let legacy = Path::new(legacy);
+        if !legacy.exists() || target.exists() {
+            continue;
+        }
+        // The working directory may be a project with its own rusteys.toml, so nothing is taken
+        // out of it
+        match create_parent(&target).and_then(|()| copy(legacy, &target)) {
+            Ok(()) => println!(
+                "Copied {} to {}; the original is left in place",
+                legacy.display(),
+                target.display()
+            ),
+            Err(error) => eprintln!(
+                "Error copying {} to {}: {}",
+                legacy.display(),
+                target.display(),
+                error
+            ),
+        }
+    }
+
+    if let Err(error) = create_parent(&marker).and_then(|()| std::fs::write(&marker, "")) {
+        eprintln!("Error writing {}: {}", marker.display(), error);
+    }
+}
+
+// The data and config directories don't exist until something is first saved there
+pub fn create_parent(path: &Path) -> std::io::Result<()> {
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => std::fs::create_dir_all(parent),
+        _ => Ok(()),
+    }
+}
+
+// Create files only this user can read, like the API token or logs of typed text
+pub fn private(options: &mut std::fs::OpenOptions) -> &mut std::fs::OpenOptions {
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(options, 0o600);
+    options
+}
+
+// `File::create` for files only this user can read, tightening one that already exists
+pub fn create_private(path: &Path) -> std::io::Result<std::fs::File> {
+    let file = private(
+        std::fs::OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true),
+    )
+    .open(path)?;
+    restrict(path)?;
+    Ok(file)
+}
+
+// Leave a file readable and writable by this user only, like the control socket; other platforms
+// keep their own defaults
+pub fn restrict(path: &Path) -> std::io::Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+    Ok(())
+}
+
+// A file, or a folder with everything in it
+fn copy(from: &Path, to: &Path) -> std::io::Result<()> {
+    if !from.is_dir() {
+        return std::fs::copy(from, to).map(|_| ());
+    }
+    std::fs::create_dir_all(to)?;
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        copy(&entry.path(), &to.join(entry.file_name()))?;
+    }
+    Ok(())
+}
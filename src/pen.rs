@@ -0,0 +1,58 @@
+use crate::hotplug;
+use crate::input::{EventSink, InputBackend, InputEvent, Source};
+use evdev::{InputEventKind, Key};
+use parking_lot::Mutex;
+use std::sync::Arc;
+
+// Graphics tablet stylus events read from evdev (Linux only)
+pub struct PenBackend;
+
+impl InputBackend for PenBackend {
+    fn name(&self) -> &'static str {
+        "pen"
+    }
+
+    fn run(self: Box<Self>, sink: EventSink) -> Result<(), String> {
+        hotplug::read_devices("tablet", is_tablet, read_tablet, sink)
+    }
+}
+
+// Tablets expose a pen tool; touchpads and touchscreens don't
+fn is_tablet(device: &evdev::Device) -> bool {
+    device
+        .supported_keys()
+        .is_some_and(|keys| keys.contains(Key::BTN_TOOL_PEN))
+}
+
+fn read_tablet(mut device: evdev::Device, sink: Arc<Mutex<EventSink>>) -> Result<(), String> {
+    // The eraser end reports as a separate tool while it is near the tablet
+    let mut eraser = false;
+
+    loop {
+        let events = device.fetch_events().map_err(|error| error.to_string())?;
+        for event in events {
+            let InputEventKind::Key(key) = event.kind() else {
+                continue;
+            };
+            let pressed = event.value() == 1;
+
+            let label = if key == Key::BTN_TOOL_RUBBER {
+                eraser = pressed;
+                continue;
+            } else if key == Key::BTN_TOUCH {
+                if eraser { "Eraser" } else { "Pen Tip" }
+            } else if key == Key::BTN_STYLUS {
+                "Pen Button 1"
+            } else if key == Key::BTN_STYLUS2 {
+                "Pen Button 2"
+            } else {
+                continue;
+            };
+
+            if pressed {
+                let mut sink = sink.lock();
+                (*sink)(InputEvent::Chip(label.to_string(), Source::Pointer));
+            }
+        }
+    }
+}
@@ -0,0 +1,125 @@
+use crate::input::InputBackend;
+use eframe::egui;
+
+// macOS needs Accessibility and Input Monitoring permission before global input can be read;
+// other platforms have nothing to ask for
+
+#[cfg(target_os = "macos")]
+mod macos {
+    use crate::input::{EventSink, InputBackend};
+    use std::thread;
+    use std::time::Duration;
+
+    // IOHIDCheckAccess request and result values
+    const LISTEN_EVENT: u32 = 1;
+    const ACCESS_GRANTED: u32 = 0;
+
+    #[link(name = "ApplicationServices", kind = "framework")]
+    unsafe extern "C" {
+        fn AXIsProcessTrusted() -> bool;
+    }
+
+    #[link(name = "IOKit", kind = "framework")]
+    unsafe extern "C" {
+        fn IOHIDCheckAccess(request: u32) -> u32;
+    }
+
+    pub fn accessibility() -> bool {
+        unsafe { AXIsProcessTrusted() }
+    }
+
+    pub fn input_monitoring() -> bool {
+        unsafe { IOHIDCheckAccess(LISTEN_EVENT) == ACCESS_GRANTED }
+    }
+
+    pub fn open_settings(pane: &str) {
+        let url = format!(
+            "x-apple.systempreferences:com.apple.preference.security?{}",
+            pane
+        );
+        if let Err(error) = std::process::Command::new("open").arg(url).spawn() {
+            eprintln!("Error opening System Settings: {}", error);
+        }
+    }
+
+    // Starts the wrapped backend once permission has been granted
+    pub struct WaitForPermission(pub Box<dyn InputBackend>);
+
+    impl InputBackend for WaitForPermission {
+        fn name(&self) -> &'static str {
+            self.0.name()
+        }
+
+        fn run(self: Box<Self>, sink: EventSink) -> Result<(), String> {
+            while !(accessibility() && input_monitoring()) {
+                thread::sleep(Duration::from_secs(1));
+            }
+            self.0.run(sink)
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+pub fn wrap(backend: Box<dyn InputBackend>) -> Box<dyn InputBackend> {
+    Box::new(macos::WaitForPermission(backend))
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn wrap(backend: Box<dyn InputBackend>) -> Box<dyn InputBackend> {
+    backend
+}
+
+// Guided prompt shown until every permission is granted
+#[cfg(target_os = "macos")]
+pub fn show_onboarding(ctx: &egui::Context) {
+    use crate::i18n::{Text, tr, tr_args};
+
+    let accessibility = macos::accessibility();
+    let input_monitoring = macos::input_monitoring();
+    if accessibility && input_monitoring {
+        return;
+    }
+
+    ctx.show_viewport_immediate(
+        egui::ViewportId::from_hash_of("permissions"),
+        egui::ViewportBuilder::default()
+            .with_title(tr(Text::PermissionsTitle))
+            .with_inner_size([480.0, 260.0])
+            .with_always_on_top(),
+        |ctx, _class| {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                ui.heading(tr(Text::PermissionsHeading));
+                ui.label(tr(Text::PermissionsIntro));
+                ui.add_space(8.0);
+
+                let steps = [
+                    (
+                        accessibility,
+                        tr(Text::Accessibility),
+                        "Privacy_Accessibility",
+                    ),
+                    (
+                        input_monitoring,
+                        tr(Text::InputMonitoring),
+                        "Privacy_ListenEvent",
+                    ),
+                ];
+                for (granted, name, pane) in steps {
+                    ui.horizontal(|ui| {
+                        ui.label(if granted { "✔" } else { "✖" });
+                        ui.label(name);
+                        if !granted && ui.button(tr_args(Text::OpenSettings, &[&name])).clicked() {
+                            macos::open_settings(pane);
+                        }
+                    });
+                }
+            });
+        },
+    );
+
+    // Notice the grant without waiting for input
+    ctx.request_repaint_after(std::time::Duration::from_secs(1));
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn show_onboarding(_ctx: &egui::Context) {}
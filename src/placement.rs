@@ -0,0 +1,159 @@
+// Placements that move the overlay window while rusteys runs, rather than putting it at a fixed
+// spot on each monitor, and keeping it clear of parts of the screen
+
+use crate::config::{Anchor, PositionConfig};
+use crate::{MonitorInfo, WINDOW_WIDTH_FRACTION};
+use eframe::egui;
+
+const ANCHORS: [Anchor; 9] = [
+    Anchor::TopLeft,
+    Anchor::Top,
+    Anchor::TopRight,
+    Anchor::Left,
+    Anchor::Center,
+    Anchor::Right,
+    Anchor::BottomLeft,
+    Anchor::Bottom,
+    Anchor::BottomRight,
+];
+
+// Moves shorter than this (in points) finish at once instead of creeping on forever
+const SETTLED: f32 = 0.5;
+
+// Follow-cursor placement: centered below the pointer (above it near the bottom edge), kept
+// inside the monitor the pointer is on. The pointer is in physical pixels, like rdev reports it.
+pub fn follow_cursor(
+    monitors: &[MonitorInfo],
+    pointer: egui::Pos2,
+    size: egui::Vec2,
+    config: &PositionConfig,
+) -> egui::Pos2 {
+    let monitor = monitor_at(monitors, pointer);
+    let bounds = monitor.rect().shrink(config.edge_margin);
+    let pointer = (pointer.to_vec2() / monitor.scale).to_pos2();
+
+    let mut position = egui::pos2(pointer.x - size.x / 2.0, pointer.y + config.cursor_offset);
+    if position.y + size.y > bounds.bottom() {
+        position.y = pointer.y - config.cursor_offset - size.y;
+    }
+    keep_inside(position, size, bounds)
+}
+
+// Window placement: at the configured anchor inside the focused window, as wide as a panel is on
+// a monitor. The window is in the pointer's coordinates; `height` gives the overlay height for a
+// width.
+pub fn on_window(
+    monitors: &[MonitorInfo],
+    window: egui::Rect,
+    config: &PositionConfig,
+    height: impl Fn(f32) -> f32,
+) -> egui::Rect {
+    let monitor = monitor_at(monitors, window.center());
+    let window = egui::Rect::from_min_max(
+        (window.min.to_vec2() / monitor.scale).to_pos2(),
+        (window.max.to_vec2() / monitor.scale).to_pos2(),
+    );
+    let width = window.width() * WINDOW_WIDTH_FRACTION;
+    let size = egui::vec2(width, height(width));
+    let position = config
+        .window_anchor
+        .align()
+        .align_size_within_rect(size, window.shrink(config.edge_margin))
+        .min;
+    // Windows partly off-screen still get a fully visible overlay
+    let bounds = monitor.rect().shrink(config.edge_margin);
+    egui::Rect::from_min_size(keep_inside(position, size, bounds), size)
+}
+
+// The overlay moved to the nearest of the anchor points on its monitor where it covers none of
+// the keep-out regions; where it is when it covers none, or when every anchor covers one.
+// Everything in points.
+pub fn avoid(monitors: &[MonitorInfo], overlay: egui::Rect, config: &PositionConfig) -> egui::Rect {
+    if config.keep_out.is_empty() {
+        return overlay;
+    }
+    let monitor = monitors
+        .iter()
+        .map(MonitorInfo::rect)
+        .find(|monitor| monitor.contains(overlay.center()))
+        .unwrap_or_else(|| monitors[0].rect());
+    let regions: Vec<egui::Rect> = config
+        .keep_out
+        .iter()
+        .map(|region| {
+            egui::Rect::from_min_size(
+                monitor.min + egui::vec2(region.x, region.y),
+                egui::vec2(region.width, region.height),
+            )
+        })
+        .collect();
+    let clear = |rect: &egui::Rect| !regions.iter().any(|region| region.intersects(*rect));
+    if clear(&overlay) {
+        return overlay;
+    }
+
+    let bounds = monitor.shrink(config.edge_margin);
+    ANCHORS
+        .iter()
+        .map(|anchor| {
+            anchor
+                .align()
+                .align_size_within_rect(overlay.size(), bounds)
+        })
+        .filter(clear)
+        .min_by(|a, b| {
+            let distance = |rect: &egui::Rect| rect.center().distance_sq(overlay.center());
+            distance(a).total_cmp(&distance(b))
+        })
+        .unwrap_or(overlay)
+}
+
+// Anchor the panel snaps to when dropped at `window`: the closest one on the monitor, if within
+// the snap distance
+pub fn snap(monitor: egui::Rect, window: egui::Rect, config: &PositionConfig) -> Option<Anchor> {
+    let bounds = monitor.shrink(config.edge_margin);
+    ANCHORS
+        .iter()
+        .map(|anchor| {
+            let rect = anchor.align().align_size_within_rect(window.size(), bounds);
+            (*anchor, rect.min.distance(window.min))
+        })
+        .filter(|(_, distance)| *distance <= config.snap_distance)
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(anchor, _)| anchor)
+}
+
+// One frame's step from where the window is towards where it should be. Smoothing 0 jumps
+// straight there; closer to 1 trails further behind, independent of the frame rate.
+pub fn smooth(current: egui::Pos2, target: egui::Pos2, smoothing: f32, dt: f32) -> egui::Pos2 {
+    if !current.x.is_finite() || current.distance(target) < SETTLED {
+        return target;
+    }
+    let remaining = smoothing.clamp(0.0, 0.99).powf(dt * 60.0);
+    target + (current - target) * remaining
+}
+
+// Monitor a point in physical pixels is on, or the first one
+fn monitor_at(monitors: &[MonitorInfo], point: egui::Pos2) -> &MonitorInfo {
+    monitors
+        .iter()
+        .find(|monitor| {
+            egui::Rect::from_min_size(
+                egui::pos2(monitor.x, monitor.y),
+                egui::vec2(monitor.width, monitor.height),
+            )
+            .contains(point)
+        })
+        .unwrap_or(&monitors[0])
+}
+
+fn keep_inside(position: egui::Pos2, size: egui::Vec2, bounds: egui::Rect) -> egui::Pos2 {
+    egui::pos2(
+        position
+            .x
+            .clamp(bounds.left(), (bounds.right() - size.x).max(bounds.left())),
+        position
+            .y
+            .clamp(bounds.top(), (bounds.bottom() - size.y).max(bounds.top())),
+    )
+}
@@ -0,0 +1,179 @@
+use crate::PluginOutput;
+use crate::input::InputEvent;
+use crate::session::SessionEvent;
+use std::path::{Path, PathBuf};
+use wasmtime::{Caller, Engine, Instance, Linker, Memory, Module, Store, TypedFunc};
+
+// Budget for one event; plugins run on the input hook's thread, so one that loops must trap
+// rather than hold up every key and click on the system
+const FUEL_PER_EVENT: u64 = 10_000_000;
+
+// WASM plugins from the config's `plugins`. The ABI, for any language that compiles to wasm32:
+//
+// Exports
+//   memory                          linear memory
+//   alloc(len: i32) -> i32          room for an event; only needs to stay valid during `on_event`
+//   on_event(ptr: i32, len: i32)    one input event as UTF-8 JSON, in the session file format
+//
+// Imports from module "rusteys", all taking a UTF-8 string in the plugin's memory
+//   chip(ptr: i32, len: i32)        show a chip
+//   action(ptr: i32, len: i32)      run an action, like "confetti" or "profile gaming"
+//   log(ptr: i32, len: i32)         print a line to stderr
+struct Plugin {
+    name: String,
+    store: Store<Vec<PluginOutput>>,
+    instance: Instance,
+    alloc: TypedFunc<i32, i32>,
+    on_event: TypedFunc<(i32, i32), ()>,
+}
+
+impl Plugin {
+    fn load(
+        engine: &Engine,
+        linker: &Linker<Vec<PluginOutput>>,
+        path: &Path,
+    ) -> Result<Self, String> {
+        let module = Module::from_file(engine, path).map_err(|e| e.to_string())?;
+        let mut store = Store::new(engine, Vec::new());
+        let instance = linker
+            .instantiate(&mut store, &module)
+            .map_err(|e| e.to_string())?;
+        let alloc = instance
+            .get_typed_func(&mut store, "alloc")
+            .map_err(|e| e.to_string())?;
+        let on_event = instance
+            .get_typed_func(&mut store, "on_event")
+            .map_err(|e| e.to_string())?;
+        Ok(Self {
+            name: path.display().to_string(),
+            store,
+            instance,
+            alloc,
+            on_event,
+        })
+    }
+
+    fn send(&mut self, event: &[u8]) -> wasmtime::Result<Vec<PluginOutput>> {
+        self.store.set_fuel(FUEL_PER_EVENT)?;
+        let memory = self.memory()?;
+        let ptr = self.alloc.call(&mut self.store, event.len() as i32)?;
+        let offset = usize::try_from(ptr)?;
+        memory.write(&mut self.store, offset, event)?;
+        self.on_event
+            .call(&mut self.store, (ptr, event.len() as i32))?;
+        Ok(std::mem::take(self.store.data_mut()))
+    }
+
+    fn memory(&mut self) -> wasmtime::Result<Memory> {
+        self.instance
+            .get_memory(&mut self.store, "memory")
+            .ok_or_else(|| wasmtime::Error::msg("no exported memory"))
+    }
+}
+
+// Every plugin that loaded; one that traps is dropped
+pub struct Plugins(Vec<Plugin>);
+
+impl Plugins {
+    pub fn load(paths: &[PathBuf]) -> Self {
+        let mut config = wasmtime::Config::new();
+        config.consume_fuel(true);
+        let engine = match Engine::new(&config) {
+            Ok(engine) => engine,
+            Err(error) => {
+                eprintln!("Error setting up plugins: {}", error);
+                return Plugins(Vec::new());
+            }
+        };
+        let mut linker = Linker::new(&engine);
+        let imports: [(&str, fn(String) -> Option<PluginOutput>); 3] = [
+            ("chip", |text| Some(PluginOutput::Chip(text))),
+            ("action", |text| Some(PluginOutput::Action(text))),
+            ("log", |text| {
+                eprintln!("{}", text);
+                None
+            }),
+        ];
+        for (name, output) in imports {
+            let wrapped = linker.func_wrap(
+                "rusteys",
+                name,
+                move |mut caller: Caller<'_, Vec<PluginOutput>>,
+                      ptr: i32,
+                      len: i32|
+                      -> wasmtime::Result<()> {
+                    let text = read_string(&mut caller, ptr, len)?;
+                    if let Some(output) = output(text) {
+                        caller.data_mut().push(output);
+                    }
+                    Ok(())
+                },
+            );
+            if let Err(error) = wrapped {
+                eprintln!("Error setting up plugins: {}", error);
+                return Plugins(Vec::new());
+            }
+        }
+
+        let plugins = paths
+            .iter()
+            .filter_map(|path| match Plugin::load(&engine, &linker, path) {
+                Ok(plugin) => Some(plugin),
+                Err(error) => {
+                    eprintln!("Error loading plugin {}: {}", path.display(), error);
+                    None
+                }
+            })
+            .collect();
+        Plugins(plugins)
+    }
+
+    // Give an input event to every plugin and collect what they ask for. Pointer motion comes
+    // hundreds of times a second and isn't passed on.
+    pub fn send(&mut self, event: &InputEvent) -> Vec<PluginOutput> {
+        let moved = match event {
+            InputEvent::Device(event) | InputEvent::Tagged(event, _) => {
+                matches!(event.event_type, rdev::EventType::MouseMove { .. })
+            }
+            _ => false,
+        };
+        if self.0.is_empty() || moved {
+            return Vec::new();
+        }
+        let Ok(json) = serde_json::to_vec(&SessionEvent::from(event.clone())) else {
+            return Vec::new();
+        };
+        let mut outputs = Vec::new();
+        self.0.retain_mut(|plugin| match plugin.send(&json) {
+            Ok(plugin_outputs) => {
+                outputs.extend(plugin_outputs);
+                true
+            }
+            Err(error) => {
+                eprintln!("Plugin {} stopped: {}", plugin.name, error);
+                false
+            }
+        });
+        outputs
+    }
+}
+
+fn read_string(
+    caller: &mut Caller<'_, Vec<PluginOutput>>,
+    ptr: i32,
+    len: i32,
+) -> wasmtime::Result<String> {
+    let memory = caller
+        .get_export("memory")
+        .and_then(|export| export.into_memory())
+        .ok_or_else(|| wasmtime::Error::msg("no exported memory"))?;
+    let start = usize::try_from(ptr)?;
+    let end = start
+        .checked_add(usize::try_from(len)?)
+        .ok_or_else(|| wasmtime::Error::msg("string out of bounds"))?;
+    let bytes = memory
+        .data(&caller)
+        .get(start..end)
+        .ok_or_else(|| wasmtime::Error::msg("string out of bounds"))?;
+    Ok(String::from_utf8_lossy(bytes).into_owned())
+}
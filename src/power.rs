@@ -0,0 +1,70 @@
+use std::time::{Duration, Instant};
+
+// The power source rarely changes; don't query it every frame
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+// Cached answer to "is this machine running on battery?"
+#[derive(Default)]
+pub struct BatteryCheck {
+    checked: Option<Instant>,
+    on_battery: bool,
+}
+
+impl BatteryCheck {
+    pub fn on_battery(&mut self) -> bool {
+        if self
+            .checked
+            .is_none_or(|checked| checked.elapsed() >= CHECK_INTERVAL)
+        {
+            self.on_battery = on_battery();
+            self.checked = Some(Instant::now());
+        }
+        self.on_battery
+    }
+}
+
+// A battery is present and no mains adapter is online. Wireless mice and keyboards list their
+// batteries here too, with a scope of "Device"; only the system's own supplies count
+#[cfg(target_os = "linux")]
+fn on_battery() -> bool {
+    let Ok(supplies) = std::fs::read_dir("/sys/class/power_supply") else {
+        return false;
+    };
+    let read = |path: std::path::PathBuf| std::fs::read_to_string(path).unwrap_or_default();
+
+    let mut battery = false;
+    for supply in supplies.flatten() {
+        let path = supply.path();
+        if read(path.join("scope")).trim() == "Device" {
+            continue;
+        }
+        match read(path.join("type")).trim() {
+            "Mains" if read(path.join("online")).trim() == "1" => return false,
+            "Battery" => battery = true,
+            _ => {}
+        }
+    }
+    battery
+}
+
+#[cfg(windows)]
+fn on_battery() -> bool {
+    use winapi::um::winbase::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
+
+    let mut status: SYSTEM_POWER_STATUS = unsafe { std::mem::zeroed() };
+    // ACLineStatus is 0 offline, 1 online and 255 unknown
+    unsafe { GetSystemPowerStatus(&mut status) != 0 && status.ACLineStatus == 0 }
+}
+
+#[cfg(target_os = "macos")]
+fn on_battery() -> bool {
+    std::process::Command::new("pmset")
+        .args(["-g", "batt"])
+        .output()
+        .is_ok_and(|output| String::from_utf8_lossy(&output.stdout).contains("'Battery Power'"))
+}
+
+#[cfg(not(any(target_os = "linux", windows, target_os = "macos")))]
+fn on_battery() -> bool {
+    false
+}
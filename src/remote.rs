@@ -0,0 +1,171 @@
+use crate::config::RemoteConfig;
+use crate::input::{EventSink, InputBackend, InputEvent, Source};
+use crate::ui::MouseIcon;
+use serde::{Deserialize, Serialize};
+use std::io::{self, Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// Larger frames are treated as a corrupt stream
+const MAX_FRAME: usize = 64 * 1024;
+
+const RECONNECT_DELAY: Duration = Duration::from_secs(2);
+
+const SECRET_TIMEOUT: Duration = Duration::from_secs(5);
+
+// One chip as sent between machines, a big-endian u32 length followed by this as JSON
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RemoteChip {
+    pub text: String,
+    pub mouse: Option<MouseIcon>,
+    pub source: Source,
+    // Updates the previous scroll chip instead of adding one
+    pub continues: bool,
+}
+
+fn write_frame(stream: &mut TcpStream, chip: &RemoteChip) -> io::Result<()> {
+    write_bytes(stream, &serde_json::to_vec(chip)?)
+}
+
+fn read_frame(stream: &mut TcpStream) -> io::Result<RemoteChip> {
+    Ok(serde_json::from_slice(&read_bytes(stream)?)?)
+}
+
+fn write_bytes(stream: &mut TcpStream, body: &[u8]) -> io::Result<()> {
+    stream.write_all(&(body.len() as u32).to_be_bytes())?;
+    stream.write_all(body)
+}
+
+fn read_bytes(stream: &mut TcpStream) -> io::Result<Vec<u8>> {
+    let mut length = [0; 4];
+    stream.read_exact(&mut length)?;
+    let length = u32::from_be_bytes(length) as usize;
+    if length > MAX_FRAME {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("frame of {} bytes", length),
+        ));
+    }
+
+    let mut body = vec![0; length];
+    stream.read_exact(&mut body)?;
+    Ok(body)
+}
+
+// Without a secret, only another instance on this machine may send chips
+fn is_loopback(address: &str) -> bool {
+    address
+        .to_socket_addrs()
+        .is_ok_and(|mut addresses| addresses.all(|address| address.ip().is_loopback()))
+}
+
+// Forwards chips to a receiving instance, reconnecting whenever the connection drops
+pub struct RemoteSender {
+    chips: Sender<RemoteChip>,
+}
+
+impl RemoteSender {
+    pub fn new(config: &RemoteConfig) -> Self {
+        let (chips, queue) = mpsc::channel::<RemoteChip>();
+        let address = config.address.clone();
+        let secret = config.secret.clone();
+
+        thread::spawn(move || {
+            let mut stream: Option<TcpStream> = None;
+            let mut last_attempt: Option<Instant> = None;
+            for chip in queue {
+                // Chips are dropped while the receiver is unreachable
+                if stream.is_none()
+                    && last_attempt.is_none_or(|attempt| attempt.elapsed() >= RECONNECT_DELAY)
+                {
+                    last_attempt = Some(Instant::now());
+                    // The first frame is the shared secret
+                    let connected = TcpStream::connect(&address).and_then(|mut connected| {
+                        connected.set_nodelay(true)?;
+                        write_bytes(&mut connected, secret.as_bytes())?;
+                        Ok(connected)
+                    });
+                    match connected {
+                        Ok(connected) => stream = Some(connected),
+                        Err(error) => {
+                            eprintln!("Error connecting to remote display {}: {}", address, error);
+                        }
+                    }
+                }
+
+                if let Some(connected) = &mut stream
+                    && let Err(error) = write_frame(connected, &chip)
+                {
+                    eprintln!("Lost connection to remote display {}: {}", address, error);
+                    stream = None;
+                }
+            }
+        });
+
+        Self { chips }
+    }
+
+    pub fn send(&self, chip: RemoteChip) {
+        let _ = self.chips.send(chip);
+    }
+}
+
+// Shows the chips of a sending instance, one connection at a time
+pub struct RemoteBackend {
+    pub address: String,
+    pub secret: String,
+}
+
+impl InputBackend for RemoteBackend {
+    fn name(&self) -> &'static str {
+        "remote"
+    }
+
+    fn run(self: Box<Self>, mut sink: EventSink) -> Result<(), String> {
+        if self.secret.is_empty() && !is_loopback(&self.address) {
+            return Err(format!(
+                "set remote.secret to receive chips on {}; without one only 127.0.0.1 is allowed",
+                self.address
+            ));
+        }
+        let listener = TcpListener::bind(&self.address).map_err(|error| error.to_string())?;
+        println!("Waiting for remote chips on {}", self.address);
+
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else {
+                continue;
+            };
+            let peer = stream
+                .peer_addr()
+                .map_or_else(|_| "unknown".to_string(), |peer| peer.to_string());
+            // A client that never sends the secret mustn't hold the listener
+            let _ = stream.set_read_timeout(Some(SECRET_TIMEOUT));
+            let authorized =
+                read_bytes(&mut stream).is_ok_and(|secret| secret == self.secret.as_bytes());
+            let _ = stream.set_read_timeout(None);
+            if !authorized {
+                eprintln!("Remote sender {} sent the wrong secret", peer);
+                continue;
+            }
+            println!("Remote sender {} connected", peer);
+
+            loop {
+                match read_frame(&mut stream) {
+                    Ok(chip) => {
+                        sink(InputEvent::Remote(chip));
+                    }
+                    Err(error) => {
+                        if error.kind() != io::ErrorKind::UnexpectedEof {
+                            eprintln!("Error reading from {}: {}", peer, error);
+                        }
+                        break;
+                    }
+                }
+            }
+            println!("Remote sender {} disconnected", peer);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,472 @@
+// `rusteys render`: the overlay drawn offline from a recorded session, frame by frame with a
+// transparent background, for compositing onto the screen recording in a video editor
+
+use crate::config::{Config, DisplayMode, State};
+use crate::export::{self, ExportEvent};
+use crate::input::Source;
+use crate::keyboard::KeyboardLayout;
+use crate::{KeyPress, MonitorInfo};
+use eframe::egui;
+use std::collections::{HashMap, VecDeque};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+
+// `rusteys render --session <file>... --out <path>` and its options
+pub struct RenderOptions {
+    pub sessions: Vec<String>,
+    pub out: PathBuf,
+    // Video size in pixels
+    pub width: u32,
+    pub height: u32,
+    pub fps: u32,
+    // Pixels per point, 2 for footage of a screen at 200% scaling
+    pub scale: f32,
+    // How long after the recording-start mark the video starts, as for `rusteys export`
+    pub offset_ms: i64,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            sessions: Vec::new(),
+            out: PathBuf::new(),
+            width: 1920,
+            height: 1080,
+            fps: 30,
+            scale: 1.0,
+            offset_ms: 0,
+        }
+    }
+}
+
+pub fn run(options: &RenderOptions) -> Result<(), String> {
+    let events = export::read_events(&options.sessions, options.offset_ms)?;
+    if events.is_empty() {
+        return Err("nothing to render: the session has no key presses".to_string());
+    }
+
+    let mut config = Config::load();
+    // Held keys aren't replayed, so footage always gets the chips
+    config.mode = DisplayMode::Chips;
+    let state = State::load();
+    config.theme = config.theme.resolved(false, egui::Theme::Dark);
+    config.theme.zoom(state.zoom);
+    let animations = config.animations.clone();
+    let fade = animations.fade();
+    let (lanes, max_chips) = (config.lanes, config.max_chips());
+
+    // The panel where it would be on a monitor the size of the video
+    let monitor = MonitorInfo {
+        x: 0.0,
+        y: 0.0,
+        width: options.width as f32,
+        height: options.height as f32,
+        scale: options.scale,
+    };
+    let overlay = monitor.overlay_geometry(&config, &KeyboardLayout::ansi(), state.anchor);
+
+    // Chips are timed as if pressed live: from a base early enough that presses from before the
+    // video starts can still be fading out in its first frames
+    let lead_ms = events
+        .iter()
+        .map(|event| -event.video_ms)
+        .max()
+        .unwrap_or(0)
+        .max(0);
+    let base = Instant::now();
+    let at = |video_ms: i64| base + Duration::from_millis((video_ms + lead_ms).max(0) as u64);
+    let end_ms = events
+        .iter()
+        .map(|event| event.video_ms + (display(event, &config) + fade).as_millis() as i64)
+        .max()
+        .unwrap_or(0)
+        .max(0);
+    let frames = (end_ms as u64 * options.fps as u64).div_ceil(1000);
+
+    let ctx = egui::Context::default();
+    crate::prepare_context(&ctx, &config);
+    let mut canvas = Canvas::new(options.width as usize, options.height as usize);
+    let mut output = Output::open(&options.out, options.width, options.height, options.fps)?;
+    let mut pending = events.iter().peekable();
+    let mut key_presses = VecDeque::new();
+
+    for frame in 0..frames {
+        let video_ms = (frame * 1000 / options.fps as u64) as i64;
+        let now = at(video_ms);
+        while let Some(event) = pending.next_if(|event| event.video_ms <= video_ms) {
+            let key_press = KeyPress {
+                text: event.text(),
+                timestamp: at(event.video_ms),
+                mouse: None,
+                source: match event.category {
+                    "mouse" | "pointer-chip" => Source::Pointer,
+                    _ => Source::Keyboard,
+                },
+                display: display(event, &config),
+                color: None,
+                icon: None,
+            };
+            crate::push_key_press(&mut key_presses, key_press, lanes, max_chips);
+        }
+        key_presses
+            .retain(|key_press| now.duration_since(key_press.timestamp) < key_press.display + fade);
+
+        let mut input = egui::RawInput {
+            screen_rect: Some(egui::Rect::from_min_size(
+                egui::Pos2::ZERO,
+                egui::vec2(options.width as f32, options.height as f32) / options.scale,
+            )),
+            time: Some(video_ms as f64 / 1000.0),
+            predicted_dt: 1.0 / options.fps as f32,
+            ..Default::default()
+        };
+        input.viewports.insert(
+            egui::ViewportId::ROOT,
+            egui::ViewportInfo {
+                native_pixels_per_point: Some(options.scale),
+                ..Default::default()
+            },
+        );
+        let full_output = ctx.run(input, |ctx| {
+            egui::Area::new(egui::Id::new("render_overlay"))
+                .fixed_pos(overlay.min)
+                .show(ctx, |ui| {
+                    let panel = crate::panel_frame(&config, false);
+                    panel.show(ui, |ui| {
+                        let size = overlay.size() - panel.inner_margin.sum();
+                        ui.set_min_size(size);
+                        ui.set_max_size(size);
+                        crate::render_chips(ui, &key_presses, now, &animations, &config);
+                    });
+                });
+        });
+
+        canvas.update_textures(&full_output.textures_delta);
+        let primitives = ctx.tessellate(full_output.shapes, full_output.pixels_per_point);
+        canvas.paint(&primitives, full_output.pixels_per_point);
+        output.write(&canvas.rgba(), frame)?;
+        canvas.free_textures(&full_output.textures_delta);
+    }
+
+    output.finish()?;
+    println!(
+        "Rendered {} frames ({:.1} s) to {}",
+        frames,
+        frames as f64 / options.fps as f64,
+        options.out.display()
+    );
+    Ok(())
+}
+
+// How long the overlay shows a press of this kind
+fn display(event: &ExportEvent, config: &Config) -> Duration {
+    let durations = &config.durations;
+    Duration::from_millis(match event.category {
+        "shortcut" => durations.shortcut_ms,
+        "key" => durations.key_ms,
+        "mouse" => durations.mouse_ms,
+        _ => durations.other_ms,
+    })
+}
+
+// Where the frames go: ffmpeg for a video file, or numbered PNGs in a folder
+enum Output {
+    Video(Child),
+    Frames {
+        dir: PathBuf,
+        width: u32,
+        height: u32,
+    },
+}
+
+impl Output {
+    fn open(path: &Path, width: u32, height: u32, fps: u32) -> Result<Self, String> {
+        // Both codecs keep the alpha channel, which most others drop
+        let codec: &[&str] = match path.extension().and_then(|extension| extension.to_str()) {
+            None => {
+                std::fs::create_dir_all(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+                return Ok(Output::Frames {
+                    dir: path.to_path_buf(),
+                    width,
+                    height,
+                });
+            }
+            Some("mov") => &[
+                "-c:v",
+                "prores_ks",
+                "-profile:v",
+                "4444",
+                "-pix_fmt",
+                "yuva444p10le",
+            ],
+            Some("webm") => &["-c:v", "libvpx-vp9", "-pix_fmt", "yuva420p"],
+            Some(other) => {
+                return Err(format!(
+                    "can't render to .{}: use .mov (ProRes 4444), .webm (VP9) or a folder for PNG frames",
+                    other
+                ));
+            }
+        };
+        let size = format!("{}x{}", width, height);
+        let fps = fps.to_string();
+        Command::new("ffmpeg")
+            .args(["-loglevel", "error", "-y"])
+            .args([
+                "-f", "rawvideo", "-pix_fmt", "rgba", "-s", &size, "-r", &fps,
+            ])
+            .args(["-i", "-"])
+            .args(codec)
+            .arg(path)
+            .stdin(Stdio::piped())
+            .spawn()
+            .map(Output::Video)
+            .map_err(|e| format!("could not start ffmpeg, which encodes the video: {}", e))
+    }
+
+    // One frame of straight (not premultiplied) RGBA bytes
+    fn write(&mut self, rgba: &[u8], frame: u64) -> Result<(), String> {
+        match self {
+            Output::Video(ffmpeg) => ffmpeg
+                .stdin
+                .as_mut()
+                .ok_or("ffmpeg has no input")?
+                .write_all(rgba)
+                .map_err(|e| format!("ffmpeg stopped: {}", e)),
+            Output::Frames { dir, width, height } => {
+                let path = dir.join(format!("frame-{:06}.png", frame + 1));
+                image::save_buffer(
+                    &path,
+                    rgba,
+                    *width,
+                    *height,
+                    image::ExtendedColorType::Rgba8,
+                )
+                .map_err(|e| format!("{}: {}", path.display(), e))
+            }
+        }
+    }
+
+    fn finish(self) -> Result<(), String> {
+        match self {
+            Output::Video(mut ffmpeg) => {
+                // Closing its input lets ffmpeg finish the file
+                drop(ffmpeg.stdin.take());
+                let status = ffmpeg.wait().map_err(|e| e.to_string())?;
+                if status.success() {
+                    Ok(())
+                } else {
+                    Err(format!("ffmpeg failed ({})", status))
+                }
+            }
+            Output::Frames { .. } => Ok(()),
+        }
+    }
+}
+
+struct Texture {
+    size: [usize; 2],
+    pixels: Vec<egui::Color32>,
+}
+
+// A software renderer for what egui tessellates, so rendering needs no GPU or window. Colors
+// stay premultiplied and in gamma space, blended the way eframe's renderers do.
+struct Canvas {
+    width: usize,
+    height: usize,
+    pixels: Vec<[f32; 4]>,
+    textures: HashMap<egui::TextureId, Texture>,
+}
+
+impl Canvas {
+    fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![[0.0; 4]; width * height],
+            textures: HashMap::new(),
+        }
+    }
+
+    fn update_textures(&mut self, delta: &egui::TexturesDelta) {
+        for (id, delta) in &delta.set {
+            let egui::ImageData::Color(image) = &delta.image;
+            match delta.pos {
+                None => {
+                    self.textures.insert(
+                        *id,
+                        Texture {
+                            size: image.size,
+                            pixels: image.pixels.clone(),
+                        },
+                    );
+                }
+                // Part of the font atlas, as new glyphs are drawn
+                Some([x, y]) => {
+                    if let Some(texture) = self.textures.get_mut(id) {
+                        let [width, height] = image.size;
+                        for row in 0..height {
+                            let start = (y + row) * texture.size[0] + x;
+                            texture.pixels[start..start + width]
+                                .copy_from_slice(&image.pixels[row * width..(row + 1) * width]);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn free_textures(&mut self, delta: &egui::TexturesDelta) {
+        for id in &delta.free {
+            self.textures.remove(id);
+        }
+    }
+
+    fn paint(&mut self, primitives: &[egui::ClippedPrimitive], pixels_per_point: f32) {
+        self.pixels.fill([0.0; 4]);
+        for primitive in primitives {
+            let egui::epaint::Primitive::Mesh(mesh) = &primitive.primitive else {
+                continue;
+            };
+            let Some(texture) = self.textures.get(&mesh.texture_id) else {
+                continue;
+            };
+            let clip = primitive.clip_rect;
+            let pixel = |value: f32| (value * pixels_per_point).round().max(0.0) as usize;
+            let clip = [
+                pixel(clip.min.x),
+                pixel(clip.min.y),
+                pixel(clip.max.x).min(self.width),
+                pixel(clip.max.y).min(self.height),
+            ];
+            for triangle in mesh.indices.chunks_exact(3) {
+                let vertices = [0, 1, 2].map(|corner| &mesh.vertices[triangle[corner] as usize]);
+                fill_triangle(
+                    &mut self.pixels,
+                    self.width,
+                    vertices,
+                    pixels_per_point,
+                    clip,
+                    texture,
+                );
+            }
+        }
+    }
+
+    fn rgba(&self) -> Vec<u8> {
+        self.pixels
+            .iter()
+            .flat_map(|&[r, g, b, a]| {
+                let straight = |value: f32| {
+                    let value = if a > 0.0 { value / a } else { 0.0 };
+                    (value.clamp(0.0, 1.0) * 255.0).round() as u8
+                };
+                [
+                    straight(r),
+                    straight(g),
+                    straight(b),
+                    (a.clamp(0.0, 1.0) * 255.0).round() as u8,
+                ]
+            })
+            .collect()
+    }
+}
+
+// Twice the signed area of a, b, p; which side of the edge a → b the point is on
+fn edge(a: egui::Pos2, b: egui::Pos2, p: egui::Pos2) -> f32 {
+    (b.x - a.x) * (p.y - a.y) - (b.y - a.y) * (p.x - a.x)
+}
+
+fn fill_triangle(
+    pixels: &mut [[f32; 4]],
+    width: usize,
+    mut vertices: [&egui::epaint::Vertex; 3],
+    pixels_per_point: f32,
+    clip: [usize; 4],
+    texture: &Texture,
+) {
+    let mut points = vertices.map(|vertex| (vertex.pos.to_vec2() * pixels_per_point).to_pos2());
+    let mut area = edge(points[0], points[1], points[2]);
+    if area.abs() < f32::EPSILON {
+        return;
+    }
+    // One winding for all triangles, so two sharing an edge walk it in opposite directions
+    if area < 0.0 {
+        vertices.swap(1, 2);
+        points.swap(1, 2);
+        area = -area;
+    }
+    // Pixels exactly on a shared edge belong to one of the triangles, or translucent fills would
+    // show their seams
+    let owns = |a: egui::Pos2, b: egui::Pos2, weight: f32| {
+        weight > 0.0 || (weight == 0.0 && (b.y > a.y || (b.y == a.y && b.x < a.x)))
+    };
+
+    let min = points
+        .iter()
+        .fold(egui::pos2(f32::MAX, f32::MAX), |min, p| min.min(*p));
+    let max = points
+        .iter()
+        .fold(egui::pos2(f32::MIN, f32::MIN), |max, p| max.max(*p));
+    let x0 = (min.x.floor().max(0.0) as usize).max(clip[0]);
+    let y0 = (min.y.floor().max(0.0) as usize).max(clip[1]);
+    let x1 = (max.x.ceil().max(0.0) as usize).min(clip[2]);
+    let y1 = (max.y.ceil().max(0.0) as usize).min(clip[3]);
+
+    for y in y0..y1 {
+        for x in x0..x1 {
+            let center = egui::pos2(x as f32 + 0.5, y as f32 + 0.5);
+            let weights = [
+                edge(points[1], points[2], center),
+                edge(points[2], points[0], center),
+                edge(points[0], points[1], center),
+            ];
+            if !(owns(points[1], points[2], weights[0])
+                && owns(points[2], points[0], weights[1])
+                && owns(points[0], points[1], weights[2]))
+            {
+                continue;
+            }
+            let weights = weights.map(|weight| weight / area);
+
+            let mut uv = egui::Vec2::ZERO;
+            let mut color = [0.0; 4];
+            for (vertex, weight) in vertices.iter().zip(weights) {
+                uv += vertex.uv.to_vec2() * weight;
+                for (channel, value) in color.iter_mut().zip(vertex.color.to_array()) {
+                    *channel += value as f32 / 255.0 * weight;
+                }
+            }
+            let sample = sample(texture, uv);
+            let source: [f32; 4] = std::array::from_fn(|i| color[i] * sample[i]);
+
+            let target = &mut pixels[y * width + x];
+            for i in 0..4 {
+                target[i] = source[i] + target[i] * (1.0 - source[3]);
+            }
+        }
+    }
+}
+
+// Bilinear lookup, premultiplied RGBA in 0..1
+fn sample(texture: &Texture, uv: egui::Vec2) -> [f32; 4] {
+    let [width, height] = texture.size;
+    let x = (uv.x * width as f32 - 0.5).clamp(0.0, (width - 1) as f32);
+    let y = (uv.y * height as f32 - 0.5).clamp(0.0, (height - 1) as f32);
+    let (left, top) = (x.floor() as usize, y.floor() as usize);
+    let (right, bottom) = ((left + 1).min(width - 1), (top + 1).min(height - 1));
+    let (dx, dy) = (x - left as f32, y - top as f32);
+    let texel = |x: usize, y: usize| texture.pixels[y * width + x].to_array();
+    let (a, b, c, d) = (
+        texel(left, top),
+        texel(right, top),
+        texel(left, bottom),
+        texel(right, bottom),
+    );
+    std::array::from_fn(|i| {
+        let upper = a[i] as f32 * (1.0 - dx) + b[i] as f32 * dx;
+        let lower = c[i] as f32 * (1.0 - dx) + d[i] as f32 * dx;
+        (upper * (1.0 - dy) + lower * dy) / 255.0
+    })
+}
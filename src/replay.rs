@@ -0,0 +1,172 @@
+use crate::config::{ReplayConfig, ReplayFormat};
+use eframe::egui;
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+// Shortest a frame is shown for, also for the last one
+const MIN_DELAY: Duration = Duration::from_millis(20);
+
+// The last few seconds of the panel as drawn, for `export-replay`. Frames are only captured
+// when the UI repaints, so an idle panel costs nothing; how long each one stayed on screen
+// comes from the time until the next.
+#[derive(Default)]
+pub struct Replay {
+    frames: VecDeque<(Instant, Arc<egui::ColorImage>)>,
+    last_request: Option<Instant>,
+}
+
+impl Replay {
+    // Whether to ask for a picture of the frame drawn now
+    pub fn wants_frame(&mut self, config: &ReplayConfig, now: Instant) -> bool {
+        if !config.enabled {
+            self.frames.clear();
+            return false;
+        }
+        let interval = Duration::from_secs_f32(1.0 / config.fps.max(1) as f32);
+        if self
+            .last_request
+            .is_some_and(|last| now.duration_since(last) < interval)
+        {
+            return false;
+        }
+        self.last_request = Some(now);
+        true
+    }
+
+    pub fn push(&mut self, image: Arc<egui::ColorImage>, config: &ReplayConfig, now: Instant) {
+        // An animation has one size throughout, so a resized panel starts over
+        if self
+            .frames
+            .back()
+            .is_some_and(|(_, last)| last.size != image.size)
+        {
+            self.frames.clear();
+        }
+        self.frames.push_back((now, image));
+        let kept = Duration::from_secs(config.seconds);
+        while self
+            .frames
+            .front()
+            .is_some_and(|(time, _)| now.duration_since(*time) > kept)
+        {
+            self.frames.pop_front();
+        }
+    }
+
+    // Encode the buffer on a background thread; the path's extension picks the format, or the
+    // configured one when there is none
+    pub fn export(&self, path: PathBuf, format: ReplayFormat) -> Result<(), String> {
+        if self.frames.is_empty() {
+            return Err("nothing recorded yet; is [replay] enabled?".to_string());
+        }
+        let now = Instant::now();
+        let frames: Vec<(Duration, Arc<egui::ColorImage>)> = self
+            .frames
+            .iter()
+            .zip(
+                self.frames
+                    .iter()
+                    .skip(1)
+                    .map(|(time, _)| *time)
+                    .chain([now]),
+            )
+            .map(|((time, image), next)| {
+                (next.duration_since(*time).max(MIN_DELAY), Arc::clone(image))
+            })
+            .collect();
+        let format = match path.extension().and_then(|extension| extension.to_str()) {
+            Some("gif") => ReplayFormat::Gif,
+            Some("png" | "apng") => ReplayFormat::Apng,
+            _ => format,
+        };
+
+        thread::spawn(move || {
+            let result = match format {
+                ReplayFormat::Gif => encode_gif(&frames, &path),
+                ReplayFormat::Apng => encode_apng(&frames, &path),
+            };
+            match result {
+                Ok(()) => println!("Saved replay to {}", path.display()),
+                Err(error) => eprintln!("Error saving replay to {}: {}", path.display(), error),
+            }
+        });
+        Ok(())
+    }
+}
+
+// "screenshots/rusteys-20240501-123456.gif"
+pub fn default_path(dir: &Path, format: ReplayFormat) -> PathBuf {
+    let extension = match format {
+        ReplayFormat::Gif => "gif",
+        ReplayFormat::Apng => "png",
+    };
+    dir.join(format!(
+        "rusteys-{}.{}",
+        chrono::Local::now().format("%Y%m%d-%H%M%S"),
+        extension
+    ))
+}
+
+// Straight (not premultiplied) RGBA bytes
+fn rgba(image: &egui::ColorImage) -> Vec<u8> {
+    image
+        .pixels
+        .iter()
+        .flat_map(|color| color.to_srgba_unmultiplied())
+        .collect()
+}
+
+fn create(path: &Path) -> Result<BufWriter<File>, String> {
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    File::create(path)
+        .map(BufWriter::new)
+        .map_err(|e| e.to_string())
+}
+
+// GIF pixels are either transparent or opaque, so a translucent panel comes out solid
+fn encode_gif(frames: &[(Duration, Arc<egui::ColorImage>)], path: &Path) -> Result<(), String> {
+    use image::codecs::gif::{GifEncoder, Repeat};
+
+    let mut encoder = GifEncoder::new_with_speed(create(path)?, 10);
+    encoder
+        .set_repeat(Repeat::Infinite)
+        .map_err(|e| e.to_string())?;
+    for (delay, image) in frames {
+        let [width, height] = image.size;
+        let buffer = image::RgbaImage::from_raw(width as u32, height as u32, rgba(image))
+            .ok_or("image size doesn't match its pixels")?;
+        let frame =
+            image::Frame::from_parts(buffer, 0, 0, image::Delay::from_saturating_duration(*delay));
+        encoder.encode_frame(frame).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+// Animated PNG keeps the panel's translucency; browsers and most chat apps play it
+fn encode_apng(frames: &[(Duration, Arc<egui::ColorImage>)], path: &Path) -> Result<(), String> {
+    let [width, height] = frames[0].1.size;
+    let mut encoder = png::Encoder::new(create(path)?, width as u32, height as u32);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder
+        .set_animated(frames.len() as u32, 0)
+        .map_err(|e| e.to_string())?;
+    let mut writer = encoder.write_header().map_err(|e| e.to_string())?;
+    for (delay, image) in frames {
+        let millis = delay.as_millis().min(u16::MAX as u128) as u16;
+        writer
+            .set_frame_delay(millis, 1000)
+            .map_err(|e| e.to_string())?;
+        writer
+            .write_image_data(&rgba(image))
+            .map_err(|e| e.to_string())?;
+    }
+    writer.finish().map_err(|e| e.to_string())
+}
@@ -0,0 +1,56 @@
+use eframe::egui;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+// A `screenshot` or `export-replay` action waiting for the UI thread
+pub struct Request {
+    // Saved here instead of the configured folder
+    pub path: Option<PathBuf>,
+}
+
+// The overlay image eframe delivered for an earlier `ViewportCommand::Screenshot`, if it is in
+// this frame's events
+pub fn received(ctx: &egui::Context) -> Option<Arc<egui::ColorImage>> {
+    ctx.input(|i| {
+        i.raw.events.iter().find_map(|event| match event {
+            egui::Event::Screenshot { image, .. } => Some(Arc::clone(image)),
+            _ => None,
+        })
+    })
+}
+
+// Whether this frame only happens because an image arrived, which changes nothing on screen
+pub fn only_received(ctx: &egui::Context) -> bool {
+    ctx.input(|i| {
+        !i.raw.events.is_empty()
+            && i.raw
+                .events
+                .iter()
+                .all(|event| matches!(event, egui::Event::Screenshot { .. }))
+    })
+}
+
+// "screenshots/rusteys-20240501-123456.png"
+pub fn default_path(dir: &Path) -> PathBuf {
+    dir.join(format!(
+        "rusteys-{}.png",
+        chrono::Local::now().format("%Y%m%d-%H%M%S")
+    ))
+}
+
+// PNG with the panel's transparency kept, so it can be placed over anything
+pub fn save(image: &egui::ColorImage, path: &Path) -> Result<(), String> {
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    // egui colors are premultiplied; PNG wants them straight
+    let pixels = image
+        .pixels
+        .iter()
+        .flat_map(|color| color.to_srgba_unmultiplied())
+        .collect();
+    let [width, height] = image.size;
+    let image = image::RgbaImage::from_raw(width as u32, height as u32, pixels)
+        .ok_or("image size doesn't match its pixels")?;
+    image.save(path).map_err(|e| e.to_string())
+}
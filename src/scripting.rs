@@ -0,0 +1,117 @@
+use crate::focus;
+use crate::input::Source;
+use crate::{KeyPress, Modifiers};
+use eframe::egui;
+use rhai::{AST, Dynamic, Engine, Map, Scope};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+// `format` runs on the input thread for every chip, so a script that loops forever or recurses
+// without end is stopped there and reported like any other error
+const MAX_OPERATIONS: u64 = 1_000_000;
+const MAX_CALL_LEVELS: usize = 32;
+
+// A rhai script from the config's `script`, whose `format(event)` decides how each chip looks:
+//
+//     fn format(event) {
+//         if event.ctrl && event.text.ends_with("S") { return #{ text: "💾 Save", color: "#2E7D32" }; }
+//         if event.app == "Spotify" { return #{ hide: true }; }
+//     }
+//
+// `event` has `text`, `group` ("keyboard" or "pointer"), `mouse`, the held modifiers `ctrl`,
+// `shift`, `alt`, `altgr` and `meta`, and `app`, the focused application (or `()`). Returning
+// nothing keeps the chip as it is, a string replaces its text, and a map may set `text`,
+// `color` ("#RRGGBB"), `group`, `display_ms` and `hide`.
+pub struct Script {
+    engine: Engine,
+    ast: AST,
+    // Errors are reported once, not for every key
+    reported: AtomicBool,
+}
+
+impl Script {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+        engine.set_max_call_levels(MAX_CALL_LEVELS);
+        let ast = engine
+            .compile_file(path.to_path_buf())
+            .map_err(|e| format!("{}: {}", path.display(), e))?;
+        Ok(Self {
+            engine,
+            ast,
+            reported: AtomicBool::new(false),
+        })
+    }
+
+    // Restyle a chip; false when the script hides it
+    pub fn apply(&self, key_press: &mut KeyPress, modifiers: &Modifiers) -> bool {
+        let mut event = Map::new();
+        event.insert("text".into(), key_press.text.clone().into());
+        event.insert("group".into(), group_name(key_press.source).into());
+        event.insert("mouse".into(), key_press.mouse.is_some().into());
+        event.insert("ctrl".into(), modifiers.ctrl.into());
+        event.insert("shift".into(), modifiers.shift.into());
+        event.insert("alt".into(), modifiers.alt.into());
+        event.insert("altgr".into(), modifiers.altgr.into());
+        event.insert("meta".into(), modifiers.meta.into());
+        event.insert(
+            "app".into(),
+            focus::focused_app().map_or(Dynamic::UNIT, Dynamic::from),
+        );
+
+        let result =
+            self.engine
+                .call_fn::<Dynamic>(&mut Scope::new(), &self.ast, "format", (event,));
+        let spec = match result {
+            Ok(spec) => spec,
+            Err(error) => {
+                if !self.reported.swap(true, Ordering::Relaxed) {
+                    eprintln!("Error in chip script: {}", error);
+                }
+                return true;
+            }
+        };
+
+        if spec.is_string() {
+            key_press.text = spec.into_string().unwrap_or_default();
+            return true;
+        }
+        let Some(spec) = spec.try_cast::<Map>() else {
+            return true;
+        };
+        let field = |name: &str| spec.get(name).cloned();
+        if field("hide").and_then(|hide| hide.as_bool().ok()) == Some(true) {
+            return false;
+        }
+        if let Some(text) = field("text").and_then(|text| text.into_string().ok()) {
+            key_press.text = text;
+        }
+        if let Some(color) = field("color").and_then(|color| color.into_string().ok()) {
+            match egui::Color32::from_hex(&color) {
+                Ok(color) => key_press.color = Some(color),
+                Err(_) => eprintln!("Invalid color `{}` from chip script", color),
+            }
+        }
+        match field("group")
+            .and_then(|group| group.into_string().ok())
+            .as_deref()
+        {
+            Some("keyboard") => key_press.source = Source::Keyboard,
+            Some("pointer") => key_press.source = Source::Pointer,
+            _ => {}
+        }
+        if let Some(display_ms) = field("display_ms").and_then(|ms| ms.as_int().ok()) {
+            key_press.display = Duration::from_millis(display_ms.max(0) as u64);
+        }
+        true
+    }
+}
+
+fn group_name(source: Source) -> &'static str {
+    match source {
+        Source::Keyboard => "keyboard",
+        Source::Pointer => "pointer",
+    }
+}
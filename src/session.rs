@@ -0,0 +1,152 @@
+use crate::errors::BackendErrors;
+use crate::input::{self, DeviceInfo, EventSink, InputBackend, InputEvent, Source};
+use crate::paths;
+use crate::remote::RemoteChip;
+use crate::ui::Repaint;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+// Serializable mirror of `InputEvent`
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SessionEvent {
+    Device(rdev::EventType),
+    Tagged(rdev::EventType, DeviceInfo),
+    Chip(String, Source),
+    Remote(RemoteChip),
+    // A named point in time, like the start of a screen recording; not replayed
+    Mark(String),
+}
+
+impl From<InputEvent> for SessionEvent {
+    fn from(event: InputEvent) -> Self {
+        match event {
+            InputEvent::Device(event) => SessionEvent::Device(event.event_type),
+            InputEvent::Tagged(event, device) => SessionEvent::Tagged(event.event_type, device),
+            InputEvent::Chip(text, source) => SessionEvent::Chip(text, source),
+            InputEvent::Remote(chip) => SessionEvent::Remote(chip),
+        }
+    }
+}
+
+impl SessionEvent {
+    // What a replay feeds to the overlay; None for marks
+    fn into_input(self) -> Option<InputEvent> {
+        let event = match self {
+            SessionEvent::Device(event_type) => InputEvent::Device(rdev::Event {
+                time: SystemTime::now(),
+                name: None,
+                event_type,
+            }),
+            SessionEvent::Tagged(event_type, device) => InputEvent::Tagged(
+                rdev::Event {
+                    time: SystemTime::now(),
+                    name: None,
+                    event_type,
+                },
+                device,
+            ),
+            SessionEvent::Chip(text, source) => InputEvent::Chip(text, source),
+            SessionEvent::Remote(chip) => InputEvent::Remote(chip),
+            SessionEvent::Mark(_) => return None,
+        };
+        Some(event)
+    }
+}
+
+// One line of a session file (JSON Lines)
+#[derive(Serialize, Deserialize)]
+pub struct SessionRecord {
+    // Monotonic time since the recording started
+    pub elapsed_ms: u64,
+    // Milliseconds since the Unix epoch; not in sessions from older versions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_ms: Option<u64>,
+    pub event: SessionEvent,
+}
+
+// Write every input event to `path` until the process is stopped
+pub fn record(path: &Path, backends: Vec<Box<dyn InputBackend>>) -> Result<(), String> {
+    // Every key typed, so only for this user's eyes
+    let file = paths::create_private(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let writer = Arc::new(Mutex::new(BufWriter::new(file)));
+    let started = Instant::now();
+
+    for backend in backends {
+        let writer = Arc::clone(&writer);
+        input::spawn(
+            backend,
+            Box::new(move |event| {
+                let record = SessionRecord {
+                    elapsed_ms: started.elapsed().as_millis() as u64,
+                    time_ms: SystemTime::now()
+                        .duration_since(UNIX_EPOCH)
+                        .ok()
+                        .map(|time| time.as_millis() as u64),
+                    event: event.into(),
+                };
+                // Flush every line so stopping with Ctrl+C loses nothing
+                let mut writer = writer.lock();
+                let result = serde_json::to_writer(&mut *writer, &record)
+                    .map_err(|e| e.to_string())
+                    .and_then(|()| writeln!(writer).map_err(|e| e.to_string()))
+                    .and_then(|()| writer.flush().map_err(|e| e.to_string()));
+                if let Err(error) = result {
+                    eprintln!("Error writing session file: {}", error);
+                }
+                false
+            }),
+            BackendErrors::default(),
+            Repaint::default(),
+        );
+    }
+
+    println!("Recording to {}; press Ctrl+C to stop", path.display());
+    loop {
+        thread::park();
+    }
+}
+
+// Feeds a recorded session into the overlay with its original timing
+pub struct ReplayBackend {
+    pub path: PathBuf,
+    // 2.0 plays twice as fast
+    pub speed: f64,
+}
+
+impl InputBackend for ReplayBackend {
+    fn name(&self) -> &'static str {
+        "replay"
+    }
+
+    fn run(self: Box<Self>, mut sink: EventSink) -> Result<(), String> {
+        let file = File::open(&self.path).map_err(|e| e.to_string())?;
+        let started = Instant::now();
+
+        for (number, line) in BufReader::new(file).lines().enumerate() {
+            let line = line.map_err(|e| e.to_string())?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let record: SessionRecord =
+                serde_json::from_str(&line).map_err(|e| format!("line {}: {}", number + 1, e))?;
+
+            let due = Duration::from_secs_f64(record.elapsed_ms as f64 / 1000.0 / self.speed);
+            if let Some(wait) = due.checked_sub(started.elapsed()) {
+                thread::sleep(wait);
+            }
+            if let Some(event) = record.event.into_input() {
+                sink(event);
+            }
+        }
+
+        println!("Replay of {} finished", self.path.display());
+        Ok(())
+    }
+}
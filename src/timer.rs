@@ -0,0 +1,150 @@
+use crate::chip_alpha;
+use crate::config::{AnimationConfig, ThemeConfig};
+use crate::ui;
+use eframe::egui;
+use std::time::{Duration, Instant};
+
+// Countdowns this close to the end are highlighted
+const FINAL_SECONDS: Duration = Duration::from_secs(10);
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum TimerKind {
+    Countdown(Duration),
+    Stopwatch,
+}
+
+// Countdown or stopwatch shown in the panel, for timed demos and exams
+pub struct Timer {
+    kind: TimerKind,
+    started: Instant,
+    // A stopped stopwatch keeps its time until it fades out
+    stopped: Option<Instant>,
+}
+
+impl Timer {
+    pub fn new(kind: TimerKind) -> Self {
+        Self {
+            kind,
+            started: Instant::now(),
+            stopped: None,
+        }
+    }
+
+    pub fn is_running_stopwatch(&self) -> bool {
+        self.kind == TimerKind::Stopwatch && self.stopped.is_none()
+    }
+
+    pub fn stop(&mut self) {
+        self.stopped.get_or_insert_with(Instant::now);
+    }
+
+    // When it stopped counting: stopped by hand, or a countdown reaching zero
+    fn finished_at(&self) -> Option<Instant> {
+        match self.kind {
+            TimerKind::Countdown(duration) => {
+                let end = self.started + duration;
+                Some(self.stopped.map_or(end, |stopped| stopped.min(end)))
+            }
+            TimerKind::Stopwatch => self.stopped,
+        }
+    }
+
+    // "4:59" left or "1:02:03" elapsed; countdowns round up, so they show 0:00 only at the end
+    pub fn text(&self, now: Instant) -> String {
+        let now = self.stopped.map_or(now, |stopped| stopped.min(now));
+        let elapsed = now.saturating_duration_since(self.started);
+        let seconds = match self.kind {
+            TimerKind::Countdown(duration) => {
+                duration.saturating_sub(elapsed).as_millis().div_ceil(1000) as u64
+            }
+            TimerKind::Stopwatch => elapsed.as_secs(),
+        };
+        format_duration(Duration::from_secs(seconds))
+    }
+
+    // Fully shown while counting, then shown for `display` and faded like a chip
+    pub fn alpha(&self, now: Instant, display: Duration, animations: &AnimationConfig) -> u8 {
+        match self.finished_at() {
+            Some(finished) if finished <= now => chip_alpha(now - finished, display, animations),
+            _ => 255,
+        }
+    }
+
+    // Finished, shown and faded out
+    pub fn is_gone(&self, now: Instant, display: Duration, animations: &AnimationConfig) -> bool {
+        self.finished_at()
+            .is_some_and(|finished| now >= finished + display + animations.fade())
+    }
+
+    // Time until it next looks different: the next second while counting, then the fade
+    pub fn next_change(&self, now: Instant, display: Duration) -> Duration {
+        match self.finished_at() {
+            Some(finished) if finished <= now => {
+                (finished + display).saturating_duration_since(now)
+            }
+            _ => {
+                let into_second = now.saturating_duration_since(self.started).subsec_millis();
+                Duration::from_millis(1000 - into_second as u64)
+            }
+        }
+    }
+
+    // Countdown in its last seconds or over
+    fn is_urgent(&self, now: Instant) -> bool {
+        match self.kind {
+            TimerKind::Countdown(duration) => {
+                (self.started + duration).saturating_duration_since(now) <= FINAL_SECONDS
+            }
+            TimerKind::Stopwatch => false,
+        }
+    }
+}
+
+// Draw the timer at the panel's left edge with the theme's chip colors
+pub fn paint(ui: &egui::Ui, timer: &Timer, theme: &ThemeConfig, alpha: u8, now: Instant) {
+    let fade = alpha as f32 / 255.0;
+    let (fill, text) = if timer.is_urgent(now) {
+        (theme.palette.accent().0, egui::Color32::WHITE)
+    } else {
+        let (fill, _, text) = theme.idle_colors();
+        (fill, text)
+    };
+    ui::badge(
+        ui,
+        ui.max_rect().left_center(),
+        egui::Align2::LEFT_CENTER,
+        &timer.text(now),
+        theme.font_size,
+        fill.gamma_multiply(fade),
+        text.gamma_multiply(fade),
+    );
+}
+
+// "90", "1:30" or "1:00:00"
+pub fn parse_duration(text: &str) -> Result<Duration, String> {
+    let mut seconds = 0u64;
+    for part in text.split(':') {
+        let value: u64 = part.trim().parse().map_err(|_| {
+            format!(
+                "invalid duration `{}`, expected seconds, m:ss or h:mm:ss",
+                text
+            )
+        })?;
+        seconds = seconds * 60 + value;
+    }
+    Ok(Duration::from_secs(seconds))
+}
+
+pub fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    if seconds >= 3600 {
+        format!(
+            "{}:{:02}:{:02}",
+            seconds / 3600,
+            seconds / 60 % 60,
+            seconds % 60
+        )
+    } else {
+        format!("{}:{:02}", seconds / 60, seconds % 60)
+    }
+}
@@ -0,0 +1,192 @@
+use crate::gestures::direction_arrow;
+use crate::hotplug;
+use crate::input::{EventSink, InputBackend, InputEvent, Source};
+use eframe::egui;
+use evdev::{AbsoluteAxisType, InputEventKind, PropType};
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+// Contacts shorter than this that barely move are taps
+const TAP_DURATION: Duration = Duration::from_millis(250);
+
+// Movement, as a fraction of the surface width, that makes a gesture
+const MOVE_THRESHOLD: f32 = 0.05;
+
+// Touchscreens and touchpads read from evdev multitouch slots (Linux only)
+pub struct TouchBackend;
+
+impl InputBackend for TouchBackend {
+    fn name(&self) -> &'static str {
+        "touch"
+    }
+
+    fn run(self: Box<Self>, sink: EventSink) -> Result<(), String> {
+        hotplug::read_devices("multitouch", is_touch_surface, read_surface, sink)
+    }
+}
+
+fn is_touch_surface(device: &evdev::Device) -> bool {
+    device
+        .supported_absolute_axes()
+        .is_some_and(|axes| axes.contains(AbsoluteAxisType::ABS_MT_SLOT))
+}
+
+#[derive(Clone, Copy)]
+struct Contact {
+    // Unknown until the report that put the finger down is complete
+    start: Option<egui::Pos2>,
+    end: egui::Pos2,
+}
+
+// Contacts from first finger down to last finger up
+#[derive(Default)]
+struct Gesture {
+    started: Option<Instant>,
+    active: HashMap<usize, Contact>,
+    lifted: Vec<Contact>,
+    max_fingers: usize,
+}
+
+impl Gesture {
+    // Name the finished gesture, if it is worth a chip
+    fn classify(&self, width: f32, direct: bool) -> Option<String> {
+        // (start, end) of every finger that took part
+        let contacts: Vec<(egui::Pos2, egui::Pos2)> = self
+            .lifted
+            .iter()
+            .chain(self.active.values())
+            .filter_map(|contact| Some((contact.start?, contact.end)))
+            .collect();
+        if contacts.is_empty() {
+            return None;
+        }
+
+        let count = contacts.len() as f32;
+        let start_centroid = contacts
+            .iter()
+            .map(|(start, _)| start.to_vec2())
+            .fold(egui::Vec2::ZERO, |a, b| a + b)
+            / count;
+        let end_centroid = contacts
+            .iter()
+            .map(|(_, end)| end.to_vec2())
+            .fold(egui::Vec2::ZERO, |a, b| a + b)
+            / count;
+        let movement = (end_centroid - start_centroid) / width;
+        let moved = movement.length() >= MOVE_THRESHOLD;
+        let quick = self
+            .started
+            .is_some_and(|started| started.elapsed() < TAP_DURATION);
+
+        let fingers = match self.max_fingers {
+            1 => "",
+            2 => "Two-Finger ",
+            3 => "Three-Finger ",
+            _ => "Four-Finger ",
+        };
+
+        // One-finger touchpad taps and moves already arrive as mouse events
+        if self.max_fingers == 1 && !direct {
+            return None;
+        }
+
+        if self.max_fingers == 2 && contacts.len() == 2 {
+            let (first, second) = (contacts[0], contacts[1]);
+            let change = (first.1.distance(second.1) - first.0.distance(second.0)) / width;
+            if change.abs() >= MOVE_THRESHOLD && change.abs() > movement.length() {
+                let pinch = if change > 0.0 {
+                    "Pinch Out"
+                } else {
+                    "Pinch In"
+                };
+                return Some(pinch.to_string());
+            }
+        }
+
+        if moved {
+            // Two fingers moving together is the usual scroll gesture
+            let kind = if self.max_fingers == 2 {
+                "Scroll"
+            } else {
+                "Swipe"
+            };
+            Some(format!("{}{} {}", fingers, kind, direction_arrow(movement)))
+        } else if quick {
+            Some(format!("{}Tap", fingers))
+        } else {
+            None
+        }
+    }
+}
+
+fn read_surface(mut device: evdev::Device, sink: Arc<Mutex<EventSink>>) -> Result<(), String> {
+    // Touchscreens map contacts to the display; touchpads drive the pointer
+    let direct = device.properties().contains(PropType::DIRECT);
+    let abs_state = device.get_abs_state().map_err(|error| error.to_string())?;
+    let width = abs_state[AbsoluteAxisType::ABS_MT_POSITION_X.0 as usize]
+        .maximum
+        .max(1) as f32;
+
+    let mut slot = 0;
+    let mut position: HashMap<usize, egui::Pos2> = HashMap::new();
+    let mut gesture = Gesture::default();
+
+    loop {
+        let events = device.fetch_events().map_err(|error| error.to_string())?;
+        for event in events {
+            let axis = match event.kind() {
+                InputEventKind::AbsAxis(axis) => axis,
+                InputEventKind::Synchronization(_) => {
+                    // A complete report: move every finger to its latest position
+                    for (slot, contact) in gesture.active.iter_mut() {
+                        if let Some(&point) = position.get(slot) {
+                            contact.start.get_or_insert(point);
+                            contact.end = point;
+                        }
+                    }
+                    continue;
+                }
+                _ => continue,
+            };
+            let value = event.value();
+
+            if axis == AbsoluteAxisType::ABS_MT_SLOT {
+                slot = value.max(0) as usize;
+            } else if axis == AbsoluteAxisType::ABS_MT_POSITION_X
+                || axis == AbsoluteAxisType::ABS_MT_POSITION_Y
+            {
+                let point = position.entry(slot).or_insert(egui::Pos2::ZERO);
+                if axis == AbsoluteAxisType::ABS_MT_POSITION_X {
+                    point.x = value as f32;
+                } else {
+                    point.y = value as f32;
+                }
+            } else if axis == AbsoluteAxisType::ABS_MT_TRACKING_ID {
+                if value >= 0 {
+                    // Finger down; its position follows in the same report
+                    gesture.started.get_or_insert_with(Instant::now);
+                    gesture.active.insert(
+                        slot,
+                        Contact {
+                            start: None,
+                            end: egui::Pos2::ZERO,
+                        },
+                    );
+                    gesture.max_fingers = gesture.max_fingers.max(gesture.active.len());
+                } else if let Some(contact) = gesture.active.remove(&slot) {
+                    gesture.lifted.push(contact);
+
+                    if gesture.active.is_empty() {
+                        if let Some(label) = gesture.classify(width, direct) {
+                            let mut sink = sink.lock();
+                            (*sink)(InputEvent::Chip(label, Source::Pointer));
+                        }
+                        gesture = Gesture::default();
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,277 @@
+use crate::i18n::{Text, tr};
+use eframe::egui;
+use rdev::Button;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+
+// Wakes the idle UI from input and control threads; does nothing until eframe has
+// started, and never when headless
+#[derive(Clone, Default)]
+pub struct Repaint(Arc<OnceLock<egui::Context>>);
+
+impl Repaint {
+    pub fn set(&self, ctx: &egui::Context) {
+        let _ = self.0.set(ctx.clone());
+    }
+
+    pub fn request(&self) {
+        if let Some(ctx) = self.0.get() {
+            ctx.request_repaint();
+        }
+    }
+}
+
+// Which part of the mouse icon to highlight
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MouseIcon {
+    Left,
+    Right,
+    Middle,
+    // Side and other extra buttons
+    Other,
+    // Scroll wheel movement
+    Wheel,
+}
+
+impl MouseIcon {
+    pub fn from_button(button: Button) -> Self {
+        match button {
+            Button::Left => MouseIcon::Left,
+            Button::Right => MouseIcon::Right,
+            Button::Middle => MouseIcon::Middle,
+            Button::Unknown(_) => MouseIcon::Other,
+        }
+    }
+}
+
+// Draw a mouse outline `height` points tall with the given part filled in
+pub fn mouse_icon(ui: &mut egui::Ui, icon: MouseIcon, height: f32, color: egui::Color32) {
+    let (rect, _) = ui.allocate_exact_size(egui::vec2(height * 0.65, height), egui::Sense::hover());
+    let painter = ui.painter();
+    let stroke = egui::Stroke::new((height / 16.0).max(1.0), color);
+    let radius = (rect.width() / 2.0) as u8;
+
+    // Buttons occupy the top part of the body, split down the middle
+    let split_y = rect.top() + rect.height() * 0.42;
+    let center_x = rect.center().x;
+
+    match icon {
+        MouseIcon::Left => {
+            let button_rect = egui::Rect::from_min_max(rect.min, egui::pos2(center_x, split_y));
+            painter.rect_filled(
+                button_rect,
+                egui::CornerRadius {
+                    nw: radius,
+                    ne: 0,
+                    sw: 0,
+                    se: 0,
+                },
+                color,
+            );
+        }
+        MouseIcon::Right => {
+            let button_rect = egui::Rect::from_min_max(
+                egui::pos2(center_x, rect.top()),
+                egui::pos2(rect.right(), split_y),
+            );
+            painter.rect_filled(
+                button_rect,
+                egui::CornerRadius {
+                    nw: 0,
+                    ne: radius,
+                    sw: 0,
+                    se: 0,
+                },
+                color,
+            );
+        }
+        MouseIcon::Other => {
+            let side_rect = egui::Rect::from_min_size(
+                egui::pos2(rect.left(), split_y + rect.height() * 0.08),
+                egui::vec2(rect.width() * 0.2, rect.height() * 0.22),
+            );
+            painter.rect_filled(side_rect, egui::CornerRadius::same(1), color);
+        }
+        MouseIcon::Middle | MouseIcon::Wheel => {}
+    }
+
+    painter.rect_stroke(
+        rect,
+        egui::CornerRadius::same(radius),
+        stroke,
+        egui::StrokeKind::Inside,
+    );
+    painter.line_segment(
+        [
+            egui::pos2(rect.left(), split_y),
+            egui::pos2(rect.right(), split_y),
+        ],
+        stroke,
+    );
+
+    // Scroll wheel sits on the split between the buttons
+    let wheel_rect = egui::Rect::from_center_size(
+        egui::pos2(center_x, rect.top() + rect.height() * 0.22),
+        egui::vec2(rect.width() * 0.2, rect.height() * 0.22),
+    );
+    painter.line_segment(
+        [
+            egui::pos2(center_x, rect.top()),
+            egui::pos2(center_x, wheel_rect.top()),
+        ],
+        stroke,
+    );
+    painter.line_segment(
+        [
+            egui::pos2(center_x, wheel_rect.bottom()),
+            egui::pos2(center_x, split_y),
+        ],
+        stroke,
+    );
+
+    let wheel_radius = egui::CornerRadius::same((wheel_rect.width() / 2.0) as u8);
+    if matches!(icon, MouseIcon::Middle | MouseIcon::Wheel) {
+        painter.rect_filled(wheel_rect, wheel_radius, color);
+    } else {
+        painter.rect_stroke(wheel_rect, wheel_radius, stroke, egui::StrokeKind::Inside);
+    }
+}
+
+// System fonts with arrows and key symbols (↵ ⇥ ⌫ ␣ ⇪) that egui's built-in fonts lack
+const SYMBOL_FONTS: &[&str] = &[
+    "C:\\Windows\\Fonts\\seguisym.ttf",
+    "/System/Library/Fonts/Apple Symbols.ttf",
+    "/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf",
+    "/usr/share/fonts/TTF/DejaVuSans.ttf",
+    "/usr/share/fonts/dejavu/DejaVuSans.ttf",
+    "/usr/share/fonts/truetype/noto/NotoSansSymbols2-Regular.ttf",
+];
+
+// Add a symbol font after egui's own, so only glyphs they are missing come from it
+pub fn install_symbol_font(ctx: &egui::Context, path: Option<&Path>) {
+    let candidates: Vec<PathBuf> = match path {
+        Some(path) => vec![path.to_path_buf()],
+        None => SYMBOL_FONTS.iter().map(PathBuf::from).collect(),
+    };
+    let Some(data) = candidates.iter().find_map(|path| std::fs::read(path).ok()) else {
+        if let Some(path) = path {
+            eprintln!(
+                "Error loading symbol font {}; key symbols may not render",
+                path.display()
+            );
+        }
+        return;
+    };
+
+    let mut fonts = egui::FontDefinitions::default();
+    fonts.font_data.insert(
+        "symbols".to_string(),
+        Arc::new(egui::FontData::from_owned(data)),
+    );
+    for family in [egui::FontFamily::Proportional, egui::FontFamily::Monospace] {
+        fonts
+            .families
+            .entry(family)
+            .or_default()
+            .push("symbols".to_string());
+    }
+    ctx.set_fonts(fonts);
+}
+
+// Text reordered for display, left to right, with right-to-left runs reversed by the
+// Unicode bidi algorithm. egui doesn't shape Arabic, so its letters show in isolated forms.
+pub fn visual_order(text: &str) -> Cow<'_, str> {
+    let bidi = unicode_bidi::BidiInfo::new(text, None);
+    if !bidi.has_rtl() {
+        return Cow::Borrowed(text);
+    }
+    let mut visual = String::new();
+    for paragraph in &bidi.paragraphs {
+        visual.push_str(&bidi.reorder_line(paragraph, paragraph.range.clone()));
+    }
+    Cow::Owned(visual)
+}
+
+// Rounded box with one line of text, for panel elements like the timer; `anchor` says which
+// of its corners or edges sits at `position`
+pub fn badge(
+    ui: &egui::Ui,
+    position: egui::Pos2,
+    anchor: egui::Align2,
+    text: &str,
+    font_size: f32,
+    fill: egui::Color32,
+    color: egui::Color32,
+) {
+    let painter = ui.painter();
+    let galley =
+        painter.layout_no_wrap(text.to_string(), egui::FontId::monospace(font_size), color);
+    let padding = egui::vec2(font_size * 0.4, font_size * 0.15);
+    let rect = anchor.anchor_size(position, galley.size() + padding * 2.0);
+    painter.rect_filled(
+        rect,
+        egui::CornerRadius::same((font_size * 0.25) as u8),
+        fill,
+    );
+    painter.galley(rect.min + padding, galley, color);
+}
+
+// Grip to drag the overlay by and a padlock to lock it in place, side by side at the top center
+// of the panel. Returns the grip's and the padlock's responses; the grip only drags when unlocked.
+pub fn drag_handle(
+    ui: &egui::Ui,
+    locked: bool,
+    color: egui::Color32,
+) -> (egui::Response, egui::Response) {
+    let size = egui::vec2(22.0, 16.0);
+    let top = ui.max_rect().center_top();
+    let grip = egui::Rect::from_min_size(top - egui::vec2(size.x + 2.0, 0.0), size);
+    let lock = egui::Rect::from_min_size(top + egui::vec2(2.0, 0.0), size);
+
+    let grip_sense = if locked {
+        egui::Sense::hover()
+    } else {
+        egui::Sense::drag()
+    };
+    let grip_response = ui.interact(grip, ui.id().with("drag_handle"), grip_sense);
+    let lock_response = ui.interact(lock, ui.id().with("position_lock"), egui::Sense::click());
+
+    let painter = ui.painter();
+    let font = egui::FontId::proportional(14.0);
+    for (rect, response) in [(grip, &grip_response), (lock, &lock_response)] {
+        if response.hovered() {
+            painter.rect_filled(rect, egui::CornerRadius::same(4), color.gamma_multiply(0.2));
+        }
+    }
+    // A grip that can't be used is drawn faded
+    let grip_color = if locked {
+        color.gamma_multiply(0.35)
+    } else {
+        color
+    };
+    painter.text(
+        grip.center(),
+        egui::Align2::CENTER_CENTER,
+        "⠿",
+        font.clone(),
+        grip_color,
+    );
+    painter.text(
+        lock.center(),
+        egui::Align2::CENTER_CENTER,
+        if locked { "🔒" } else { "🔓" },
+        font,
+        color,
+    );
+    (
+        grip_response,
+        lock_response.on_hover_text(tr(if locked {
+            Text::UnlockPosition
+        } else {
+            Text::LockPosition
+        })),
+    )
+}
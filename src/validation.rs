@@ -0,0 +1,467 @@
+// Checking the config file setting by setting, so one mistake costs that setting rather than the
+// whole file, and the user is told which line to fix and what it accepts
+
+use crate::config::{Config, merge};
+use crate::i18n::{Text, tr, tr_args};
+use chrono::format::{Item, StrftimeItems};
+use eframe::egui;
+use parking_lot::Mutex;
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::path::Path;
+
+// A setting that couldn't be used as written; its default applies instead
+#[derive(Clone, Debug)]
+pub struct Problem {
+    pub line: Option<usize>,
+    // Dotted path like "theme.panel_opacity"; empty for the file as a whole
+    pub key: String,
+    pub message: String,
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(line) = self.line {
+            write!(f, "line {}: ", line)?;
+        }
+        if !self.key.is_empty() {
+            write!(f, "{}: ", self.key)?;
+        }
+        f.write_str(&self.message)
+    }
+}
+
+// Problems in the config file as last read, until the dialog listing them is dismissed
+static PROBLEMS: Mutex<Vec<Problem>> = Mutex::new(Vec::new());
+
+// Show these in the dialog, replacing the problems of an earlier read
+pub fn report(problems: Vec<Problem>) {
+    *PROBLEMS.lock() = problems;
+}
+
+// The config written in `text`, with every setting that can't be used left at its default, and what
+// was wrong with each. Err when the file isn't TOML at all.
+pub fn parse(text: &str) -> Result<(Config, Vec<Problem>), Problem> {
+    let mut table: toml::Table =
+        toml::from_str(text).map_err(|error: toml::de::Error| Problem {
+            line: error.span().map(|span| line_at(text, span.start)),
+            key: String::new(),
+            message: error.message().trim().to_string(),
+        })?;
+
+    let mut problems = Vec::new();
+    drop_invalid(&mut table, &[], text, &mut problems);
+    // Profiles are only deserialized when switched to, so check their overrides now as well
+    if let Some(toml::Value::Table(profiles)) = table.get_mut("profiles") {
+        for (name, overrides) in profiles.iter_mut() {
+            if let toml::Value::Table(overrides) = overrides {
+                let prefix = ["profiles".to_string(), name.clone()];
+                drop_invalid(overrides, &prefix, text, &mut problems);
+            }
+        }
+    }
+
+    drop_out_of_range(&mut table, text, &mut problems);
+    drop_out_of_range_in_profiles(&mut table, text, &mut problems);
+
+    match Config::from_table(table) {
+        Ok(config) => Ok((config, problems)),
+        // Only a combination of settings is wrong, which none of them shows on its own
+        Err(message) => {
+            problems.push(Problem {
+                line: None,
+                key: String::new(),
+                message: format!("{}; using the defaults for everything", message),
+            });
+            Ok((Config::default(), problems))
+        }
+    }
+}
+
+// Settings that don't come from the file, like environment variables, checked the same way but
+// without line numbers
+pub fn check(mut table: toml::Table) -> (toml::Table, Vec<Problem>) {
+    let mut problems = Vec::new();
+    drop_invalid(&mut table, &[], "", &mut problems);
+    drop_out_of_range(&mut table, "", &mut problems);
+    (table, problems)
+}
+
+// Remove the settings in `table` that don't deserialize and report them, along with the ones
+// rusteys doesn't know
+fn drop_invalid(
+    table: &mut toml::Table,
+    prefix: &[String],
+    text: &str,
+    problems: &mut Vec<Problem>,
+) {
+    if deserialize(table.clone()).is_err() {
+        for path in leaves(table) {
+            // A setting on its own, so the error is about it; a required field missing in the
+            // isolated table is not
+            let result = deserialize(only(table, &path));
+            if let Err(message) = result
+                && !message.starts_with("missing field")
+            {
+                remove(table, &path);
+                let full: Vec<String> = prefix.iter().chain(&path).cloned().collect();
+                problems.push(problem(text, &full, explain(&message)));
+            }
+        }
+    }
+
+    let mut unknown = Vec::new();
+    let _ = serde_ignored::deserialize::<_, _, Config>(toml::Value::Table(table.clone()), |path| {
+        unknown.push(path.to_string())
+    });
+    let known = toml::Value::try_from(Config::default()).ok();
+    for key in unknown {
+        let path = split_key(&key);
+        let mut message = "unknown setting, ignored".to_string();
+        if let Some(suggestion) = known.as_ref().and_then(|known| suggest(known, &path)) {
+            message.push_str(&format!("; did you mean `{}`?", suggestion));
+        }
+        let full: Vec<String> = prefix.iter().chain(&path).cloned().collect();
+        problems.push(problem(text, &full, message));
+    }
+}
+
+// Values of the right type but outside what makes sense
+fn drop_out_of_range(table: &mut toml::Table, text: &str, problems: &mut Vec<Problem>) {
+    if let Ok(config) = deserialize(table.clone()) {
+        for (key, message) in out_of_range(&config) {
+            let path = split_key(key);
+            remove(table, &path);
+            problems.push(problem(text, &path, message));
+        }
+    }
+}
+
+// A profile's overrides are merged over the rest of the file when switched to, so check each one
+// merged the same way and drop the overridden values that end up out of range
+fn drop_out_of_range_in_profiles(table: &mut toml::Table, text: &str, problems: &mut Vec<Problem>) {
+    let Some(toml::Value::Table(mut profiles)) = table.remove("profiles") else {
+        return;
+    };
+    for (name, overrides) in profiles.iter_mut() {
+        let toml::Value::Table(overrides) = overrides else {
+            continue;
+        };
+        let mut merged = table.clone();
+        merge(&mut merged, overrides);
+        let Ok(config) = deserialize(merged) else {
+            continue;
+        };
+        for (key, message) in out_of_range(&config) {
+            let path = split_key(key);
+            // Values from the top level were already dropped above
+            if only(overrides, &path).is_empty() {
+                continue;
+            }
+            remove(overrides, &path);
+            let full: Vec<String> = ["profiles".to_string(), name.clone()]
+                .into_iter()
+                .chain(path)
+                .collect();
+            problems.push(problem(text, &full, message));
+        }
+    }
+    table.insert("profiles".to_string(), toml::Value::Table(profiles));
+}
+
+fn deserialize(table: toml::Table) -> Result<Config, String> {
+    toml::Value::Table(table)
+        .try_into()
+        .map_err(|error: toml::de::Error| error.message().trim().to_string())
+}
+
+fn problem(text: &str, path: &[String], message: String) -> Problem {
+    Problem {
+        line: line_of(text, path),
+        key: path.join("."),
+        message,
+    }
+}
+
+// What the setting's type accepts, for serde's messages that only name the Rust type
+fn explain(message: &str) -> String {
+    let accepted = [
+        ("expected u8", "a whole number from 0 to 255"),
+        ("expected i8", "a whole number from -128 to 127"),
+        ("expected u16", "a whole number from 0 to 65535"),
+        ("expected u32", "a whole number, 0 or more"),
+        ("expected u64", "a whole number, 0 or more"),
+        ("expected usize", "a whole number, 0 or more"),
+        ("expected f32", "a number"),
+        ("expected f64", "a number"),
+        ("expected a boolean", "true or false"),
+    ];
+    match accepted.iter().find(|(rust, _)| message.contains(rust)) {
+        Some((_, accepted)) => format!("{} (accepts {})", message, accepted),
+        None => message.to_string(),
+    }
+}
+
+// Values of the right type that would make the overlay unusable or invisible, or crash it
+fn out_of_range(config: &Config) -> Vec<(&'static str, String)> {
+    let mut found = Vec::new();
+    let mut check = |key: &'static str, value: f32, range: RangeInclusive<f32>| {
+        if !range.contains(&value) {
+            found.push((
+                key,
+                format!(
+                    "{} is out of range, expected {} to {}",
+                    value,
+                    range.start(),
+                    range.end()
+                ),
+            ));
+        }
+    };
+    let theme = &config.theme;
+    check(
+        "theme.panel_opacity",
+        theme.panel_opacity as f32,
+        0.0..=100.0,
+    );
+    check("theme.font_size", theme.font_size, 4.0..=400.0);
+    check(
+        "theme.chip_stroke_width",
+        theme.chip_stroke_width,
+        0.0..=20.0,
+    );
+    check(
+        "max_chips",
+        config.max_chips.unwrap_or(1) as f32,
+        1.0..=500.0,
+    );
+    check(
+        "animations.pop_scale",
+        config.animations.pop_scale,
+        0.0..=2.0,
+    );
+    let position = &config.position;
+    check(
+        "position.cursor_width",
+        position.cursor_width,
+        50.0..=10000.0,
+    );
+    check("position.smoothing", position.smoothing, 0.0..=1.0);
+    check("position.edge_margin", position.edge_margin, 0.0..=1000.0);
+    check(
+        "position.snap_distance",
+        position.snap_distance,
+        0.0..=1000.0,
+    );
+    check(
+        "confetti.particles",
+        config.confetti.particles as f32,
+        0.0..=5000.0,
+    );
+    check("cursor.halo_radius", config.cursor.halo_radius, 0.0..=500.0);
+    check(
+        "cursor.ripple_radius",
+        config.cursor.ripple_radius,
+        0.0..=500.0,
+    );
+    check(
+        "history.copy_count",
+        config.history.copy_count as f32,
+        1.0..=10000.0,
+    );
+    check("big_key.font_size", config.big_key.font_size, 4.0..=1000.0);
+    check("clock.font_size", config.clock.font_size, 4.0..=400.0);
+    check(
+        "window_title.font_size",
+        config.window_title.font_size,
+        4.0..=400.0,
+    );
+    check(
+        "window_title.max_length",
+        config.window_title.max_length as f32,
+        1.0..=1000.0,
+    );
+    check(
+        "history.max_size_mb",
+        config.history.max_size_mb as f32,
+        1.0..=10000.0,
+    );
+    check("replay.seconds", config.replay.seconds as f32, 1.0..=300.0);
+    check("replay.fps", config.replay.fps as f32, 1.0..=60.0);
+    if StrftimeItems::new(&config.clock.format).any(|item| item == Item::Error) {
+        found.push((
+            "clock.format",
+            format!("`{}` is not a strftime format", config.clock.format),
+        ));
+    }
+    found
+}
+
+// Dotted paths of every value, with arrays taken whole
+fn leaves(table: &toml::Table) -> Vec<Vec<String>> {
+    let mut paths = Vec::new();
+    for (key, value) in table {
+        match value {
+            toml::Value::Table(inner) if !inner.is_empty() => {
+                for mut path in leaves(inner) {
+                    path.insert(0, key.clone());
+                    paths.push(path);
+                }
+            }
+            _ => paths.push(vec![key.clone()]),
+        }
+    }
+    paths
+}
+
+// A table holding just the value at `path`
+fn only(table: &toml::Table, path: &[String]) -> toml::Table {
+    let Some((last, parents)) = path.split_last() else {
+        return toml::Table::new();
+    };
+    let mut source = table;
+    for key in parents {
+        match source.get(key) {
+            Some(toml::Value::Table(inner)) => source = inner,
+            _ => return toml::Table::new(),
+        }
+    }
+    let Some(value) = source.get(last) else {
+        return toml::Table::new();
+    };
+    let mut single = toml::Table::from_iter([(last.clone(), value.clone())]);
+    for key in parents.iter().rev() {
+        single = toml::Table::from_iter([(key.clone(), toml::Value::Table(single))]);
+    }
+    single
+}
+
+fn remove(table: &mut toml::Table, path: &[String]) {
+    let Some((last, parents)) = path.split_last() else {
+        return;
+    };
+    let mut table = table;
+    for key in parents {
+        match table.get_mut(key) {
+            Some(toml::Value::Table(inner)) => table = inner,
+            _ => return,
+        }
+    }
+    table.remove(last);
+}
+
+// The closest known setting next to an unknown one, for typos like `panel_colour`
+fn suggest(known: &toml::Value, path: &[String]) -> Option<String> {
+    let (unknown, parents) = path.split_last()?;
+    let mut siblings = known;
+    for key in parents {
+        siblings = siblings.get(key)?;
+    }
+    siblings
+        .as_table()?
+        .keys()
+        .map(|key| (key, distance(key, unknown)))
+        .filter(|(key, distance)| *distance <= 2.max(key.len() / 4))
+        .min_by_key(|(_, distance)| *distance)
+        .map(|(key, _)| key.clone())
+}
+
+// Levenshtein distance
+fn distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b) in b.iter().enumerate() {
+            let cost = usize::from(a != *b);
+            current.push(
+                (previous[j] + cost)
+                    .min(previous[j + 1] + 1)
+                    .min(current[j] + 1),
+            );
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+fn split_key(key: &str) -> Vec<String> {
+    key.split('.')
+        .map(|part| part.trim().trim_matches('"').trim_matches('\'').to_string())
+        .collect()
+}
+
+fn line_at(text: &str, offset: usize) -> usize {
+    text[..offset.min(text.len())].matches('\n').count() + 1
+}
+
+// Line where the setting at `path` is written: `key = ...` in its [section], a dotted key, the
+// inline table or array that holds it, or the section itself
+fn line_of(text: &str, path: &[String]) -> Option<usize> {
+    let mut section = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.starts_with('#') {
+            continue;
+        }
+        if let Some(header) = line.strip_prefix('[') {
+            let header = header
+                .trim_start_matches('[')
+                .split(']')
+                .next()
+                .unwrap_or_default();
+            section = split_key(header);
+            if !section.is_empty() && section == path {
+                return Some(index + 1);
+            }
+            continue;
+        }
+        let Some((key, _)) = line.split_once('=') else {
+            continue;
+        };
+        let mut full = section.clone();
+        full.extend(split_key(key));
+        if path.starts_with(&full) {
+            return Some(index + 1);
+        }
+    }
+    None
+}
+
+// Window listing what was wrong with the config file, until the user dismisses it
+pub fn show_dialog(ctx: &egui::Context, file: &Path) {
+    let problems = PROBLEMS.lock().clone();
+    if problems.is_empty() {
+        return;
+    }
+
+    ctx.show_viewport_immediate(
+        egui::ViewportId::from_hash_of("config_problems"),
+        egui::ViewportBuilder::default()
+            .with_title(tr(Text::ConfigProblemsTitle))
+            .with_inner_size([560.0, 320.0])
+            .with_always_on_top(),
+        |ctx, _class| {
+            let mut dismissed = ctx.input(|i| i.viewport().close_requested());
+
+            egui::CentralPanel::default().show(ctx, |ui| {
+                ui.label(tr_args(Text::ConfigProblemsIntro, &[&file.display()]));
+                ui.separator();
+                egui::ScrollArea::vertical()
+                    .max_height(ui.available_height() - 32.0)
+                    .show(ui, |ui| {
+                        for problem in &problems {
+                            ui.label(egui::RichText::new(problem.to_string()).monospace());
+                        }
+                    });
+                if ui.button(tr(Text::Dismiss)).clicked() {
+                    dismissed = true;
+                }
+            });
+
+            if dismissed {
+                PROBLEMS.lock().clear();
+            }
+        },
+    );
+}
@@ -0,0 +1,89 @@
+use crate::config::Webhook;
+use chrono::{SecondsFormat, Utc};
+use std::sync::mpsc::{self, SyncSender, TrySendError};
+use std::thread;
+use std::time::Duration;
+
+// Tries per delivery; the wait doubles after each failure
+const ATTEMPTS: u32 = 4;
+const FIRST_RETRY: Duration = Duration::from_secs(1);
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+// Deliveries waiting while an earlier one retries; past this new triggers are dropped
+const QUEUE: usize = 16;
+
+struct Delivery {
+    url: String,
+    body: String,
+}
+
+// POSTs webhook payloads one at a time on a single thread, so a dead host can't pile up threads
+pub struct WebhookSender {
+    sender: SyncSender<Delivery>,
+}
+
+impl WebhookSender {
+    pub fn spawn() -> Self {
+        let (sender, deliveries) = mpsc::sync_channel::<Delivery>(QUEUE);
+        thread::spawn(move || {
+            for delivery in deliveries {
+                deliver(&delivery);
+            }
+        });
+        Self { sender }
+    }
+
+    // Never blocks: this runs on the input hook's thread
+    pub fn trigger(&self, webhook: &Webhook) {
+        let body = webhook
+            .payload
+            .clone()
+            .unwrap_or_else(|| {
+                serde_json::json!({
+                    "combo": webhook.combo.to_string(),
+                    "time": Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+                })
+            })
+            .to_string();
+        let delivery = Delivery {
+            url: webhook.url.clone(),
+            body,
+        };
+        if let Err(TrySendError::Full(delivery)) = self.sender.try_send(delivery) {
+            eprintln!(
+                "Webhook {} dropped: too many deliveries pending",
+                delivery.url
+            );
+        }
+    }
+}
+
+// Retry server errors and unreachable hosts
+fn deliver(delivery: &Delivery) {
+    let Delivery { url, body } = delivery;
+    let mut wait = FIRST_RETRY;
+    for attempt in 1..=ATTEMPTS {
+        let result = ureq::post(url)
+            .timeout(TIMEOUT)
+            .set("Content-Type", "application/json")
+            .send_string(body);
+        let error = match result {
+            Ok(_) => return,
+            // The receiver understood and refused; trying again won't help
+            Err(ureq::Error::Status(status, _)) if status < 500 && status != 429 => {
+                eprintln!("Webhook {} rejected with status {}", url, status);
+                return;
+            }
+            Err(error) => error,
+        };
+        if attempt == ATTEMPTS {
+            eprintln!(
+                "Webhook {} failed after {} attempts: {}",
+                url, ATTEMPTS, error
+            );
+            return;
+        }
+        thread::sleep(wait);
+        wait *= 2;
+    }
+}
@@ -0,0 +1,145 @@
+use parking_lot::Mutex;
+use std::io;
+use std::net::{TcpListener, TcpStream};
+use std::sync::Arc;
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::thread;
+use std::time::Duration;
+use tungstenite::handshake::server::{ErrorResponse, Request, Response};
+use tungstenite::http::StatusCode;
+use tungstenite::{Message, WebSocket};
+
+// A client that doesn't finish the handshake or take its messages in this long is dropped
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(5);
+
+// Chips waiting for a slow client; past this it misses chips rather than holding up input
+const CLIENT_QUEUE: usize = 256;
+
+// Broadcasts chips as JSON text messages to every connected client
+pub struct WebSocketServer {
+    clients: Arc<Mutex<Vec<SyncSender<String>>>>,
+}
+
+impl WebSocketServer {
+    pub fn new(address: &str, allowed_origins: Vec<String>) -> io::Result<Self> {
+        let listener = TcpListener::bind(address)?;
+        let clients = Arc::new(Mutex::new(Vec::new()));
+        let allowed_origins = Arc::new(allowed_origins);
+
+        let accepted = Arc::clone(&clients);
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let (clients, allowed_origins) =
+                    (Arc::clone(&accepted), Arc::clone(&allowed_origins));
+                thread::spawn(move || match accept(stream, &allowed_origins) {
+                    Ok(client) => {
+                        let (sender, queue) = mpsc::sync_channel(CLIENT_QUEUE);
+                        clients.lock().push(sender);
+                        write(client, queue);
+                    }
+                    Err(error) => eprintln!("Error accepting WebSocket client: {}", error),
+                });
+            }
+        });
+
+        Ok(Self { clients })
+    }
+
+    // Never blocks: this runs on the input hook's thread
+    pub fn send(&self, payload: &str) {
+        self.clients
+            .lock()
+            .retain(|client| match client.try_send(payload.to_string()) {
+                Ok(()) | Err(TrySendError::Full(_)) => true,
+                // Its writer stopped, so the client has gone away
+                Err(TrySendError::Disconnected(_)) => false,
+            });
+    }
+}
+
+fn accept(stream: TcpStream, allowed_origins: &[String]) -> Result<WebSocket<TcpStream>, String> {
+    stream
+        .set_read_timeout(Some(CLIENT_TIMEOUT))
+        .and_then(|()| stream.set_write_timeout(Some(CLIENT_TIMEOUT)))
+        .map_err(|e| e.to_string())?;
+    let check_origin = |request: &Request, response: Response| {
+        let origin = request
+            .headers()
+            .get("Origin")
+            .and_then(|origin| origin.to_str().ok());
+        match origin {
+            Some(origin) if !origin_allowed(origin, allowed_origins) => {
+                let mut rejection =
+                    ErrorResponse::new(Some(format!("origin {} not allowed", origin)));
+                *rejection.status_mut() = StatusCode::FORBIDDEN;
+                Err(rejection)
+            }
+            _ => Ok(response),
+        }
+    };
+    let client = tungstenite::accept_hdr(stream, check_origin).map_err(|e| e.to_string())?;
+    // Clients only listen; the write timeout stays, so a stalled one is dropped
+    client
+        .get_ref()
+        .set_read_timeout(None)
+        .map_err(|e| e.to_string())?;
+    Ok(client)
+}
+
+// Browsers send the page's origin, so any website could otherwise read keystrokes; programs
+// like OBS scripts and dashboards send none
+fn origin_allowed(origin: &str, allowed_origins: &[String]) -> bool {
+    if allowed_origins.iter().any(|allowed| allowed == origin) {
+        return true;
+    }
+    let host = origin
+        .split_once("://")
+        .map_or(origin, |(_, rest)| rest)
+        .trim_end_matches('/');
+    let host = match host.rsplit_once(':') {
+        Some((host, port)) if port.chars().all(|c| c.is_ascii_digit()) => host,
+        _ => host,
+    };
+    matches!(host, "localhost" | "127.0.0.1" | "[::1]")
+}
+
+fn write(mut client: WebSocket<TcpStream>, queue: Receiver<String>) {
+    for payload in queue {
+        if client.send(Message::text(payload)).is_err() {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn local_origins_on_any_port() {
+        assert!(origin_allowed("http://localhost", &[]));
+        assert!(origin_allowed("http://localhost:8080", &[]));
+        assert!(origin_allowed("http://127.0.0.1:3000/", &[]));
+        assert!(origin_allowed("http://[::1]:3000", &[]));
+    }
+
+    #[test]
+    fn other_origins_refused() {
+        assert!(!origin_allowed("http://localhost.evil.com", &[]));
+        assert!(!origin_allowed("http://localhost.evil.com:8080", &[]));
+        assert!(!origin_allowed("https://evil.com", &[]));
+        assert!(!origin_allowed("http://127.0.0.1.evil.com", &[]));
+        assert!(!origin_allowed("null", &[]));
+    }
+
+    #[test]
+    fn allowed_origins_match_exactly() {
+        let allowed = ["https://overlay.example.com".to_string()];
+        assert!(origin_allowed("https://overlay.example.com", &allowed));
+        assert!(!origin_allowed(
+            "https://overlay.example.com.evil.com",
+            &allowed
+        ));
+        assert!(!origin_allowed("http://overlay.example.com", &allowed));
+    }
+}
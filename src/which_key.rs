@@ -0,0 +1,144 @@
+use crate::MonitorInfo;
+use crate::config::{Combo, ThemeConfig};
+use crate::hotkeys::Controls;
+use crate::window_level;
+use eframe::egui;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+// Height of one continuation line
+const ROW_HEIGHT: f32 = 24.0;
+const WIDTH: f32 = 360.0;
+
+// Continuations of each prefix combo, from the `[which_key] bindings` file:
+//
+//     ["Ctrl+K"]
+//     "Ctrl+C" = "Add line comment"
+//     Z = "Zen mode"
+#[derive(Default)]
+pub struct Bindings(Vec<(Combo, Vec<(String, String)>)>);
+
+impl Bindings {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text =
+            std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let table: BTreeMap<String, BTreeMap<String, String>> =
+            toml::from_str(&text).map_err(|e| format!("{}: {}", path.display(), e))?;
+        table
+            .into_iter()
+            .map(|(prefix, continuations)| {
+                let combo =
+                    Combo::parse(&prefix).map_err(|e| format!("{}: {}", path.display(), e))?;
+                Ok((combo, continuations.into_iter().collect()))
+            })
+            .collect::<Result<_, String>>()
+            .map(Bindings)
+    }
+
+    fn hints(&self, combo: &Combo) -> Option<&[(String, String)]> {
+        self.0
+            .iter()
+            .find(|(prefix, _)| prefix == combo)
+            .map(|(_, hints)| hints.as_slice())
+    }
+}
+
+// Hint panel waiting for the key after a prefix
+#[derive(Clone)]
+pub struct Pending {
+    prefix: String,
+    hints: Vec<(String, String)>,
+    shown: Instant,
+}
+
+// Called for every combo pressed: ends a pending sequence, or starts one after a prefix
+pub fn pressed(controls: &Controls, bindings: &Bindings, combo: &Combo) {
+    let mut pending = controls.which_key.lock();
+    if pending.take().is_some() {
+        controls.repaint.request();
+        return;
+    }
+    if let Some(hints) = bindings.hints(combo) {
+        *pending = Some(Pending {
+            prefix: combo.to_string(),
+            hints: hints.to_vec(),
+            shown: Instant::now(),
+        });
+        controls.repaint.request();
+    }
+}
+
+// Panel above the overlay listing what may follow the prefix, until the sequence is
+// completed or `timeout` passes, drawn with the overlay's theme
+pub fn show_hints(
+    ctx: &egui::Context,
+    monitor: &MonitorInfo,
+    overlay: egui::Rect,
+    controls: &Controls,
+    theme: &ThemeConfig,
+    timeout: Duration,
+) {
+    // A copy, so the input thread isn't held up while the panel is drawn
+    let pending = {
+        let mut pending = controls.which_key.lock();
+        if pending
+            .as_ref()
+            .is_some_and(|pending| pending.shown.elapsed() >= timeout)
+        {
+            *pending = None;
+        }
+        pending.clone()
+    };
+    let Some(pending) = pending else {
+        return;
+    };
+    let (_, key_color, text_color) = theme.idle_colors();
+    let (_, accent) = theme.palette.accent();
+    ctx.request_repaint_after(timeout.saturating_sub(pending.shown.elapsed()));
+
+    let height = ROW_HEIGHT * (pending.hints.len() as f32 + 1.5);
+    // Above the overlay, or below a bar along the top
+    let top = if overlay.center().y < monitor.rect().center().y {
+        overlay.bottom() + ROW_HEIGHT
+    } else {
+        overlay.top() - height - ROW_HEIGHT
+    };
+    ctx.show_viewport_immediate(
+        egui::ViewportId::from_hash_of("which_key"),
+        egui::ViewportBuilder::default()
+            .with_title("Key Display Overlay - Hints")
+            .with_inner_size([WIDTH, height])
+            .with_position([monitor.rect().center().x - WIDTH / 2.0, top])
+            .with_decorations(false)
+            .with_transparent(true)
+            .with_always_on_top()
+            .with_resizable(false)
+            .with_taskbar(false)
+            .with_mouse_passthrough(true),
+        |ctx, _class| {
+            egui::CentralPanel::default()
+                .frame(
+                    egui::Frame::new()
+                        .fill(theme.panel_fill())
+                        .corner_radius(egui::CornerRadius::same(12))
+                        .inner_margin(egui::Margin::same(10)),
+                )
+                .show(ctx, |ui| {
+                    ui.label(
+                        egui::RichText::new(format!("{} …", pending.prefix))
+                            .strong()
+                            .color(accent),
+                    );
+                    egui::Grid::new("which_key_hints").show(ui, |ui| {
+                        for (key, description) in &pending.hints {
+                            ui.label(egui::RichText::new(key).monospace().color(key_color));
+                            ui.label(egui::RichText::new(description).color(text_color));
+                            ui.end_row();
+                        }
+                    });
+                });
+        },
+    );
+    window_level::raise("Key Display Overlay - Hints");
+}
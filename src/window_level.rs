@@ -0,0 +1,531 @@
+// Keep overlay windows above everything without ever taking focus, beyond what
+// eframe's always-on-top hint does, and blur what is behind them. Windows are found
+// by title, as eframe doesn't expose native handles for immediate viewports.
+
+use parking_lot::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+// Waits before trying a failed change again, doubling each time
+const FIRST_RETRY: Duration = Duration::from_millis(250);
+const MAX_RETRY: Duration = Duration::from_secs(30);
+
+// What was last applied to one window title
+struct Applied<T> {
+    title: String,
+    value: T,
+    // Being changed on another thread
+    busy: bool,
+    retry_at: Option<Instant>,
+    backoff: Duration,
+}
+
+impl<T> Applied<T> {
+    fn finish(&mut self, value: T, done: bool) {
+        if done {
+            self.value = value;
+            self.retry_at = None;
+            self.backoff = FIRST_RETRY;
+        } else {
+            self.retry_at = Some(Instant::now() + self.backoff);
+            self.backoff = (self.backoff * 2).min(MAX_RETRY);
+        }
+    }
+}
+
+// Call `set` when the window should get a value other than the one it has, which starts out
+// as the default, and not again until it succeeds or the backoff passes
+fn apply<T>(
+    applied: &'static Mutex<Vec<Applied<T>>>,
+    title: &str,
+    value: T,
+    set: impl FnOnce() -> bool + Send + 'static,
+) where
+    T: Copy + Default + PartialEq + Send + 'static,
+{
+    let mut windows = applied.lock();
+    let index = match windows.iter().position(|window| window.title == title) {
+        Some(index) => index,
+        None => {
+            windows.push(Applied {
+                title: title.to_string(),
+                value: T::default(),
+                busy: false,
+                retry_at: None,
+                backoff: FIRST_RETRY,
+            });
+            windows.len() - 1
+        }
+    };
+    let window = &mut windows[index];
+    if window.value == value || window.busy || window.retry_at.is_some_and(|at| Instant::now() < at)
+    {
+        return;
+    }
+
+    // xprop starts a process and waits on the X server, too slow for the UI thread; the native
+    // calls elsewhere have to stay on it
+    if cfg!(target_os = "linux") {
+        window.busy = true;
+        let title = title.to_string();
+        thread::spawn(move || {
+            let done = set();
+            if let Some(window) = applied
+                .lock()
+                .iter_mut()
+                .find(|window| window.title == title)
+            {
+                window.busy = false;
+                window.finish(value, done);
+            }
+        });
+    } else {
+        // Not created yet on a viewport's first frame; tried again after the backoff
+        window.finish(value, set());
+    }
+}
+
+// Blur state last applied to each window title
+static BLURRED: Mutex<Vec<Applied<bool>>> = Mutex::new(Vec::new());
+
+// Ask the compositor to blur the desktop behind the window, so a translucent panel
+// reads as frosted glass over any content
+pub fn blur_behind(title: &str, enabled: bool, radius: u8) {
+    let owned = title.to_string();
+    apply(&BLURRED, title, enabled, move || {
+        set_blur(&owned, enabled, radius)
+    });
+}
+
+// Screen edge space a bar keeps for itself, in physical pixels
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Strut {
+    // Along the top edge, otherwise the bottom one
+    pub top: bool,
+    // From that edge of the whole screen across all monitors, so it includes any space between
+    // the screen edge and the monitor the bar is on
+    pub thickness: u32,
+    // Horizontal extent along the edge
+    pub start: u32,
+    pub end: u32,
+}
+
+// Space last reserved for each window title
+static RESERVED: Mutex<Vec<Applied<Option<Strut>>>> = Mutex::new(Vec::new());
+
+// Ask the window manager to keep other windows out of the strip a bar covers, like a panel
+pub fn reserve_space(title: &str, strut: Option<Strut>) {
+    let owned = title.to_string();
+    apply(&RESERVED, title, strut, move || set_strut(&owned, strut));
+}
+
+// Each overlay window's handle and the foreground window when it was last raised, kept as
+// integers since handles aren't Send
+#[cfg(target_os = "windows")]
+static RAISED: Mutex<Vec<(String, isize, isize)>> = Mutex::new(Vec::new());
+
+#[cfg(target_os = "windows")]
+pub fn raise(title: &str) {
+    use std::ffi::OsStr;
+    use std::os::windows::ffi::OsStrExt;
+    use std::ptr;
+    use winapi::shared::windef::HWND;
+    use winapi::um::winuser::{
+        FindWindowW, GW_HWNDPREV, GWL_EXSTYLE, GetForegroundWindow, GetWindow, GetWindowLongPtrW,
+        HWND_TOPMOST, IsWindow, SWP_FRAMECHANGED, SWP_NOACTIVATE, SWP_NOMOVE, SWP_NOSIZE,
+        SetWindowLongPtrW, SetWindowPos, WS_EX_NOACTIVATE, WS_EX_TOOLWINDOW, WS_EX_TOPMOST,
+    };
+
+    let mut raised = RAISED.lock();
+    let index = raised.iter().position(|(window, _, _)| window == title);
+    unsafe {
+        // Looked up by title only once, or again after the viewport was closed and reopened
+        let cached = index
+            .map(|index| raised[index].1 as HWND)
+            .filter(|&window| IsWindow(window) != 0);
+        let window = match cached {
+            Some(window) => window,
+            None => {
+                let wide: Vec<u16> = OsStr::new(title).encode_wide().chain(Some(0)).collect();
+                FindWindowW(ptr::null(), wide.as_ptr())
+            }
+        };
+        // Not created yet on a viewport's first frame; tried again on the next
+        if window.is_null() {
+            return;
+        }
+
+        // No focus stealing on click, no taskbar button, no Alt-Tab entry
+        let style = GetWindowLongPtrW(window, GWL_EXSTYLE);
+        let wanted = (WS_EX_NOACTIVATE | WS_EX_TOOLWINDOW) as isize;
+        let topmost = style & WS_EX_TOPMOST as isize != 0;
+
+        // Full-screen apps and other topmost windows can push the overlay down, which only
+        // happens when focus moves or another topmost window comes above it
+        let foreground = GetForegroundWindow() as isize;
+        let above = GetWindow(window, GW_HWNDPREV);
+        let covered = !above.is_null()
+            && GetWindowLongPtrW(above, GWL_EXSTYLE) & WS_EX_TOPMOST as isize != 0
+            && !raised.iter().any(|(_, ours, _)| *ours == above as isize);
+        let unchanged = cached.is_some()
+            && index.is_some_and(|index| raised[index].2 == foreground)
+            && topmost
+            && !covered
+            && style & wanted == wanted;
+        if unchanged {
+            return;
+        }
+
+        let mut flags = SWP_NOMOVE | SWP_NOSIZE | SWP_NOACTIVATE;
+        if style & wanted != wanted {
+            SetWindowLongPtrW(window, GWL_EXSTYLE, style | wanted);
+            flags |= SWP_FRAMECHANGED;
+        }
+        SetWindowPos(window, HWND_TOPMOST, 0, 0, 0, 0, flags);
+
+        let entry = (title.to_string(), window as isize, foreground);
+        match index {
+            Some(index) => raised[index] = entry,
+            None => raised.push(entry),
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+pub fn raise(title: &str) {
+    use std::ffi::{CStr, c_char, c_void};
+
+    type Id = *mut c_void;
+    type Sel = *const c_void;
+
+    #[link(name = "objc")]
+    unsafe extern "C" {
+        fn objc_getClass(name: *const c_char) -> Id;
+        fn sel_registerName(name: *const c_char) -> Sel;
+        fn objc_msgSend();
+    }
+
+    // Above full-screen apps, like the screen saver
+    const SCREEN_SAVER_WINDOW_LEVEL: isize = 1000;
+    // NSWindowCollectionBehavior: CanJoinAllSpaces | Stationary | IgnoresCycle | FullScreenAuxiliary
+    const COLLECTION_BEHAVIOR: usize = (1 << 0) | (1 << 4) | (1 << 6) | (1 << 8);
+    // NSApplicationActivationPolicyAccessory: no Dock icon, but windows may float over other apps' full-screen Spaces
+    const ACCESSORY_POLICY: isize = 1;
+
+    unsafe {
+        let selector = |name: &CStr| sel_registerName(name.as_ptr());
+        let send = objc_msgSend as unsafe extern "C" fn();
+        let send_id: unsafe extern "C" fn(Id, Sel) -> Id = std::mem::transmute(send);
+        let send_index: unsafe extern "C" fn(Id, Sel, usize) -> Id = std::mem::transmute(send);
+        let send_count: unsafe extern "C" fn(Id, Sel) -> usize = std::mem::transmute(send);
+        let send_level: unsafe extern "C" fn(Id, Sel) -> isize = std::mem::transmute(send);
+        let send_set_integer: unsafe extern "C" fn(Id, Sel, isize) = std::mem::transmute(send);
+        let send_set_behavior: unsafe extern "C" fn(Id, Sel, usize) = std::mem::transmute(send);
+        let send_str: unsafe extern "C" fn(Id, Sel) -> *const c_char = std::mem::transmute(send);
+
+        let app = send_id(
+            objc_getClass(c"NSApplication".as_ptr()),
+            selector(c"sharedApplication"),
+        );
+        let windows = send_id(app, selector(c"windows"));
+        for index in 0..send_count(windows, selector(c"count")) {
+            let window = send_index(windows, selector(c"objectAtIndex:"), index);
+            let window_title = send_id(window, selector(c"title"));
+            if window_title.is_null()
+                || CStr::from_ptr(send_str(window_title, selector(c"UTF8String"))).to_bytes()
+                    != title.as_bytes()
+            {
+                continue;
+            }
+
+            // Already raised on an earlier frame
+            if send_level(window, selector(c"level")) == SCREEN_SAVER_WINDOW_LEVEL {
+                return;
+            }
+            send_set_integer(window, selector(c"setLevel:"), SCREEN_SAVER_WINDOW_LEVEL);
+            send_set_behavior(
+                window,
+                selector(c"setCollectionBehavior:"),
+                COLLECTION_BEHAVIOR,
+            );
+            send_set_integer(app, selector(c"setActivationPolicy:"), ACCESSORY_POLICY);
+            return;
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+pub fn raise(_title: &str) {}
+
+// Acrylic system backdrop, Windows 11 22H2 and later; older versions ignore it
+#[cfg(target_os = "windows")]
+fn set_blur(title: &str, enabled: bool, _radius: u8) -> bool {
+    use std::ffi::OsStr;
+    use std::os::windows::ffi::OsStrExt;
+    use std::ptr;
+    use winapi::um::dwmapi::{DwmExtendFrameIntoClientArea, DwmSetWindowAttribute};
+    use winapi::um::uxtheme::MARGINS;
+    use winapi::um::winuser::FindWindowW;
+
+    // Not in winapi's headers yet
+    const DWMWA_SYSTEMBACKDROP_TYPE: u32 = 38;
+    const DWMSBT_NONE: u32 = 1;
+    const DWMSBT_TRANSIENTWINDOW: u32 = 3;
+
+    let title: Vec<u16> = OsStr::new(title).encode_wide().chain(Some(0)).collect();
+    unsafe {
+        let window = FindWindowW(ptr::null(), title.as_ptr());
+        if window.is_null() {
+            return false;
+        }
+
+        // The backdrop only shows through where the frame extends into the client area
+        let extent = if enabled { -1 } else { 0 };
+        let margins = MARGINS {
+            cxLeftWidth: extent,
+            cxRightWidth: extent,
+            cyTopHeight: extent,
+            cyBottomHeight: extent,
+        };
+        DwmExtendFrameIntoClientArea(window, &margins);
+
+        let backdrop = if enabled {
+            DWMSBT_TRANSIENTWINDOW
+        } else {
+            DWMSBT_NONE
+        };
+        DwmSetWindowAttribute(
+            window,
+            DWMWA_SYSTEMBACKDROP_TYPE,
+            &backdrop as *const u32 as *const _,
+            std::mem::size_of::<u32>() as u32,
+        );
+    }
+    true
+}
+
+// NSVisualEffectView placed behind the content view, inside the window's frame view
+#[cfg(target_os = "macos")]
+fn set_blur(title: &str, enabled: bool, radius: u8) -> bool {
+    use std::ffi::{CStr, c_char, c_void};
+
+    type Id = *mut c_void;
+    type Sel = *const c_void;
+
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    struct Rect {
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+    }
+
+    #[link(name = "objc")]
+    unsafe extern "C" {
+        fn objc_getClass(name: *const c_char) -> Id;
+        fn sel_registerName(name: *const c_char) -> Sel;
+        fn objc_msgSend();
+        // Structs this large come back through a hidden pointer on Intel
+        #[cfg(target_arch = "x86_64")]
+        fn objc_msgSend_stret();
+    }
+
+    // NSVisualEffectMaterialHUDWindow, NSVisualEffectBlendingModeBehindWindow, NSVisualEffectStateActive
+    const MATERIAL_HUD: isize = 13;
+    const BLENDING_BEHIND_WINDOW: isize = 0;
+    const STATE_ACTIVE: isize = 1;
+    // NSViewWidthSizable | NSViewHeightSizable
+    const RESIZE_WITH_PARENT: usize = (1 << 1) | (1 << 4);
+    const NS_WINDOW_BELOW: isize = -1;
+
+    unsafe {
+        let selector = |name: &CStr| sel_registerName(name.as_ptr());
+        let send = objc_msgSend as unsafe extern "C" fn();
+        #[cfg(target_arch = "x86_64")]
+        let send_rect: unsafe extern "C" fn(Id, Sel) -> Rect =
+            std::mem::transmute(objc_msgSend_stret as unsafe extern "C" fn());
+        #[cfg(not(target_arch = "x86_64"))]
+        let send_rect: unsafe extern "C" fn(Id, Sel) -> Rect = std::mem::transmute(send);
+        let send_id: unsafe extern "C" fn(Id, Sel) -> Id = std::mem::transmute(send);
+        let send_with: unsafe extern "C" fn(Id, Sel, Id) -> bool = std::mem::transmute(send);
+        let send_index: unsafe extern "C" fn(Id, Sel, usize) -> Id = std::mem::transmute(send);
+        let send_count: unsafe extern "C" fn(Id, Sel) -> usize = std::mem::transmute(send);
+        let send_frame: unsafe extern "C" fn(Id, Sel, Rect) -> Id = std::mem::transmute(send);
+        let send_set_integer: unsafe extern "C" fn(Id, Sel, isize) = std::mem::transmute(send);
+        let send_set_mask: unsafe extern "C" fn(Id, Sel, usize) = std::mem::transmute(send);
+        let send_set_bool: unsafe extern "C" fn(Id, Sel, bool) = std::mem::transmute(send);
+        let send_set_float: unsafe extern "C" fn(Id, Sel, f64) = std::mem::transmute(send);
+        let send_add: unsafe extern "C" fn(Id, Sel, Id, isize, Id) = std::mem::transmute(send);
+        let send_plain: unsafe extern "C" fn(Id, Sel) = std::mem::transmute(send);
+        let send_str: unsafe extern "C" fn(Id, Sel) -> *const c_char = std::mem::transmute(send);
+
+        let app = send_id(
+            objc_getClass(c"NSApplication".as_ptr()),
+            selector(c"sharedApplication"),
+        );
+        let effect_class = objc_getClass(c"NSVisualEffectView".as_ptr());
+        let windows = send_id(app, selector(c"windows"));
+        for index in 0..send_count(windows, selector(c"count")) {
+            let window = send_index(windows, selector(c"objectAtIndex:"), index);
+            let window_title = send_id(window, selector(c"title"));
+            if window_title.is_null()
+                || CStr::from_ptr(send_str(window_title, selector(c"UTF8String"))).to_bytes()
+                    != title.as_bytes()
+            {
+                continue;
+            }
+
+            // Drawing happens in the content view's own layer, which would cover a subview,
+            // so the effect view goes next to it in the frame view
+            let content = send_id(window, selector(c"contentView"));
+            let frame_view = send_id(content, selector(c"superview"));
+
+            let subviews = send_id(frame_view, selector(c"subviews"));
+            for view in (0..send_count(subviews, selector(c"count")))
+                .map(|index| send_index(subviews, selector(c"objectAtIndex:"), index))
+                .collect::<Vec<_>>()
+            {
+                if send_with(view, selector(c"isKindOfClass:"), effect_class) {
+                    send_plain(view, selector(c"removeFromSuperview"));
+                }
+            }
+            if !enabled {
+                return true;
+            }
+
+            let effect = send_id(effect_class, selector(c"alloc"));
+            let effect = send_frame(
+                effect,
+                selector(c"initWithFrame:"),
+                send_rect(content, selector(c"frame")),
+            );
+            send_set_integer(effect, selector(c"setMaterial:"), MATERIAL_HUD);
+            send_set_integer(
+                effect,
+                selector(c"setBlendingMode:"),
+                BLENDING_BEHIND_WINDOW,
+            );
+            send_set_integer(effect, selector(c"setState:"), STATE_ACTIVE);
+            send_set_mask(
+                effect,
+                selector(c"setAutoresizingMask:"),
+                RESIZE_WITH_PARENT,
+            );
+
+            // Match the panel's rounded corners
+            send_set_bool(effect, selector(c"setWantsLayer:"), true);
+            let layer = send_id(effect, selector(c"layer"));
+            send_set_float(layer, selector(c"setCornerRadius:"), radius as f64);
+            send_set_bool(layer, selector(c"setMasksToBounds:"), true);
+
+            send_add(
+                frame_view,
+                selector(c"addSubview:positioned:relativeTo:"),
+                effect,
+                NS_WINDOW_BELOW,
+                content,
+            );
+            send_plain(effect, selector(c"release"));
+            return true;
+        }
+        false
+    }
+}
+
+// KWin blurs X11 windows that carry this property; the Wayland blur protocol needs the
+// surface, which winit keeps to itself
+#[cfg(target_os = "linux")]
+fn set_blur(title: &str, enabled: bool, _radius: u8) -> bool {
+    use std::process::{Command, Stdio};
+
+    const PROPERTY: &str = "_KDE_NET_WM_BLUR_BEHIND_REGION";
+
+    if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        eprintln!("Background blur is only supported on X11 under Linux");
+        return true;
+    }
+
+    let mut command = Command::new("xprop");
+    command.args(["-name", title]);
+    if enabled {
+        // A lone 0 instead of a list of rectangles blurs the whole window
+        command.args(["-f", PROPERTY, "32c", "-set", PROPERTY, "0"]);
+    } else {
+        command.args(["-remove", PROPERTY]);
+    }
+    match command.stdout(Stdio::null()).stderr(Stdio::null()).status() {
+        Ok(status) => status.success(),
+        Err(error) => {
+            eprintln!(
+                "Error setting background blur (is xprop installed?): {}",
+                error
+            );
+            true
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+fn set_blur(_title: &str, _enabled: bool, _radius: u8) -> bool {
+    true
+}
+
+// The X11 counterpart of a layer-shell exclusive zone, which winit doesn't offer on Wayland
+#[cfg(target_os = "linux")]
+fn set_strut(title: &str, strut: Option<Strut>) -> bool {
+    use std::process::{Command, Stdio};
+
+    if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        eprintln!("Reserving screen space for the bar is only supported on X11 under Linux");
+        return true;
+    }
+
+    let mut command = Command::new("xprop");
+    command.args(["-name", title]);
+    match strut {
+        // left, right, top, bottom, then the start and end of each: left_start_y, left_end_y,
+        // right_start_y, right_end_y, top_start_x, top_end_x, bottom_start_x, bottom_end_x
+        Some(strut) => {
+            let (top, bottom) = if strut.top {
+                (strut.thickness, 0)
+            } else {
+                (0, strut.thickness)
+            };
+            let (start, end) = (strut.start, strut.end);
+            let partial = if strut.top {
+                format!("0,0,{top},0,0,0,0,0,{start},{end},0,0")
+            } else {
+                format!("0,0,0,{bottom},0,0,0,0,0,0,{start},{end}")
+            };
+            command.args(["-f", "_NET_WM_STRUT_PARTIAL", "32c"]);
+            command.args(["-set", "_NET_WM_STRUT_PARTIAL", &partial]);
+            command.args(["-f", "_NET_WM_STRUT", "32c"]);
+            command.args(["-set", "_NET_WM_STRUT", &format!("0,0,{top},{bottom}")]);
+        }
+        None => {
+            command.args([
+                "-remove",
+                "_NET_WM_STRUT_PARTIAL",
+                "-remove",
+                "_NET_WM_STRUT",
+            ]);
+        }
+    }
+    match command.stdout(Stdio::null()).stderr(Stdio::null()).status() {
+        Ok(status) => status.success(),
+        Err(error) => {
+            eprintln!(
+                "Error reserving screen space (is xprop installed?): {}",
+                error
+            );
+            true
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn set_strut(_title: &str, _strut: Option<Strut>) -> bool {
+    eprintln!("Reserving screen space for the bar is only supported on X11 under Linux");
+    true
+}
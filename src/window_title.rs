@@ -0,0 +1,44 @@
+use crate::config::{Corner, ThemeConfig, WindowTitleConfig};
+use crate::focus;
+use crate::ui;
+use eframe::egui;
+
+// What the element shows now; None when it is off, the title is unknown or hidden for privacy
+pub fn text(config: &WindowTitleConfig) -> Option<String> {
+    if !config.show {
+        return None;
+    }
+    let title = focus::focused_title().filter(|title| !title.trim().is_empty())?;
+    if config.hide.iter().any(|pattern| pattern.0.is_match(&title)) {
+        return None;
+    }
+    let title = config.redact.iter().fold(title, |title, pattern| {
+        pattern.0.replace_all(&title, "•••").into_owned()
+    });
+    let text = match focus::focused_app().filter(|_| config.show_app) {
+        Some(app) => format!("{} · {}", app, title),
+        None => title,
+    };
+    Some(truncate(&text, config.max_length))
+}
+
+// Draw the title in its corner of the panel with the theme's chip colors
+pub fn paint(ui: &egui::Ui, config: &WindowTitleConfig, theme: &ThemeConfig, text: &str) {
+    let rect = ui.max_rect();
+    let (position, anchor) = match config.corner {
+        Corner::TopLeft => (rect.left_top(), egui::Align2::LEFT_TOP),
+        Corner::TopRight => (rect.right_top(), egui::Align2::RIGHT_TOP),
+        Corner::BottomLeft => (rect.left_bottom(), egui::Align2::LEFT_BOTTOM),
+        Corner::BottomRight => (rect.right_bottom(), egui::Align2::RIGHT_BOTTOM),
+    };
+    let (fill, _, color) = theme.idle_colors();
+    ui::badge(ui, position, anchor, text, config.font_size, fill, color);
+}
+
+fn truncate(text: &str, max_length: usize) -> String {
+    if text.chars().count() <= max_length {
+        return text.to_string();
+    }
+    let kept: String = text.chars().take(max_length.saturating_sub(1)).collect();
+    format!("{}…", kept.trim_end())
+}